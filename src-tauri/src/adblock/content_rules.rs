@@ -8,7 +8,9 @@
 /// directly for network-level interception.
 ///
 /// On Linux, WebKitGTK's `WebKitUserContentFilterStore` accepts the same JSON
-/// format as macOS's WKContentRuleList.
+/// format as macOS's WKContentRuleList. It is compiled once per rule set into
+/// a store under the app data directory and attached to each app webview's
+/// user content manager; see `refresh_filters` and `attach_filter`.
 
 /// Maximum number of rules Apple allows in a single WKContentRuleList.
 /// If we exceed this, rules must be split across multiple lists.
@@ -105,3 +107,413 @@ pub fn compile_and_add_rules(
         );
     }
 }
+
+/// Directory of the WebKitGTK filter store, under the app data directory.
+#[cfg(target_os = "linux")]
+const FILTER_STORE_DIR: &str = "adblock_content_filters";
+
+/// Prefix of the identifiers Orbly compiles filters under.
+#[cfg(target_os = "linux")]
+const FILTER_ID_PREFIX: &str = "orbly-adblock-";
+
+/// `WebKitPolicyError` code of a load blocked by a content filter. WebKitGTK
+/// reports it but does not name it in its public enum.
+#[cfg(target_os = "linux")]
+const POLICY_ERROR_BLOCKED_BY_CONTENT_FILTER: i32 = 104;
+
+#[cfg(target_os = "linux")]
+thread_local! {
+    /// Filter being compiled, so a refresh for the same rules while it
+    /// compiles does not compile them again.
+    static COMPILING: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Identifier to compile `json` under. It changes with the rules, so a rule
+/// set compiled in an earlier session is loaded from the store as is.
+#[cfg(target_os = "linux")]
+pub fn filter_identifier(json: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = hex::encode(Sha256::digest(json.as_bytes()));
+    format!("{}{}", FILTER_ID_PREFIX, &hash[..16])
+}
+
+/// Whether content-blocking JSON has no rules, e.g. when ad blocking is off
+/// globally. WebKit refuses to compile an empty list.
+#[cfg(any(target_os = "linux", test))]
+pub fn is_empty_rule_list(json: &str) -> bool {
+    json.trim() == "[]"
+}
+
+/// Compile the current content-blocking JSON into the filter store unless
+/// it is there already, then attach it to every app webview, replacing the
+/// filter each had. Called when the filter lists or custom rules change.
+#[cfg(target_os = "linux")]
+pub fn refresh_filters(app_handle: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let filter = app_handle
+        .state::<crate::adblock::engine::AdblockState>()
+        .content_filter();
+    let Some(((identifier, json), dir)) = filter.zip(filter_store_dir(app_handle)) else {
+        // Nothing to compile: take the filters off
+        attach_all(app_handle);
+        return;
+    };
+
+    let handle = app_handle.clone();
+    let _ = app_handle.run_on_main_thread(move || {
+        let already_compiling = COMPILING.with(|compiling| {
+            compiling.borrow_mut().replace(identifier.clone()).as_ref() == Some(&identifier)
+        });
+        if already_compiling {
+            return;
+        }
+        let store_dir = dir.clone();
+        let keep = identifier.clone();
+        gtk_filters::load(&dir, &identifier, Some(json), move |result| {
+            COMPILING.with(|compiling| {
+                let mut compiling = compiling.borrow_mut();
+                if compiling.as_ref() == Some(&keep) {
+                    *compiling = None;
+                }
+            });
+            match result {
+                Ok(_) => {
+                    log::info!("Content filter '{}' ready", keep);
+                    // Rules that changed meanwhile have their own filter to keep
+                    let current = handle
+                        .state::<crate::adblock::engine::AdblockState>()
+                        .content_filter_id();
+                    if current.as_deref() == Some(keep.as_str()) {
+                        gtk_filters::remove_stale(&store_dir, &keep);
+                    }
+                    attach_all(&handle);
+                }
+                Err(e) => log::warn!("Failed to compile content filter '{}': {}", keep, e),
+            }
+        });
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn attach_all(app_handle: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let config = app_handle
+        .state::<crate::config::manager::ConfigManager>()
+        .get_config();
+    for app in &config.apps {
+        attach_filter(app_handle, &app.id);
+    }
+}
+
+/// Attach the compiled filter to an app's webview if the app has ad blocking
+/// on, or take it off otherwise. A filter that is still being compiled is
+/// attached by `refresh_filters` once it is ready.
+#[cfg(target_os = "linux")]
+pub fn attach_filter(app_handle: &tauri::AppHandle, app_id: &str) {
+    use tauri::Manager;
+    use webkit2gtk::{UserContentManagerExt, WebViewExt};
+
+    let Some(webview) = app_handle.get_webview(app_id) else {
+        return;
+    };
+    let enabled = app_handle
+        .state::<crate::config::manager::ConfigManager>()
+        .get_app(app_id)
+        .is_some_and(|a| a.adblock_enabled);
+    let identifier = app_handle
+        .state::<crate::adblock::engine::AdblockState>()
+        .content_filter_id()
+        .filter(|_| enabled);
+    let dir = filter_store_dir(app_handle);
+
+    let _ = webview.with_webview(move |platform_webview| {
+        let Some(manager) = platform_webview.inner().user_content_manager() else {
+            return;
+        };
+        manager.remove_all_filters();
+        let (Some(identifier), Some(dir)) = (identifier, dir) else {
+            return;
+        };
+        gtk_filters::load(&dir, &identifier, None, move |result| {
+            if let Ok(filter) = result {
+                filter.add_to(&manager);
+            }
+        });
+    });
+}
+
+/// Count the loads an app's content filter blocks. WebKitGTK only reports
+/// the sub-resources it started loading before the filter stopped them.
+#[cfg(target_os = "linux")]
+pub fn count_blocked_loads(app_handle: &tauri::AppHandle, webview: &tauri::Webview, app_id: &str) {
+    use tauri::Manager;
+    use webkit2gtk::{PolicyError, WebResourceExt, WebViewExt};
+
+    let handle = app_handle.clone();
+    let app_id = app_id.to_string();
+    let _ = webview.with_webview(move |platform_webview| {
+        platform_webview
+            .inner()
+            .connect_resource_load_started(move |_, resource, _| {
+                let handle = handle.clone();
+                let app_id = app_id.clone();
+                resource.connect_failed(move |_, error| {
+                    if error.matches(PolicyError::__Unknown(
+                        POLICY_ERROR_BLOCKED_BY_CONTENT_FILTER,
+                    )) {
+                        handle
+                            .state::<crate::adblock::engine::AdblockState>()
+                            .increment_blocked(&app_id);
+                    }
+                });
+            });
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn filter_store_dir(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+    use tauri::Manager;
+    app_handle
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(FILTER_STORE_DIR))
+}
+
+/// Bindings to `WebKitUserContentFilterStore`, which the webkit2gtk crate
+/// leaves out. Everything here runs on the main thread.
+#[cfg(target_os = "linux")]
+mod gtk_filters {
+    use std::ffi::{CStr, CString};
+    use std::path::Path;
+    use std::ptr;
+
+    use webkit2gtk::ffi;
+    use webkit2gtk::gio::ffi as gio_ffi;
+    use webkit2gtk::glib::{self, gobject_ffi, prelude::ObjectType, translate::*};
+
+    use super::FILTER_ID_PREFIX;
+
+    /// A compiled filter, released on drop.
+    pub struct CompiledFilter(*mut ffi::WebKitUserContentFilter);
+
+    impl CompiledFilter {
+        pub fn add_to(&self, manager: &webkit2gtk::UserContentManager) {
+            // SAFETY: Both pointers are live; the manager takes its own reference.
+            unsafe {
+                ffi::webkit_user_content_manager_add_filter(manager.to_glib_none().0, self.0)
+            };
+        }
+    }
+
+    impl Drop for CompiledFilter {
+        fn drop(&mut self) {
+            // SAFETY: We own one reference, from load_finish or save_finish.
+            unsafe { ffi::webkit_user_content_filter_unref(self.0) };
+        }
+    }
+
+    type Then = Box<dyn FnOnce(Result<CompiledFilter, String>)>;
+
+    /// A load or save in flight.
+    struct Pending {
+        store: glib::Object,
+        identifier: CString,
+        /// JSON to compile if the store does not have the filter yet.
+        source: Option<glib::Bytes>,
+        then: Then,
+    }
+
+    fn open_store(dir: &Path) -> Option<glib::Object> {
+        let path = CString::new(dir.to_string_lossy().into_owned()).ok()?;
+        // SAFETY: Returns a new reference, which the Object takes over.
+        let store = unsafe { ffi::webkit_user_content_filter_store_new(path.as_ptr()) };
+        if store.is_null() {
+            return None;
+        }
+        Some(unsafe { from_glib_full(store as *mut gobject_ffi::GObject) })
+    }
+
+    fn store_ptr(store: &glib::Object) -> *mut ffi::WebKitUserContentFilterStore {
+        store.as_ptr() as *mut ffi::WebKitUserContentFilterStore
+    }
+
+    unsafe fn take_error(error: *mut glib::ffi::GError) -> String {
+        if error.is_null() {
+            return "unknown error".to_string();
+        }
+        let error: glib::Error = from_glib_full(error);
+        error.to_string()
+    }
+
+    /// Load the filter compiled under `identifier`, compiling `source` into
+    /// the store first if it is given and the filter is not there.
+    pub fn load(
+        dir: &Path,
+        identifier: &str,
+        source: Option<String>,
+        then: impl FnOnce(Result<CompiledFilter, String>) + 'static,
+    ) {
+        let (Some(store), Ok(identifier)) = (open_store(dir), CString::new(identifier)) else {
+            then(Err("cannot open the filter store".to_string()));
+            return;
+        };
+        let pending = Box::new(Pending {
+            store,
+            identifier,
+            source: source.map(|json| glib::Bytes::from_owned(json.into_bytes())),
+            then: Box::new(then),
+        });
+        // SAFETY: `pending` is handed to the callback, which frees it.
+        unsafe {
+            ffi::webkit_user_content_filter_store_load(
+                store_ptr(&pending.store),
+                pending.identifier.as_ptr(),
+                ptr::null_mut(),
+                Some(on_loaded),
+                Box::into_raw(pending) as glib::ffi::gpointer,
+            );
+        }
+    }
+
+    unsafe extern "C" fn on_loaded(
+        _source: *mut gobject_ffi::GObject,
+        result: *mut gio_ffi::GAsyncResult,
+        user_data: glib::ffi::gpointer,
+    ) {
+        let pending = Box::from_raw(user_data as *mut Pending);
+        let mut error = ptr::null_mut();
+        let filter = ffi::webkit_user_content_filter_store_load_finish(
+            store_ptr(&pending.store),
+            result,
+            &mut error,
+        );
+        if !filter.is_null() {
+            (pending.then)(Ok(CompiledFilter(filter)));
+            return;
+        }
+        let message = take_error(error);
+        let Some(source) = pending.source.as_ref() else {
+            (pending.then)(Err(message));
+            return;
+        };
+        let source = source.to_glib_none().0;
+        let store = store_ptr(&pending.store);
+        let identifier = pending.identifier.as_ptr();
+        // The bytes stay alive in `pending` until the save finishes
+        ffi::webkit_user_content_filter_store_save(
+            store,
+            identifier,
+            source,
+            ptr::null_mut(),
+            Some(on_saved),
+            Box::into_raw(pending) as glib::ffi::gpointer,
+        );
+    }
+
+    unsafe extern "C" fn on_saved(
+        _source: *mut gobject_ffi::GObject,
+        result: *mut gio_ffi::GAsyncResult,
+        user_data: glib::ffi::gpointer,
+    ) {
+        let pending = Box::from_raw(user_data as *mut Pending);
+        let mut error = ptr::null_mut();
+        let filter = ffi::webkit_user_content_filter_store_save_finish(
+            store_ptr(&pending.store),
+            result,
+            &mut error,
+        );
+        if filter.is_null() {
+            (pending.then)(Err(take_error(error)));
+        } else {
+            (pending.then)(Ok(CompiledFilter(filter)));
+        }
+    }
+
+    /// Remove filters compiled from earlier rule sets, keeping `keep`.
+    pub fn remove_stale(dir: &Path, keep: &str) {
+        let Some(store) = open_store(dir) else {
+            return;
+        };
+        let pending = Box::new((store, keep.to_string()));
+        // SAFETY: `pending` is handed to the callback, which frees it.
+        unsafe {
+            ffi::webkit_user_content_filter_store_fetch_identifiers(
+                store_ptr(&pending.0),
+                ptr::null_mut(),
+                Some(on_identifiers),
+                Box::into_raw(pending) as glib::ffi::gpointer,
+            );
+        }
+    }
+
+    unsafe extern "C" fn on_identifiers(
+        _source: *mut gobject_ffi::GObject,
+        result: *mut gio_ffi::GAsyncResult,
+        user_data: glib::ffi::gpointer,
+    ) {
+        let pending = Box::from_raw(user_data as *mut (glib::Object, String));
+        let (store, keep) = *pending;
+        let identifiers = ffi::webkit_user_content_filter_store_fetch_identifiers_finish(
+            store_ptr(&store),
+            result,
+        );
+        if identifiers.is_null() {
+            return;
+        }
+        let mut i = 0;
+        while !(*identifiers.add(i)).is_null() {
+            let identifier = CStr::from_ptr(*identifiers.add(i));
+            let name = identifier.to_string_lossy();
+            if name.starts_with(FILTER_ID_PREFIX) && name != keep {
+                ffi::webkit_user_content_filter_store_remove(
+                    store_ptr(&store),
+                    identifier.as_ptr(),
+                    ptr::null_mut(),
+                    Some(on_removed),
+                    ptr::null_mut(),
+                );
+            }
+            i += 1;
+        }
+        glib::ffi::g_strfreev(identifiers);
+    }
+
+    unsafe extern "C" fn on_removed(
+        source: *mut gobject_ffi::GObject,
+        result: *mut gio_ffi::GAsyncResult,
+        _user_data: glib::ffi::gpointer,
+    ) {
+        let _ = ffi::webkit_user_content_filter_store_remove_finish(
+            source as *mut ffi::WebKitUserContentFilterStore,
+            result,
+            ptr::null_mut(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_rule_list() {
+        assert!(is_empty_rule_list("[]"));
+        assert!(is_empty_rule_list(" []\n"));
+        assert!(!is_empty_rule_list(
+            r#"[{"trigger":{"url-filter":"ads"},"action":{"type":"block"}}]"#
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_filter_identifier_follows_rules() {
+        let a = filter_identifier("[1]");
+        assert!(a.starts_with(FILTER_ID_PREFIX));
+        assert_eq!(a.len(), FILTER_ID_PREFIX.len() + 16);
+        assert_eq!(a, filter_identifier("[1]"));
+        assert_ne!(a, filter_identifier("[2]"));
+    }
+}
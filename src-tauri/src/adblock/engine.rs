@@ -18,9 +18,15 @@ pub struct AdblockState {
     filter_rules_text: Mutex<Option<String>>,
     /// Custom user rules.
     custom_rules: Mutex<Vec<String>>,
-    /// Pre-computed content-blocking JSON (for macOS WKContentRuleList).
-    #[cfg(target_os = "macos")]
+    /// Pre-computed content-blocking JSON (for WKContentRuleList on macOS and
+    /// WebKitGTK content filters on Linux).
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     content_blocking_json: Mutex<Option<String>>,
+    /// Identifier of the WebKitGTK content filter compiled from
+    /// `content_blocking_json`, or `None` when there are no rules. Computed
+    /// once per rule set rather than for every webview.
+    #[cfg(target_os = "linux")]
+    content_filter_id: Mutex<Option<String>>,
 }
 
 #[allow(dead_code)]
@@ -31,8 +37,10 @@ impl AdblockState {
             blocked_counts: Mutex::new(HashMap::new()),
             filter_rules_text: Mutex::new(None),
             custom_rules: Mutex::new(Vec::new()),
-            #[cfg(target_os = "macos")]
+            #[cfg(any(target_os = "macos", target_os = "linux"))]
             content_blocking_json: Mutex::new(None),
+            #[cfg(target_os = "linux")]
+            content_filter_id: Mutex::new(None),
         }
     }
 
@@ -50,18 +58,29 @@ impl AdblockState {
         let new_engine = Engine::from_filter_set(filter_set, true);
         *self.engine.lock().expect("adblock engine lock") = Some(new_engine);
 
-        // Build content-blocking JSON on macOS
-        #[cfg(target_os = "macos")]
+        // Build content-blocking JSON for sub-resource blocking
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
         {
-            match self.build_content_blocking_json(rules_text, custom_rules) {
+            let json = match self.build_content_blocking_json(rules_text, custom_rules) {
                 Ok(json) => {
-                    *self.content_blocking_json.lock().expect("content blocking lock") = Some(json);
-                    log::info!("Content-blocking JSON generated for macOS");
+                    log::info!("Content-blocking JSON generated");
+                    Some(json)
                 }
                 Err(e) => {
                     log::warn!("Failed to generate content-blocking JSON: {}", e);
+                    None
                 }
+            };
+            let mut json_guard = self.content_blocking_json.lock().expect("content blocking lock");
+            #[cfg(target_os = "linux")]
+            {
+                use crate::adblock::content_rules::{filter_identifier, is_empty_rule_list};
+                *self.content_filter_id.lock().expect("content filter lock") = json
+                    .as_deref()
+                    .filter(|json| !is_empty_rule_list(json))
+                    .map(filter_identifier);
             }
+            *json_guard = json;
         }
 
         log::info!("Adblock engine loaded and cached");
@@ -95,13 +114,30 @@ impl AdblockState {
             .collect()
     }
 
-    /// Generate WKContentRuleList JSON (macOS only).
-    #[cfg(target_os = "macos")]
+    /// Content-blocking JSON for WKContentRuleList (macOS) and
+    /// WebKitUserContentFilterStore (Linux).
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     pub fn get_content_blocking_json(&self) -> Option<String> {
         self.content_blocking_json.lock().expect("content blocking lock").clone()
     }
 
-    #[cfg(target_os = "macos")]
+    /// Identifier of the current content filter, cheap enough to ask for
+    /// every webview.
+    #[cfg(target_os = "linux")]
+    pub fn content_filter_id(&self) -> Option<String> {
+        self.content_filter_id.lock().expect("content filter lock").clone()
+    }
+
+    /// The current content filter's identifier with the JSON to compile it
+    /// from, read together so they always match.
+    #[cfg(target_os = "linux")]
+    pub fn content_filter(&self) -> Option<(String, String)> {
+        let json = self.content_blocking_json.lock().expect("content blocking lock");
+        let id = self.content_filter_id.lock().expect("content filter lock");
+        id.clone().zip(json.clone())
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     fn build_content_blocking_json(
         &self,
         rules_text: &str,
@@ -179,4 +215,33 @@ mod tests {
         state.load_rules("", &custom);
         assert!(state.should_block("https://custom-block.test/ad.js", "https://example.com", "script"));
     }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn test_content_blocking_json() {
+        let state = AdblockState::new();
+        state.load_rules("||ads.example.com^", &[]);
+        let json = state.get_content_blocking_json().unwrap();
+        assert!(json.contains("ads\\\\.example\\\\.com"));
+
+        state.load_rules("", &[]);
+        assert_eq!(state.get_content_blocking_json().as_deref(), Some("[]"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_content_filter_id() {
+        use crate::adblock::content_rules::filter_identifier;
+
+        let state = AdblockState::new();
+        state.load_rules("||ads.example.com^", &[]);
+        let (id, json) = state.content_filter().unwrap();
+        assert_eq!(id, filter_identifier(&json));
+        assert_eq!(state.content_filter_id(), Some(id));
+
+        // No rules, no filter
+        state.load_rules("", &[]);
+        assert_eq!(state.content_filter_id(), None);
+        assert_eq!(state.content_filter(), None);
+    }
 }
@@ -0,0 +1,76 @@
+//! Switching between the accounts of an app.
+//!
+//! Every account has its own data store, so switching rebuilds the app's
+//! webview on the other store. Only the signed-in account's page is loaded;
+//! the others keep the badge they last showed.
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::session_state::SessionState;
+use super::state::{AppManager, AppRuntimeState};
+use super::{lifecycle, set_app_state};
+use crate::config::manager::ConfigManager;
+
+/// Sign `app_id` in with `account_id` ("" for the default account).
+pub fn switch_account(app_handle: &AppHandle, app_id: &str, account_id: &str) -> Result<(), String> {
+    let config_manager = app_handle.state::<ConfigManager>();
+    let app_config = config_manager
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    if !account_id.is_empty() && !app_config.accounts.iter().any(|a| a.id == account_id) {
+        return Err(format!("App '{}' has no account '{}'", app_id, account_id));
+    }
+    if app_config.active_account == account_id {
+        return Ok(());
+    }
+
+    config_manager
+        .update_with("switch_app_account", |config| {
+            if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
+                app.active_account = account_id.to_string();
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    let app_config = config_manager
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+
+    let app_manager = app_handle.state::<AppManager>();
+    let runtime = app_manager.get_state(app_id);
+    if app_handle.get_webview(app_id).is_none() {
+        // Hibernated or disabled: the account is used when the app next loads
+        forget_snapshot(app_handle, app_id);
+        if matches!(
+            runtime.map(|r| r.state),
+            Some(AppRuntimeState::Hibernated { .. })
+        ) {
+            set_app_state(
+                app_handle,
+                app_id,
+                AppRuntimeState::Hibernated {
+                    last_url: app_config.url.clone(),
+                },
+                "account switched",
+            );
+        }
+    } else {
+        let visible = runtime.is_some_and(|r| r.is_visible);
+        let _ = lifecycle::destroy_app_webview(app_handle, app_id);
+        forget_snapshot(app_handle, app_id);
+        lifecycle::reopen_app_webview(app_handle, &app_config, visible, "account switched")?;
+    }
+
+    crate::tray::rebuild_tray_menu(app_handle);
+    let _ = app_handle.emit(
+        "app-account-switched",
+        serde_json::json!({ "appId": app_id, "accountId": account_id }),
+    );
+    Ok(())
+}
+
+/// Where the other account was is no use to this one.
+fn forget_snapshot(app_handle: &AppHandle, app_id: &str) {
+    if let Some(session_state) = app_handle.try_state::<SessionState>() {
+        session_state.remove_snapshot(app_id);
+    }
+}
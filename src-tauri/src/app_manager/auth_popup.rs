@@ -0,0 +1,310 @@
+//! Sign-in popups.
+//!
+//! When an app opens an identity provider's page with `window.open`, the page
+//! is shown in a short-lived window labelled `auth-popup-<id>` that shares the
+//! app's data store and user agent, so the session it sets up lands in the
+//! app. The popup is a separate webview and cannot be a real `window.opener`
+//! relation; scripts on both sides stand in for it, carrying `postMessage`,
+//! `close` and `closed` across through commands. The popup closes itself
+//! shortly after the flow redirects back to the app's origin.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, WebviewUrl};
+
+use super::data_store;
+use crate::config::manager::ConfigManager;
+use crate::config::models::LinkRoutingRule;
+use crate::utils::wildcard_match;
+
+const LABEL_PREFIX: &str = "auth-popup-";
+
+/// Sign-in pages of common identity providers.
+pub const AUTH_URL_PATTERNS: &[&str] = &[
+    "*://accounts.google.com/*",
+    "*://login.microsoftonline.com/*",
+    "*://login.live.com/*",
+    "*://github.com/login/*",
+    "*://appleid.apple.com/auth/*",
+    "*://www.facebook.com/*/dialog/oauth*",
+    "*://api.twitter.com/oauth/*",
+    "*://discord.com/oauth2/*",
+    "*://slack.com/oauth/*",
+];
+
+/// Open sign-in popups, by popup id, with the app that opened each.
+pub struct AuthPopups {
+    openers: Mutex<HashMap<String, String>>,
+}
+
+impl AuthPopups {
+    pub fn new() -> Self {
+        Self {
+            openers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The app that opened `popup_id`.
+    pub fn opener(&self, popup_id: &str) -> Option<String> {
+        self.openers
+            .lock()
+            .expect("auth popups lock")
+            .get(popup_id)
+            .cloned()
+    }
+}
+
+/// Whether `url` is a sign-in page to open in a popup. The first
+/// link-routing rule that matches decides; the catch-all `*` rule does not
+/// override the known identity providers.
+pub fn is_auth_url(rules: &[LinkRoutingRule], url: &str) -> bool {
+    match rules.iter().find(|rule| wildcard_match(&rule.pattern, url)) {
+        Some(rule) if rule.target == "popup" => true,
+        Some(rule) if rule.pattern.trim() != "*" => false,
+        _ => AUTH_URL_PATTERNS
+            .iter()
+            .any(|pattern| wildcard_match(pattern, url)),
+    }
+}
+
+/// Label of the window showing `popup_id`.
+pub fn window_label(popup_id: &str) -> String {
+    format!("{LABEL_PREFIX}{popup_id}")
+}
+
+/// The popup a window shows, or `None` for other windows.
+pub fn popup_id_of(window_label: &str) -> Option<&str> {
+    window_label
+        .strip_prefix(LABEL_PREFIX)
+        .filter(|id| valid_popup_id(id))
+}
+
+/// Popup ids are chosen by the page, so keep them to safe label characters.
+fn valid_popup_id(popup_id: &str) -> bool {
+    !popup_id.is_empty()
+        && popup_id.len() <= 32
+        && popup_id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Show `url` in a sign-in popup of `app_id`. `popup_id` is the id the page
+/// knows the popup by, if it opened it with `window.open`.
+pub fn open(
+    app_handle: &AppHandle,
+    app_id: &str,
+    url: &str,
+    popup_id: Option<String>,
+) -> Result<(), String> {
+    let app_config = app_handle
+        .state::<ConfigManager>()
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    let popup_id = match popup_id {
+        Some(id) if valid_popup_id(&id) => id,
+        Some(_) => return Err("Invalid popup id".to_string()),
+        None => uuid::Uuid::new_v4().simple().to_string(),
+    };
+    let label = window_label(&popup_id);
+    if app_handle.get_webview_window(&label).is_some() {
+        return Ok(());
+    }
+
+    let parsed: url::Url = url.parse().map_err(|e| format!("Invalid URL: {e}"))?;
+    let opener_url = app_handle
+        .get_webview(app_id)
+        .and_then(|webview| webview.url().ok())
+        .or_else(|| app_config.url.parse().ok());
+    let opener_origin = opener_url
+        .map(|u| u.origin().ascii_serialization())
+        .unwrap_or_default();
+
+    let mut builder =
+        tauri::WebviewWindowBuilder::new(app_handle, &label, WebviewUrl::External(parsed))
+            .title(format!("Sign in - {}", app_config.name))
+            .inner_size(520.0, 700.0)
+            .data_store_identifier(data_store::live_store(app_handle, &app_config).into_bytes())
+            .initialization_script(&popup_script(&popup_id, &opener_origin));
+    if !app_config.user_agent.is_empty() {
+        builder = builder.user_agent(&app_config.user_agent);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to open sign-in window: {e}"))?;
+
+    app_handle
+        .state::<AuthPopups>()
+        .openers
+        .lock()
+        .expect("auth popups lock")
+        .insert(popup_id, app_id.to_string());
+    log::info!("Opened sign-in popup for '{}'", app_id);
+    Ok(())
+}
+
+/// Deliver a popup event to the page of the app that opened it. `kind` is
+/// "message" or "closed".
+pub fn notify_opener(
+    app_handle: &AppHandle,
+    app_id: &str,
+    popup_id: &str,
+    kind: &str,
+    data: &serde_json::Value,
+    origin: &str,
+) {
+    let Some(webview) = app_handle.get_webview(app_id) else {
+        return;
+    };
+    let args = serde_json::json!([popup_id, kind, data, origin]);
+    let _ = webview.eval(format!(
+        "window.__orblyPopupEvent && window.__orblyPopupEvent.apply(null, {args})"
+    ));
+}
+
+/// Deliver a message from the opener to the popup's page.
+pub fn post_to_popup(app_handle: &AppHandle, popup_id: &str, data: &serde_json::Value) {
+    if let Some(window) = app_handle.get_webview_window(&window_label(popup_id)) {
+        let _ = window.eval(format!(
+            "window.__orblyOpenerMessage && window.__orblyOpenerMessage({data})"
+        ));
+    }
+}
+
+pub fn close(app_handle: &AppHandle, popup_id: &str) {
+    if let Some(window) = app_handle.get_webview_window(&window_label(popup_id)) {
+        let _ = window.close();
+    }
+}
+
+/// Close the popups an app opened, e.g. when its webview goes away.
+pub fn close_for_app(app_handle: &AppHandle, app_id: &str) {
+    let Some(popups) = app_handle.try_state::<AuthPopups>() else {
+        return;
+    };
+    let ids: Vec<String> = popups
+        .openers
+        .lock()
+        .expect("auth popups lock")
+        .iter()
+        .filter(|(_, opener)| *opener == app_id)
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in ids {
+        close(app_handle, &id);
+    }
+}
+
+/// Close every popup, e.g. when Orbly quits.
+pub fn close_all(app_handle: &AppHandle) {
+    for label in app_handle.windows().into_keys() {
+        if let Some(popup_id) = popup_id_of(&label) {
+            close(app_handle, popup_id);
+        }
+    }
+}
+
+/// Handle an event of a popup window.
+pub fn on_window_event(window: &tauri::Window, popup_id: &str, event: &tauri::WindowEvent) {
+    if let tauri::WindowEvent::Destroyed = event {
+        let app_handle = window.app_handle();
+        let opener = app_handle
+            .state::<AuthPopups>()
+            .openers
+            .lock()
+            .expect("auth popups lock")
+            .remove(popup_id);
+        if let Some(app_id) = opener {
+            notify_opener(
+                app_handle,
+                &app_id,
+                popup_id,
+                "closed",
+                &serde_json::Value::Null,
+                "",
+            );
+        }
+    }
+}
+
+/// Script run in the popup, standing in for `window.opener`.
+fn popup_script(popup_id: &str, opener_origin: &str) -> String {
+    let opener_origin = serde_json::to_string(opener_origin).unwrap_or_default();
+    format!(
+        r#"
+(function() {{
+    'use strict';
+    var POPUP_ID = '{popup_id}';
+    var OPENER_ORIGIN = {opener_origin};
+
+    function invoke(cmd, args) {{
+        if (window.__TAURI_INTERNALS__) {{
+            window.__TAURI_INTERNALS__.invoke(cmd, args).catch(function() {{}});
+        }}
+    }}
+
+    var opener = {{
+        closed: false,
+        postMessage: function(data) {{
+            invoke('auth_popup_message', {{ popup_id: POPUP_ID, data: data }});
+        }},
+        focus: function() {{}},
+        close: function() {{}}
+    }};
+    try {{
+        Object.defineProperty(window, 'opener', {{ get: function() {{ return opener; }}, configurable: true }});
+    }} catch (e) {{}}
+    window.close = function() {{
+        invoke('close_auth_popup', {{ popup_id: POPUP_ID }});
+    }};
+
+    window.__orblyOpenerMessage = function(data) {{
+        var event = new MessageEvent('message', {{ data: data, origin: OPENER_ORIGIN }});
+        try {{ Object.defineProperty(event, 'source', {{ value: opener }}); }} catch (e) {{}}
+        window.dispatchEvent(event);
+    }};
+
+    // Back at the app: the flow is over once the page has said its piece
+    if (location.origin === OPENER_ORIGIN) {{
+        window.addEventListener('load', function() {{
+            setTimeout(window.close, 1500);
+        }});
+    }}
+}})();
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, target: &str) -> LinkRoutingRule {
+        LinkRoutingRule {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_auth_urls_follow_link_routing_rules() {
+        let google = "https://accounts.google.com/o/oauth2/v2/auth?client_id=1";
+        let sso = "https://sso.example.com/login";
+
+        // Known providers get past the catch-all rule
+        let defaults = vec![rule("*", "external")];
+        assert!(is_auth_url(&defaults, google));
+        assert!(!is_auth_url(&defaults, sso));
+        assert!(!is_auth_url(&defaults, "https://www.google.com/search?q=x"));
+
+        // Rules can add a provider or send a known one elsewhere
+        let custom = vec![
+            rule("*://sso.example.com/*", "popup"),
+            rule("*://accounts.google.com/*", "external"),
+            rule("*", "external"),
+        ];
+        assert!(is_auth_url(&custom, sso));
+        assert!(!is_auth_url(&custom, google));
+
+        assert_eq!(popup_id_of(&window_label("a1b2")), Some("a1b2"));
+        assert_eq!(popup_id_of("auth-popup-../x"), None);
+    }
+}
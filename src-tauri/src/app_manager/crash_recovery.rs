@@ -0,0 +1,182 @@
+//! Automatic recovery of crashed apps.
+//!
+//! When the crash detector marks an app crashed, its webview is rebuilt at the
+//! last URL it showed, after a delay that doubles with each recent crash. An
+//! app that crashes `CRASH_LOOP_LIMIT` times within `CRASH_LOOP_WINDOW` is
+//! parked in the Error state instead, so a page that crashes on load does not
+//! keep reloading forever.
+
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::state::{AppManager, AppRuntimeState, ContentBounds, StateKind};
+use super::{lifecycle, set_app_state, set_app_state_if};
+use crate::config::manager::ConfigManager;
+use crate::config::models::CrashRecovery;
+
+/// Crashes within `CRASH_LOOP_WINDOW` that make a crash loop.
+pub const CRASH_LOOP_LIMIT: usize = 3;
+pub const CRASH_LOOP_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Longest wait before a reload, however often the app has crashed.
+const MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, PartialEq)]
+pub enum RecoveryAction {
+    /// Rebuild the webview after this long
+    Reload(Duration),
+    /// Stop recovering and show the crash loop to the user
+    Park,
+    /// Leave the app crashed
+    Leave,
+}
+
+/// Decide what to do about an app with `recent_crashes` crashes (including
+/// the one just seen) within the crash-loop window.
+pub fn plan(policy: CrashRecovery, recent_crashes: usize) -> RecoveryAction {
+    let base = match policy {
+        CrashRecovery::Reload => Duration::from_secs(2),
+        CrashRecovery::Delayed => Duration::from_secs(30),
+        CrashRecovery::Off => return RecoveryAction::Leave,
+    };
+    if recent_crashes >= CRASH_LOOP_LIMIT {
+        return RecoveryAction::Park;
+    }
+    let doublings = recent_crashes.saturating_sub(1).min(16) as u32;
+    RecoveryAction::Reload(base.saturating_mul(1 << doublings).min(MAX_DELAY))
+}
+
+/// Count a crash of `app_id` and recover from it according to the app's
+/// policy. `last_url` is where the app was when it crashed.
+pub fn handle_crash(app_handle: &AppHandle, app_id: &str, last_url: Option<String>, cause: &str) {
+    let app_manager = app_handle.state::<AppManager>();
+    let recent = app_manager.record_crash(app_id, CRASH_LOOP_WINDOW);
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let Some(app_config) = config.apps.iter().find(|a| a.id == app_id) else {
+        return;
+    };
+
+    match plan(app_config.crash_recovery, recent) {
+        RecoveryAction::Leave => {}
+        RecoveryAction::Park => {
+            let message = format!(
+                "Crashed {} times in {} minutes (last cause: {}). Automatic recovery \
+                 has stopped; reload the app to try again.",
+                recent,
+                CRASH_LOOP_WINDOW.as_secs() / 60,
+                cause
+            );
+            log::warn!("App '{}' is in a crash loop: {}", app_id, message);
+            let _ = lifecycle::destroy_app_webview(app_handle, app_id);
+            if set_app_state_if(
+                app_handle,
+                app_id,
+                &[StateKind::Crashed],
+                AppRuntimeState::Error {
+                    message: message.clone(),
+                },
+                "crash loop",
+            ) {
+                let _ = app_handle.emit(
+                    "app-crash-loop",
+                    serde_json::json!({
+                        "appId": app_id,
+                        "crashes": recent,
+                        "message": message,
+                    }),
+                );
+            }
+        }
+        RecoveryAction::Reload(delay) => {
+            log::info!(
+                "Reloading crashed app '{}' in {}s (crash {} of {} before parking)",
+                app_id,
+                delay.as_secs(),
+                recent,
+                CRASH_LOOP_LIMIT
+            );
+            let app_handle = app_handle.clone();
+            let app_id = app_id.to_string();
+            let url = last_url
+                .filter(|u| !u.is_empty())
+                .unwrap_or_else(|| app_config.url.clone());
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                recover(&app_handle, &app_id, url);
+            });
+        }
+    }
+}
+
+/// Rebuild a crashed app's webview at `url`.
+fn recover(app_handle: &AppHandle, app_id: &str, url: String) {
+    // The user may have reloaded, hibernated or removed the app meanwhile
+    if !set_app_state_if(
+        app_handle,
+        app_id,
+        &[StateKind::Crashed],
+        AppRuntimeState::Loading {
+            target_url: url.clone(),
+        },
+        "crash recovery",
+    ) {
+        return;
+    }
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let Some(mut app_config) = config.apps.iter().find(|a| a.id == app_id).cloned() else {
+        return;
+    };
+    app_config.url = url;
+
+    let app_manager = app_handle.state::<AppManager>();
+    let visible = app_manager
+        .get_state(app_id)
+        .is_some_and(|runtime| runtime.is_visible);
+    // Give the new webview a fresh heartbeat window and load timeout
+    app_manager.touch_heartbeat(app_id);
+    app_manager.touch_interaction(app_id);
+
+    let _ = lifecycle::destroy_app_webview(app_handle, app_id);
+    let bounds = app_handle.state::<ContentBounds>().get();
+    let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
+    let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+    if let Err(e) = lifecycle::create_app_webview(app_handle, &app_config, position, size) {
+        log::error!("Failed to recover crashed app '{}': {}", app_id, e);
+        set_app_state(
+            app_handle,
+            app_id,
+            AppRuntimeState::Error { message: e },
+            "crash recovery failed",
+        );
+        return;
+    }
+    if !visible {
+        let _ = lifecycle::set_webview_visible(app_handle, app_id, false, None, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_until_crash_loop() {
+        assert_eq!(
+            plan(CrashRecovery::Reload, 1),
+            RecoveryAction::Reload(Duration::from_secs(2))
+        );
+        assert_eq!(
+            plan(CrashRecovery::Reload, 2),
+            RecoveryAction::Reload(Duration::from_secs(4))
+        );
+        assert_eq!(
+            plan(CrashRecovery::Delayed, 2),
+            RecoveryAction::Reload(Duration::from_secs(60))
+        );
+        assert_eq!(
+            plan(CrashRecovery::Reload, CRASH_LOOP_LIMIT),
+            RecoveryAction::Park
+        );
+        assert_eq!(plan(CrashRecovery::Off, 1), RecoveryAction::Leave);
+    }
+}
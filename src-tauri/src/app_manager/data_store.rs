@@ -0,0 +1,471 @@
+//! App data stores on disk.
+//!
+//! Every app, and every extra account of an app, keeps its cookies, caches
+//! and site storage in a directory named by its `data_store_uuid` under
+//! `<app data>/WebKit`. Sizes are read and stores cleared straight on disk,
+//! so hibernated apps are not woken for it. An app with a webview has it
+//! closed first and rebuilt afterwards, since the engine holds the files open.
+//!
+//! Ephemeral apps get a new store, unknown to the config, every time their
+//! webview is built; it is deleted when the webview goes away or Orbly quits.
+//! Stores left behind by a crash are orphans and go at the next startup.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use super::session_state::SessionState;
+use super::state::{AppManager, AppRuntimeState};
+use super::{lifecycle, set_app_state};
+use crate::config::manager::ConfigManager;
+use crate::config::models::{AppConfig, OrblyConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    Cookies,
+    Cache,
+    LocalStorage,
+    All,
+}
+
+/// On-disk size of one data store.
+#[derive(Clone, Debug, Serialize)]
+pub struct DataStoreSize {
+    pub app_id: String,
+    /// "" for the app's default account
+    pub account_id: String,
+    pub data_store_uuid: Uuid,
+    pub bytes: u64,
+}
+
+/// Throwaway stores of the ephemeral apps that have a webview, by app id.
+pub struct EphemeralStores {
+    stores: Mutex<HashMap<String, Uuid>>,
+}
+
+impl EphemeralStores {
+    pub fn new() -> Self {
+        Self {
+            stores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The store the app's webview is using, if the app is ephemeral.
+    pub fn get(&self, app_id: &str) -> Option<Uuid> {
+        self.stores
+            .lock()
+            .expect("ephemeral stores lock")
+            .get(app_id)
+            .copied()
+    }
+
+    fn in_use(&self, store: &Uuid) -> bool {
+        self.stores
+            .lock()
+            .expect("ephemeral stores lock")
+            .values()
+            .any(|s| s == store)
+    }
+
+    fn replace(&self, app_id: &str, store: Uuid) -> Option<Uuid> {
+        self.stores
+            .lock()
+            .expect("ephemeral stores lock")
+            .insert(app_id.to_string(), store)
+    }
+
+    fn take(&self, app_id: &str) -> Option<Uuid> {
+        self.stores
+            .lock()
+            .expect("ephemeral stores lock")
+            .remove(app_id)
+    }
+
+    fn take_all(&self) -> Vec<Uuid> {
+        self.stores
+            .lock()
+            .expect("ephemeral stores lock")
+            .drain()
+            .map(|(_, store)| store)
+            .collect()
+    }
+}
+
+/// The directory holding every data store.
+pub fn stores_root(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join("WebKit"))
+}
+
+/// Total size of the files under `path`.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| dir_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Which category a file or directory of a data store belongs to, judged
+/// by the names WebKit gives them on each platform.
+pub fn category_of(name: &str) -> Option<DataCategory> {
+    let name = name.to_lowercase();
+    if name.contains("cookie") {
+        Some(DataCategory::Cookies)
+    } else if name.contains("cache") {
+        Some(DataCategory::Cache)
+    } else if [
+        "localstorage",
+        "indexeddb",
+        "databases",
+        "websql",
+        "serviceworkers",
+        "storage",
+    ]
+    .iter()
+    .any(|n| name.contains(n))
+    {
+        Some(DataCategory::LocalStorage)
+    } else {
+        None
+    }
+}
+
+/// Delete the parts of the store at `dir` in `category`. Returns the bytes freed.
+pub fn clear_dir(dir: &Path, category: DataCategory) -> u64 {
+    if category == DataCategory::All {
+        let freed = dir_size(dir);
+        return match std::fs::remove_dir_all(dir) {
+            Ok(()) => freed,
+            Err(_) => 0,
+        };
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut freed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if category_of(&name) == Some(category) {
+            let size = dir_size(&path);
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            if removed.is_ok() {
+                freed += size;
+            }
+        } else if path.is_dir() && !path.is_symlink() {
+            // Per-origin storage lives a few levels down
+            freed += clear_dir(&path, category);
+        }
+    }
+    freed
+}
+
+/// Every data store `config` still uses.
+pub fn referenced_stores(config: &OrblyConfig) -> HashSet<Uuid> {
+    config
+        .apps
+        .iter()
+        .flat_map(|app| {
+            std::iter::once(app.data_store_uuid)
+                .chain(app.accounts.iter().map(|a| a.data_store_uuid))
+        })
+        .collect()
+}
+
+/// Stores under `root` that no app or account uses any more.
+pub fn orphaned_stores(root: &Path, referenced: &HashSet<Uuid>) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| {
+            Uuid::parse_str(&entry.file_name().to_string_lossy())
+                .is_ok_and(|uuid| !referenced.contains(&uuid))
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// The store to build the app's webview on: a fresh throwaway one for an
+/// ephemeral app, otherwise that of its current account.
+pub fn store_for_new_webview(app_handle: &AppHandle, app_config: &AppConfig) -> Uuid {
+    if !app_config.ephemeral {
+        return app_config.active_data_store();
+    }
+    let store = Uuid::new_v4();
+    if let Some(old) = app_handle
+        .state::<EphemeralStores>()
+        .replace(&app_config.id, store)
+    {
+        delete_store(app_handle, old);
+    }
+    store
+}
+
+/// The store the app's webview is using, or would use if it has none.
+pub fn live_store(app_handle: &AppHandle, app_config: &AppConfig) -> Uuid {
+    app_handle
+        .try_state::<EphemeralStores>()
+        .and_then(|stores| stores.get(&app_config.id))
+        .unwrap_or_else(|| app_config.active_data_store())
+}
+
+/// Delete the throwaway store of an app whose webview went away. A no-op for
+/// apps that are not ephemeral.
+pub fn discard_ephemeral_store(app_handle: &AppHandle, app_id: &str) {
+    let store = app_handle
+        .try_state::<EphemeralStores>()
+        .and_then(|stores| stores.take(app_id));
+    if let Some(store) = store {
+        delete_store(app_handle, store);
+    }
+}
+
+/// Delete every throwaway store, e.g. when Orbly quits.
+pub fn discard_all_ephemeral_stores(app_handle: &AppHandle) {
+    let Some(stores) = app_handle.try_state::<EphemeralStores>() else {
+        return;
+    };
+    for store in stores.take_all() {
+        delete_store(app_handle, store);
+    }
+}
+
+fn delete_store(app_handle: &AppHandle, store: Uuid) {
+    if let Some(root) = stores_root(app_handle) {
+        let freed = clear_dir(&root.join(store.to_string()), DataCategory::All);
+        log::info!("Removed ephemeral data store {} ({} bytes)", store, freed);
+    }
+}
+
+/// Size of every store used by an app or account.
+pub fn store_sizes(app_handle: &AppHandle) -> Vec<DataStoreSize> {
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let root = stores_root(app_handle);
+    let size = |uuid: &Uuid| {
+        root.as_ref()
+            .map(|root| dir_size(&root.join(uuid.to_string())))
+            .unwrap_or(0)
+    };
+    config
+        .apps
+        .iter()
+        .flat_map(|app| {
+            std::iter::once((String::new(), app.data_store_uuid))
+                .chain(
+                    app.accounts
+                        .iter()
+                        .map(|a| (a.id.clone(), a.data_store_uuid)),
+                )
+                .map(|(account_id, uuid)| DataStoreSize {
+                    app_id: app.id.clone(),
+                    account_id,
+                    data_store_uuid: uuid,
+                    bytes: size(&uuid),
+                })
+        })
+        .collect()
+}
+
+/// Delete `category` from the store of the app's current account. Returns
+/// the bytes freed.
+pub fn clear_app_data(
+    app_handle: &AppHandle,
+    app_id: &str,
+    category: DataCategory,
+) -> Result<u64, String> {
+    let app_config = app_handle
+        .state::<ConfigManager>()
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    let dir = stores_root(app_handle)
+        .ok_or("App data directory not found")?
+        .join(app_config.active_data_store().to_string());
+    // Without its cookies the page the app was on is likely gone
+    let signs_out = matches!(category, DataCategory::Cookies | DataCategory::All);
+    with_webview_closed(app_handle, app_id, signs_out, "data cleared", || {
+        clear_dir(&dir, category)
+    })
+}
+
+/// Move the app's current account to a fresh, empty data store and delete
+/// the old one.
+pub fn reset_app_data(app_handle: &AppHandle, app_id: &str) -> Result<u64, String> {
+    let config_manager = app_handle.state::<ConfigManager>();
+    let app_config = config_manager
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    let old_dir = stores_root(app_handle)
+        .ok_or("App data directory not found")?
+        .join(app_config.active_data_store().to_string());
+
+    with_webview_closed(app_handle, app_id, true, "data reset", || {
+        config_manager
+            .update_with("reset_app_data", |config| {
+                let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) else {
+                    return;
+                };
+                let account = app.active_account.clone();
+                match app.accounts.iter_mut().find(|a| a.id == account) {
+                    Some(account) => account.data_store_uuid = Uuid::new_v4(),
+                    None => app.data_store_uuid = Uuid::new_v4(),
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        Ok(clear_dir(&old_dir, DataCategory::All))
+    })?
+}
+
+/// Delete the stores no app or account uses, such as those of apps removed
+/// without deleting their data. Returns the bytes freed.
+pub fn clean_orphaned_stores(app_handle: &AppHandle) -> u64 {
+    let Some(root) = stores_root(app_handle) else {
+        return 0;
+    };
+    let referenced = referenced_stores(&app_handle.state::<ConfigManager>().get_config());
+    let ephemeral = app_handle.try_state::<EphemeralStores>();
+    orphaned_stores(&root, &referenced)
+        .iter()
+        // Ephemeral apps may build their webviews while this runs
+        .filter(|dir| {
+            !ephemeral.as_ref().is_some_and(|stores| {
+                dir.file_name()
+                    .and_then(|name| Uuid::parse_str(&name.to_string_lossy()).ok())
+                    .is_some_and(|store| stores.in_use(&store))
+            })
+        })
+        .map(|dir| {
+            let freed = clear_dir(dir, DataCategory::All);
+            log::info!(
+                "Removed unused data store {} ({} bytes)",
+                dir.display(),
+                freed
+            );
+            freed
+        })
+        .sum()
+}
+
+/// Run `f` on an app's store with its webview, if it has one, closed.
+/// With `signs_out` the app starts over at its configured URL.
+fn with_webview_closed<T>(
+    app_handle: &AppHandle,
+    app_id: &str,
+    signs_out: bool,
+    reason: &str,
+    f: impl FnOnce() -> T,
+) -> Result<T, String> {
+    let runtime = app_handle.state::<AppManager>().get_state(app_id);
+    let webview = app_handle.get_webview(app_id);
+    // Only `f` touches the store: the webview's own clearing takes every
+    // category, and on Linux every app's data with it
+    if webview.is_some() {
+        lifecycle::destroy_app_webview(app_handle, app_id)?;
+    }
+    if signs_out {
+        if let Some(session_state) = app_handle.try_state::<SessionState>() {
+            session_state.remove_snapshot(app_id);
+        }
+    }
+
+    let result = f();
+
+    // Re-read the config: `f` may have moved the app to another store
+    let app_config = app_handle
+        .state::<ConfigManager>()
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    if webview.is_some() {
+        let visible = runtime.is_some_and(|r| r.is_visible);
+        let mut app_config = app_config;
+        if !signs_out {
+            if let Some(AppRuntimeState::Active { current_url }) = runtime.map(|r| r.state) {
+                app_config.url = current_url;
+            }
+        }
+        lifecycle::reopen_app_webview(app_handle, &app_config, visible, reason)?;
+    } else if signs_out
+        && matches!(
+            runtime.map(|r| r.state),
+            Some(AppRuntimeState::Hibernated { .. })
+        )
+    {
+        set_app_state(
+            app_handle,
+            app_id,
+            AppRuntimeState::Hibernated {
+                last_url: app_config.url.clone(),
+            },
+            reason,
+        );
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("orbly-store-{}", Uuid::new_v4()));
+        for sub in [
+            "Cookies",
+            "NetworkCache",
+            "Origins/abc/abc/LocalStorage",
+            "Origins/abc/abc/IndexedDB",
+        ] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        std::fs::write(dir.join("Cookies/cookies.binarycookies"), [0u8; 100]).unwrap();
+        std::fs::write(dir.join("NetworkCache/blob"), [0u8; 1000]).unwrap();
+        std::fs::write(dir.join("Origins/abc/abc/LocalStorage/db"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("Origins/abc/abc/IndexedDB/db"), [0u8; 10]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_clear_dir_by_category() {
+        let dir = temp_store();
+        assert_eq!(dir_size(&dir), 1120);
+        assert_eq!(clear_dir(&dir, DataCategory::Cache), 1000);
+        assert_eq!(clear_dir(&dir, DataCategory::LocalStorage), 20);
+        assert!(dir.join("Origins/abc/abc").exists());
+        assert!(dir.join("Cookies/cookies.binarycookies").exists());
+        assert_eq!(clear_dir(&dir, DataCategory::Cookies), 100);
+        assert_eq!(dir_size(&dir), 0);
+        assert_eq!(clear_dir(&dir, DataCategory::All), 0);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_orphaned_stores_skip_used_and_foreign_dirs() {
+        let root = std::env::temp_dir().join(format!("orbly-stores-{}", Uuid::new_v4()));
+        let used = Uuid::new_v4();
+        let orphan = Uuid::new_v4();
+        for name in [used.to_string(), orphan.to_string(), "Default".to_string()] {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+        }
+        let orphans = orphaned_stores(&root, &HashSet::from([used]));
+        assert_eq!(orphans, vec![root.join(orphan.to_string())]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
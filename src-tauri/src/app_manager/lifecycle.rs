@@ -15,6 +15,10 @@ pub fn create_app_webview(
     position: tauri::LogicalPosition<f64>,
     size: tauri::LogicalSize<f64>,
 ) -> Result<(), String> {
+    if let Some(config_manager) = app_handle.try_state::<crate::config::manager::ConfigManager>() {
+        config_manager.policy().check_url(&app_config.url)?;
+    }
+
     let main_window = app_handle
         .get_window("main")
         .ok_or("Main window not found")?;
@@ -26,9 +30,10 @@ pub fn create_app_webview(
             .map_err(|e| format!("Invalid URL: {e}"))?,
     );
 
+    let data_store = crate::app_manager::data_store::store_for_new_webview(app_handle, app_config);
     let mut builder =
         tauri::webview::WebviewBuilder::new(&app_config.id, url)
-            .data_store_identifier(app_config.data_store_uuid.into_bytes())
+            .data_store_identifier(data_store.into_bytes())
             .auto_resize();
 
     if !app_config.user_agent.is_empty() {
@@ -38,8 +43,12 @@ pub fn create_app_webview(
     // Per-app proxy support
     // Note: On macOS, requires the "macos-proxy" feature and macOS 14+.
     if !app_config.proxy.is_empty() {
-        let proxy = url::Url::parse(&app_config.proxy)
-            .map_err(|e| format!("Invalid proxy URL: {e}"))?;
+        // Credentials are only resolved from the secret store here
+        let proxy = match app_handle.try_state::<crate::config::manager::ConfigManager>() {
+            Some(config_manager) => config_manager.secrets().resolve_proxy_url(app_config)?,
+            None => url::Url::parse(&app_config.proxy)
+                .map_err(|e| format!("Invalid proxy URL: {e}"))?,
+        };
         #[cfg(not(target_os = "macos"))]
         {
             builder = builder.proxy_url(proxy);
@@ -256,13 +265,25 @@ pub fn create_app_webview(
     // are set via tauri.conf.json bundle configuration.
     // Tauri/Wry does not currently expose on_permission_request() for WebView2/WKWebView.
 
-    let webview = main_window
+    // Detached apps fill a window of their own
+    let (host_window, position, size) = if app_config.detached {
+        let window = crate::app_manager::popout::host_window(app_handle, app_config)?;
+        let size = crate::app_manager::popout::content_size(&window);
+        (window, tauri::LogicalPosition::new(0.0, 0.0), size)
+    } else {
+        (main_window, position, size)
+    };
+    let webview = host_window
         .add_child(
             builder,
             tauri::LogicalPosition::from(position),
             tauri::LogicalSize::from(size),
         )
-        .map_err(|e| format!("Failed to create webview: {e}"))?;
+        .map_err(|e| {
+            crate::app_manager::popout::close_window(app_handle, &app_config.id);
+            crate::app_manager::data_store::discard_ephemeral_store(app_handle, &app_config.id);
+            format!("Failed to create webview: {e}")
+        })?;
 
     if app_handle
         .state::<crate::config::manager::ConfigManager>()
@@ -285,6 +306,12 @@ pub fn create_app_webview(
         }
     }
 
+    #[cfg(target_os = "linux")]
+    {
+        crate::adblock::content_rules::attach_filter(app_handle, &app_config.id);
+        crate::adblock::content_rules::count_blocked_loads(app_handle, &webview, &app_config.id);
+    }
+
     // Apply zoom level natively after webview creation
     if app_config.zoom_level != 100 {
         if let Some(webview) = app_handle.get_webview(&app_config.id) {
@@ -306,9 +333,14 @@ pub fn destroy_app_webview(
 ) -> Result<Option<String>, String> {
     let current_url = if let Some(webview) = app_handle.get_webview(app_id) {
         let url = webview.url().map(|u| u.to_string()).unwrap_or_default();
+        // The page's snapshot is the last one it reported: a report asked
+        // for now would arrive after the webview is gone
         webview
             .close()
             .map_err(|e| format!("Failed to close webview: {e}"))?;
+        crate::app_manager::popout::close_window(app_handle, app_id);
+        crate::app_manager::auth_popup::close_for_app(app_handle, app_id);
+        crate::app_manager::data_store::discard_ephemeral_store(app_handle, app_id);
         Some(url)
     } else {
         None
@@ -316,6 +348,84 @@ pub fn destroy_app_webview(
     Ok(current_url)
 }
 
+/// Build the webview of an app whose webview was just destroyed, at the
+/// app's configured URL, shown or hidden as it was.
+pub fn reopen_app_webview(
+    app_handle: &AppHandle,
+    app_config: &AppConfig,
+    visible: bool,
+    reason: &str,
+) -> Result<(), String> {
+    let app_manager = app_handle.state::<crate::app_manager::state::AppManager>();
+    crate::app_manager::set_app_state(
+        app_handle,
+        &app_config.id,
+        crate::app_manager::state::AppRuntimeState::Loading {
+            target_url: app_config.url.clone(),
+        },
+        reason,
+    );
+    app_manager.touch_heartbeat(&app_config.id);
+    app_manager.touch_interaction(&app_config.id);
+
+    let bounds = app_handle
+        .state::<crate::app_manager::state::ContentBounds>()
+        .get();
+    let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
+    let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+    if let Err(e) = create_app_webview(app_handle, app_config, position, size) {
+        crate::app_manager::set_app_state(
+            app_handle,
+            &app_config.id,
+            crate::app_manager::state::AppRuntimeState::Error { message: e.clone() },
+            reason,
+        );
+        return Err(e);
+    }
+    let _ = set_webview_visible(app_handle, &app_config.id, visible, None, None);
+    Ok(())
+}
+
+/// Whether a config change only takes effect when the webview is rebuilt.
+pub fn needs_webview_recreate(old: &AppConfig, new: &AppConfig) -> bool {
+    old.url != new.url
+        || old.user_agent != new.user_agent
+        || old.custom_css != new.custom_css
+        || old.custom_js != new.custom_js
+        || old.proxy != new.proxy
+        || old.proxy_credentials != new.proxy_credentials
+        || old.service_type != new.service_type
+        || old.active_data_store() != new.active_data_store()
+}
+
+/// Close and rebuild an app's webview, if it has one, with its current config.
+pub fn recreate_app_webview(app_handle: &AppHandle, app_config: &AppConfig) {
+    let Some(existing) = app_handle.get_webview(&app_config.id) else {
+        return;
+    };
+    let _ = existing.close();
+    if let Some(app_manager) = app_handle.try_state::<crate::app_manager::state::AppManager>() {
+        let content_bounds = app_handle.state::<crate::app_manager::state::ContentBounds>();
+        let bounds = content_bounds.get();
+        let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
+        let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+
+        crate::app_manager::set_app_state(
+            app_handle,
+            &app_config.id,
+            crate::app_manager::state::AppRuntimeState::Loading {
+                target_url: app_config.url.clone(),
+            },
+            "config changed",
+        );
+        app_manager.touch_interaction(&app_config.id);
+
+        if let Err(e) = create_app_webview(app_handle, app_config, position, size) {
+            log::error!("Failed to recreate webview for {}: {}", app_config.id, e);
+        }
+    }
+}
+
 /// Build the initialization script for an app webview.
 fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -> String {
     let mut scripts = Vec::new();
@@ -427,25 +537,63 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
         app_config.id
     ));
 
-    // Unsaved work detection (checks document.title periodically)
+    // Unsaved work detection: form fields or editable areas typed into and not
+    // yet submitted or emptied, and typing on a page with beforeunload handlers.
+    // The handlers are only tracked, never called: pages save drafts, send
+    // beacons or tear down state in them
     scripts.push(format!(
         r#"
 (function() {{
     'use strict';
     var ORBLY_APP_ID = '{}';
-    var lastUnsaved = false;
+    var lastUnsaved = null;
+    var unloadHandlers = [];
+    var edited = [];
+    var typed = false;
+    var pending = null;
+
+    var addListener = window.addEventListener;
+    var removeListener = window.removeEventListener;
+    window.addEventListener = function(type, handler) {{
+        if (type === 'beforeunload' && handler && unloadHandlers.indexOf(handler) === -1) {{
+            unloadHandlers.push(handler);
+        }}
+        return addListener.apply(this, arguments);
+    }};
+    window.removeEventListener = function(type, handler) {{
+        if (type === 'beforeunload') {{
+            var idx = unloadHandlers.indexOf(handler);
+            if (idx !== -1) unloadHandlers.splice(idx, 1);
+        }}
+        return removeListener.apply(this, arguments);
+    }};
+
+    function hasUnloadHandlers() {{
+        return unloadHandlers.length > 0 || typeof window.onbeforeunload === 'function';
+    }}
+
+    function isTracked(el) {{
+        if (!el || el.nodeType !== 1) return false;
+        if (el.isContentEditable) return true;
+        if (el.tagName === 'TEXTAREA') return true;
+        if (el.tagName !== 'INPUT') return false;
+        var type = (el.type || 'text').toLowerCase();
+        return ['text', 'email', 'url', 'tel', 'number'].indexOf(type) !== -1;
+    }}
+
+    function isDirty(el) {{
+        if (el.isContentEditable) return (el.textContent || '').trim() !== '';
+        return el.value !== el.defaultValue && el.value.trim() !== '';
+    }}
+
+    function formDirty() {{
+        edited = edited.filter(function(el) {{ return el.isConnected; }});
+        return edited.some(isDirty);
+    }}
 
     function checkUnsaved() {{
-        var title = document.title || '';
-        var unsaved = false;
-        if (title.charAt(0) === '*' || title.charAt(0) === '●') unsaved = true;
-        if (!unsaved) {{
-            var lower = title.toLowerCase();
-            var indicators = ['draft', 'editing', 'unsaved', 'composing', 'modified'];
-            for (var i = 0; i < indicators.length; i++) {{
-                if (lower.indexOf(indicators[i]) !== -1) {{ unsaved = true; break; }}
-            }}
-        }}
+        pending = null;
+        var unsaved = formDirty() || (typed && hasUnloadHandlers());
         if (unsaved !== lastUnsaved) {{
             lastUnsaved = unsaved;
             if (window.__TAURI_INTERNALS__) {{
@@ -457,8 +605,27 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
         }}
     }}
 
+    function checkSoon() {{
+        if (!pending) pending = setTimeout(checkUnsaved, 500);
+    }}
+
+    document.addEventListener('input', function(e) {{
+        var el = e.target;
+        // Typing into a child of an editable area edits the area
+        while (el && el.parentElement && el.parentElement.isContentEditable) el = el.parentElement;
+        if (isTracked(el) && edited.indexOf(el) === -1) edited.push(el);
+        typed = true;
+        checkSoon();
+    }}, true);
+    document.addEventListener('submit', function(e) {{
+        edited = edited.filter(function(el) {{ return !e.target.contains(el); }});
+        typed = false;
+        checkSoon();
+    }}, true);
+
     setInterval(checkUnsaved, 5000);
     document.addEventListener('visibilitychange', checkUnsaved);
+    checkUnsaved();
 }})();
 "#,
         app_config.id
@@ -666,6 +833,22 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
         app_config.id
     ));
 
+    // Back stack, scroll and form snapshot, replayed if one was saved for this
+    // page. Ephemeral apps leave nothing behind to replay.
+    if !app_config.ephemeral {
+        let snapshot = app_handle
+            .try_state::<crate::app_manager::session_state::SessionState>()
+            .and_then(|session_state| session_state.get_snapshot(&app_config.id))
+            .and_then(|snapshot| {
+                crate::app_manager::page_snapshot::sanitize(snapshot, app_config.restore_form_fields)
+            });
+        scripts.push(crate::app_manager::page_snapshot::snapshot_script(
+            &app_config.id,
+            app_config.restore_form_fields,
+            snapshot.as_ref(),
+        ));
+    }
+
     // Heartbeat for crash detection (only reported when document is visible)
     scripts.push(format!(
         r#"
@@ -684,56 +867,6 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
         app_config.id
     ));
 
-    // OAuth detection: open known OAuth URLs in system browser
-    scripts.push(format!(
-        r#"
-(function() {{
-    'use strict';
-    var oauthPatterns = [
-        'accounts.google.com/o/oauth',
-        'accounts.google.com/signin/oauth',
-        'login.microsoftonline.com',
-        'github.com/login/oauth',
-        'appleid.apple.com/auth',
-        'www.facebook.com/v',
-        'api.twitter.com/oauth',
-        'discord.com/oauth2',
-        'slack.com/oauth',
-        'login.live.com/oauth'
-    ];
-
-    var origAssign = Object.getOwnPropertyDescriptor(Location.prototype, 'href');
-    var observer = new MutationObserver(function() {{
-        var url = location.href;
-        for (var i = 0; i < oauthPatterns.length; i++) {{
-            if (url.indexOf(oauthPatterns[i]) !== -1) {{
-                if (window.__TAURI_INTERNALS__) {{
-                    window.__TAURI_INTERNALS__.invoke('open_in_external_browser', {{ url: url }}).catch(function() {{}});
-                    history.back();
-                }}
-                break;
-            }}
-        }}
-    }});
-
-    observer.observe(document.documentElement, {{ childList: true, subtree: true }});
-
-    window.addEventListener('load', function() {{
-        var url = location.href;
-        for (var i = 0; i < oauthPatterns.length; i++) {{
-            if (url.indexOf(oauthPatterns[i]) !== -1) {{
-                if (window.__TAURI_INTERNALS__) {{
-                    window.__TAURI_INTERNALS__.invoke('open_in_external_browser', {{ url: url }}).catch(function() {{}});
-                    history.back();
-                }}
-                break;
-            }}
-        }}
-    }});
-}})();
-"#
-    ));
-
     // Link interception for link routing
     scripts.push(format!(
         r#"
@@ -771,6 +904,48 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
         }} catch(ex) {{}}
     }}, true);
 
+    // Windows opened cross-origin, standing in for the real window objects.
+    // Sign-in pages open in a popup the host relays messages to and from.
+    var popups = {{}};
+    function openPopup(href) {{
+        var id = '';
+        for (var i = 0; i < 4; i++) id += Math.random().toString(36).slice(2, 8);
+        var popup = {{
+            closed: false,
+            close: function() {{
+                window.__TAURI_INTERNALS__.invoke('close_auth_popup', {{ popup_id: id }}).catch(function() {{}});
+            }},
+            focus: function() {{}},
+            blur: function() {{}},
+            postMessage: function(data) {{
+                if (popup.closed) return;
+                window.__TAURI_INTERNALS__.invoke('post_to_auth_popup', {{ popup_id: id, data: data }}).catch(function() {{}});
+            }}
+        }};
+        popups[id] = popup;
+        window.__TAURI_INTERNALS__.invoke('route_link', {{
+            url: href,
+            source_app_id: ORBLY_APP_ID,
+            popup_id: id
+        }}).catch(function() {{
+            popup.closed = true;
+            delete popups[id];
+        }});
+        return popup;
+    }}
+    window.__orblyPopupEvent = function(id, kind, data, origin) {{
+        var popup = popups[id];
+        if (!popup) return;
+        if (kind === 'closed') {{
+            popup.closed = true;
+            delete popups[id];
+        }} else if (kind === 'message') {{
+            var event = new MessageEvent('message', {{ data: data, origin: origin }});
+            try {{ Object.defineProperty(event, 'source', {{ value: popup }}); }} catch (e) {{}}
+            window.dispatchEvent(event);
+        }}
+    }};
+
     // Override window.open for cross-origin
     var _origOpen = window.open;
     window.open = function(url, target, features) {{
@@ -782,12 +957,7 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
                 return null;
             }}
             if (parsed.origin !== pageOrigin && (parsed.protocol === 'http:' || parsed.protocol === 'https:')) {{
-                if (window.__TAURI_INTERNALS__) {{
-                    window.__TAURI_INTERNALS__.invoke('route_link', {{
-                        url: parsed.href,
-                        source_app_id: ORBLY_APP_ID
-                    }}).catch(function() {{}});
-                }}
+                if (window.__TAURI_INTERNALS__) return openPopup(parsed.href);
                 return null;
             }}
         }} catch(ex) {{}}
@@ -859,7 +1029,9 @@ fn build_initialization_script(app_handle: &AppHandle, app_config: &AppConfig) -
     scripts.join("\n")
 }
 
-/// Show/hide a webview (used for switching active app)
+/// Show/hide a webview (used for switching active app).
+/// A popped-out app fills its own window and stays up until docked, so
+/// hiding it does nothing and showing it brings its window forward.
 pub fn set_webview_visible(
     app_handle: &AppHandle,
     app_id: &str,
@@ -867,6 +1039,17 @@ pub fn set_webview_visible(
     position: Option<tauri::LogicalPosition<f64>>,
     size: Option<tauri::LogicalSize<f64>>,
 ) -> Result<(), String> {
+    let popout_window =
+        app_handle.get_window(&crate::app_manager::popout::window_label(app_id));
+    if let (Some(webview), Some(window)) = (app_handle.get_webview(app_id), popout_window) {
+        if visible {
+            webview.show().map_err(|e| e.to_string())?;
+            let _ = window.unminimize();
+            window.show().map_err(|e| e.to_string())?;
+            let _ = window.set_focus();
+        }
+        return Ok(());
+    }
     if let Some(webview) = app_handle.get_webview(app_id) {
         if visible {
             if let (Some(pos), Some(sz)) = (position, size) {
@@ -1,14 +1,197 @@
+pub mod accounts;
+pub mod auth_popup;
 pub mod certificate;
+pub mod crash_recovery;
+pub mod data_store;
 pub mod lifecycle;
+pub mod page_snapshot;
+pub mod popout;
+pub mod schedule;
 pub mod session_state;
+pub mod sign_in;
+pub mod startup;
 pub mod state;
+pub mod unsaved_work;
 
-use tauri::{Emitter, Manager};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
 
 use crate::config::manager::ConfigManager;
+use state::{AppRuntimeState, StateKind};
+
+/// Payload of the `app-state-changed` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStateChanged {
+    pub app_id: String,
+    pub old: StateKind,
+    pub new: StateKind,
+    pub reason: String,
+}
+
+/// Move an app to `state` and tell the frontend if its state kind changed.
+/// Transitions the state machine does not allow are logged and dropped.
+/// Returns whether the state was applied.
+pub fn set_app_state(
+    app_handle: &AppHandle,
+    app_id: &str,
+    state: AppRuntimeState,
+    reason: &str,
+) -> bool {
+    let app_manager = app_handle.state::<state::AppManager>();
+    emit_transition(app_handle, app_id, app_manager.transition(app_id, state, reason))
+}
+
+/// Like `set_app_state`, but only if the app is still in one of `expected`.
+pub fn set_app_state_if(
+    app_handle: &AppHandle,
+    app_id: &str,
+    expected: &[StateKind],
+    state: AppRuntimeState,
+    reason: &str,
+) -> bool {
+    let app_manager = app_handle.state::<state::AppManager>();
+    emit_transition(
+        app_handle,
+        app_id,
+        app_manager.transition_if(app_id, expected, state, reason),
+    )
+}
+
+fn emit_transition(
+    app_handle: &AppHandle,
+    app_id: &str,
+    result: Result<Option<state::StateTransition>, String>,
+) -> bool {
+    match result {
+        Ok(Some(transition)) => {
+            let _ = app_handle.emit(
+                "app-state-changed",
+                AppStateChanged {
+                    app_id: app_id.to_string(),
+                    old: transition.from,
+                    new: transition.to,
+                    reason: transition.reason,
+                },
+            );
+            true
+        }
+        Ok(None) => true,
+        Err(e) => {
+            log::debug!("Ignored state change: {}", e);
+            false
+        }
+    }
+}
+
+/// Hibernate an app on Orbly's own initiative: close its webview, keep its
+/// URL for waking and persist the hibernated flag. Apps with unsaved work are
+/// left alone. Returns whether the app was hibernated.
+pub fn hibernate_in_background(app_handle: &AppHandle, app_id: &str, reason: &str) -> bool {
+    if unsaved_work::has_unsaved_work(app_handle, app_id) {
+        log::info!("Not hibernating '{}' ({}): it has unsaved work", app_id, reason);
+        return false;
+    }
+    // Check the transition before the webview goes: an app that cannot
+    // hibernate now (loading, say) must keep it
+    let Some(current) = app_handle
+        .state::<state::AppManager>()
+        .get_state(app_id)
+        .map(|rt| rt.state.kind())
+    else {
+        return false;
+    };
+    if !current.can_become(StateKind::Hibernated) {
+        log::debug!("Not hibernating '{}' ({}): it is {:?}", app_id, reason, current);
+        return false;
+    }
+    let config_manager = app_handle.state::<ConfigManager>();
+    let Ok(last_url) = lifecycle::destroy_app_webview(app_handle, app_id) else {
+        return false;
+    };
+    let url = last_url.unwrap_or_else(|| {
+        config_manager
+            .get_config()
+            .apps
+            .iter()
+            .find(|a| a.id == app_id)
+            .map(|a| a.url.clone())
+            .unwrap_or_default()
+    });
+    if !set_app_state(
+        app_handle,
+        app_id,
+        AppRuntimeState::Hibernated { last_url: url },
+        reason,
+    ) {
+        return false;
+    }
+    let _ = config_manager.update_quietly("auto-hibernate", |config| {
+        if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
+            app.hibernated = true;
+        }
+    });
+    true
+}
+
+/// Wake a hibernated app on Orbly's own initiative: rebuild its webview in
+/// the background at its last URL and clear the hibernated flag.
+pub fn wake_in_background(app_handle: &AppHandle, app_id: &str, reason: &str) -> Result<(), String> {
+    let config_manager = app_handle.state::<ConfigManager>();
+    let app_manager = app_handle.state::<state::AppManager>();
+    let mut app_config = config_manager
+        .get_config()
+        .apps
+        .into_iter()
+        .find(|a| a.id == app_id)
+        .ok_or_else(|| format!("App '{}' not found in config", app_id))?;
+    if let Some(AppRuntimeState::Hibernated { last_url }) =
+        app_manager.get_state(app_id).map(|rt| rt.state)
+    {
+        if !last_url.is_empty() {
+            app_config.url = last_url;
+        }
+    }
+    if !set_app_state(
+        app_handle,
+        app_id,
+        AppRuntimeState::Loading {
+            target_url: app_config.url.clone(),
+        },
+        reason,
+    ) {
+        return Err(format!("App '{}' cannot be woken now", app_id));
+    }
+
+    if app_handle.get_webview(app_id).is_none() {
+        let bounds = app_handle.state::<state::ContentBounds>().get();
+        let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
+        let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+        if let Err(e) = lifecycle::create_app_webview(app_handle, &app_config, position, size) {
+            set_app_state(
+                app_handle,
+                app_id,
+                AppRuntimeState::Error { message: e.clone() },
+                reason,
+            );
+            return Err(e);
+        }
+        let _ = lifecycle::set_webview_visible(app_handle, app_id, false, None, None);
+        app_manager.set_visible(app_id, false);
+    }
+
+    config_manager
+        .update_quietly("auto-wake", |config| {
+            if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
+                app.hibernated = false;
+            }
+        })
+        .map_err(|e| e.to_string())
+}
 
 /// Spawns a background task that monitors heartbeats from visible webviews
 /// and transitions apps to Crashed state if heartbeat is missing for too long.
+/// Crashed apps are then recovered according to their `crash_recovery` policy.
 pub fn start_crash_detection_task(app_handle: tauri::AppHandle) {
     const HEARTBEAT_TIMEOUT_SECS: u64 = 90;
 
@@ -25,7 +208,7 @@ pub fn start_crash_detection_task(app_handle: tauri::AppHandle) {
                 if !runtime.is_visible {
                     continue;
                 }
-                if let state::AppRuntimeState::Active { .. } = &runtime.state {
+                if let state::AppRuntimeState::Active { current_url } = &runtime.state {
                     if let Some(last_hb) = runtime.last_heartbeat {
                         if last_hb.elapsed().as_secs() > HEARTBEAT_TIMEOUT_SECS {
                             log::warn!(
@@ -33,8 +216,21 @@ pub fn start_crash_detection_task(app_handle: tauri::AppHandle) {
                                 app_id,
                                 last_hb.elapsed().as_secs()
                             );
-                            app_manager.set_state(app_id, state::AppRuntimeState::Crashed);
-                            let _ = app_handle.emit("app-crashed", app_id.clone());
+                            if set_app_state_if(
+                                &app_handle,
+                                app_id,
+                                &[StateKind::Active],
+                                AppRuntimeState::Crashed,
+                                "heartbeat timeout",
+                            ) {
+                                let _ = app_handle.emit("app-crashed", app_id.clone());
+                                crash_recovery::handle_crash(
+                                    &app_handle,
+                                    app_id,
+                                    Some(current_url.clone()),
+                                    "heartbeat timeout",
+                                );
+                            }
                         }
                     }
                 }
@@ -46,13 +242,15 @@ pub fn start_crash_detection_task(app_handle: tauri::AppHandle) {
                                 "App '{}' stuck in Loading state for over 30s, marking as error",
                                 app_id,
                             );
-                            app_manager.set_state(
+                            set_app_state_if(
+                                &app_handle,
                                 app_id,
-                                state::AppRuntimeState::Error {
+                                &[StateKind::Loading],
+                                AppRuntimeState::Error {
                                     message: "Page took too long to load".to_string(),
                                 },
+                                "load timeout",
                             );
-                            let _ = app_handle.emit("app-state-changed", app_id.clone());
                         }
                     }
                 }
@@ -110,26 +308,9 @@ pub fn start_auto_hibernate_task(app_handle: tauri::AppHandle) {
                                     }
                                 };
 
-                                if should_hibernate {
-                                    if let Ok(last_url) =
-                                        lifecycle::destroy_app_webview(&app_handle, app_id)
-                                    {
-                                        let url = last_url.unwrap_or_else(|| app_config.url.clone());
-                                        let mut apps_lock = app_manager.apps.lock().expect("apps lock");
-                                        if let Some(rt) = apps_lock.get_mut(app_id) {
-                                            rt.state =
-                                                state::AppRuntimeState::Hibernated { last_url: url };
-                                        }
-                                    }
-                                    // Update persisted config
-                                    let mut cfg = config_manager.get_config();
-                                    if let Some(a) =
-                                        cfg.apps.iter_mut().find(|a| a.id == *app_id)
-                                    {
-                                        a.hibernated = true;
-                                    }
-                                    let _ = config_manager.save_config(cfg);
-
+                                if should_hibernate
+                                    && hibernate_in_background(&app_handle, app_id, "inactivity timeout")
+                                {
                                     let _ = app_handle.emit("app-auto-hibernated", app_id.clone());
                                 }
                             }
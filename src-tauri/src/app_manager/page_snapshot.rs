@@ -0,0 +1,281 @@
+//! Page snapshots that survive hibernation and crashes.
+//!
+//! An injected script keeps a same-origin back stack of the app's page in
+//! `sessionStorage` and reports it, with the scroll offset and (for apps that
+//! opt in) the text of its form fields, to `save_page_snapshot`. When the
+//! app's webview is rebuilt at the snapshot URL, the same script pushes the
+//! back stack onto the new page's history, scrolls back and refills the
+//! fields. The forward stack is not replayed: the History API can only add
+//! entries behind the current page.
+
+use std::collections::HashMap;
+
+use super::session_state::PageSnapshot;
+
+/// Most history entries kept, matching WebKit's own session history limit.
+const MAX_HISTORY: usize = 50;
+const MAX_FIELDS: usize = 50;
+const MAX_SELECTOR_LEN: usize = 200;
+const MAX_FIELD_LEN: usize = 10_000;
+
+/// Clean up a snapshot reported by a page, or `None` if it cannot be replayed.
+pub fn sanitize(snapshot: PageSnapshot, keep_fields: bool) -> Option<PageSnapshot> {
+    let url = url::Url::parse(&snapshot.url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let origin = url.origin();
+
+    // Only the same-origin tail can be pushed back with the History API
+    let mut history: Vec<String> = snapshot
+        .history
+        .iter()
+        .rev()
+        .take_while(|entry| url::Url::parse(entry).is_ok_and(|u| u.origin() == origin))
+        .take(MAX_HISTORY)
+        .cloned()
+        .collect();
+    history.reverse();
+    if history.last() != Some(&snapshot.url) {
+        history = vec![snapshot.url.clone()];
+    }
+
+    let offset = |v: f64| if v.is_finite() && v > 0.0 { v } else { 0.0 };
+    let fields: HashMap<String, String> = if keep_fields {
+        snapshot
+            .fields
+            .into_iter()
+            .filter(|(selector, value)| {
+                !selector.is_empty() && selector.len() <= MAX_SELECTOR_LEN && !value.is_empty()
+            })
+            .take(MAX_FIELDS)
+            .map(|(selector, value)| (selector, value.chars().take(MAX_FIELD_LEN).collect()))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    Some(PageSnapshot {
+        url: snapshot.url,
+        history,
+        scroll_x: offset(snapshot.scroll_x),
+        scroll_y: offset(snapshot.scroll_y),
+        fields,
+    })
+}
+
+/// The script that tracks and reports the page, replaying `restore` first
+/// if the page is the one it was taken on.
+pub fn snapshot_script(app_id: &str, keep_fields: bool, restore: Option<&PageSnapshot>) -> String {
+    let restore_json = restore
+        .and_then(|s| serde_json::to_string(s).ok())
+        .unwrap_or_else(|| "null".to_string());
+    format!(
+        r#"
+(function() {{
+    'use strict';
+    var ORBLY_APP_ID = '{app_id}';
+    var KEEP_FIELDS = {keep_fields};
+    var RESTORE = {restore_json};
+    var NAV_KEY = '__orbly_nav__';
+    var RESTORED_KEY = '__orbly_restored__';
+    var MAX_HISTORY = {MAX_HISTORY};
+    var TEXT_TYPES = ['text', 'search', 'email', 'url', 'tel', 'number'];
+
+    function readNav() {{
+        try {{
+            var saved = JSON.parse(sessionStorage.getItem(NAV_KEY));
+            if (saved && saved.entries && saved.entries.length) return saved;
+        }} catch (e) {{}}
+        return {{ entries: [location.href], index: 0 }};
+    }}
+    function writeNav() {{
+        try {{ sessionStorage.setItem(NAV_KEY, JSON.stringify(nav)); }} catch (e) {{}}
+    }}
+
+    // Follow the page's position in its own history
+    function record(replace) {{
+        var href = location.href;
+        if (nav.entries[nav.index] === href) return;
+        if (replace) {{
+            nav.entries[nav.index] = href;
+        }} else if (nav.entries[nav.index - 1] === href) {{
+            nav.index--;
+        }} else if (nav.entries[nav.index + 1] === href) {{
+            nav.index++;
+        }} else {{
+            nav.entries = nav.entries.slice(0, nav.index + 1);
+            nav.entries.push(href);
+            if (nav.entries.length > MAX_HISTORY) nav.entries.shift();
+            nav.index = nav.entries.length - 1;
+        }}
+        writeNav();
+        saveSoon();
+    }}
+
+    var nav = readNav();
+    var saveTimer = null;
+    var restoring = false;
+    try {{
+        restoring = !!RESTORE && RESTORE.url === location.href && !sessionStorage.getItem(RESTORED_KEY);
+        if (restoring) sessionStorage.setItem(RESTORED_KEY, '1');
+    }} catch (e) {{}}
+
+    if (restoring && RESTORE.history.length > 1) {{
+        try {{
+            history.replaceState(null, '', RESTORE.history[0]);
+            for (var i = 1; i < RESTORE.history.length; i++) {{
+                history.pushState(null, '', RESTORE.history[i]);
+            }}
+            nav = {{ entries: RESTORE.history.slice(), index: RESTORE.history.length - 1 }};
+            writeNav();
+        }} catch (e) {{}}
+    }} else {{
+        var entry = performance.getEntriesByType && performance.getEntriesByType('navigation')[0];
+        record(!!entry && entry.type === 'reload');
+    }}
+
+    var pushState = history.pushState;
+    var replaceState = history.replaceState;
+    history.pushState = function() {{
+        var result = pushState.apply(this, arguments);
+        record(false);
+        return result;
+    }};
+    history.replaceState = function() {{
+        var result = replaceState.apply(this, arguments);
+        record(true);
+        return result;
+    }};
+    window.addEventListener('popstate', function() {{ record(false); }});
+    window.addEventListener('hashchange', function() {{ record(false); }});
+
+    function fieldSelector(el) {{
+        if (el.id) return '#' + CSS.escape(el.id);
+        if (el.name) return el.tagName.toLowerCase() + '[name="' + CSS.escape(el.name) + '"]';
+        return null;
+    }}
+    function collectFields() {{
+        var fields = {{}};
+        if (!KEEP_FIELDS) return fields;
+        var els = document.querySelectorAll('input, textarea');
+        var count = 0;
+        for (var i = 0; i < els.length && count < {MAX_FIELDS}; i++) {{
+            var el = els[i];
+            if (el.tagName === 'INPUT' && TEXT_TYPES.indexOf(el.type) === -1) continue;
+            if (el.disabled || el.readOnly || !el.value || el.autocomplete === 'off') continue;
+            var selector = fieldSelector(el);
+            if (!selector) continue;
+            fields[selector] = el.value;
+            count++;
+        }}
+        return fields;
+    }}
+
+    var lastSent = '';
+    function save() {{
+        var snapshot = {{
+            url: location.href,
+            history: nav.entries.slice(0, nav.index + 1),
+            scroll_x: window.scrollX,
+            scroll_y: window.scrollY,
+            fields: collectFields()
+        }};
+        var json = JSON.stringify(snapshot);
+        if (json === lastSent || !window.__TAURI_INTERNALS__) return;
+        lastSent = json;
+        window.__TAURI_INTERNALS__.invoke('save_page_snapshot', {{
+            app_id: ORBLY_APP_ID,
+            snapshot: snapshot
+        }}).catch(function() {{}});
+    }}
+    function saveSoon() {{
+        clearTimeout(saveTimer);
+        saveTimer = setTimeout(save, 1000);
+    }}
+    // The host replays the last report it got, so report soon after anything
+    // the snapshot holds changes
+    window.addEventListener('scroll', saveSoon, {{ passive: true }});
+    if (KEEP_FIELDS) document.addEventListener('input', saveSoon, true);
+    setInterval(save, 15000);
+    document.addEventListener('visibilitychange', function() {{
+        if (document.visibilityState === 'hidden') save();
+    }});
+    window.addEventListener('pagehide', save);
+
+    if (!restoring) return;
+
+    // Pages render after load, so keep trying for a few seconds
+    function setValue(el, value) {{
+        var proto = el.tagName === 'TEXTAREA' ? HTMLTextAreaElement.prototype : HTMLInputElement.prototype;
+        Object.getOwnPropertyDescriptor(proto, 'value').set.call(el, value);
+        el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+    }}
+    var pending = Object.keys(RESTORE.fields);
+    var scrolled = RESTORE.scroll_x === 0 && RESTORE.scroll_y === 0;
+    var attempts = 0;
+    function replay() {{
+        if (!scrolled) {{
+            window.scrollTo(RESTORE.scroll_x, RESTORE.scroll_y);
+            scrolled = Math.abs(window.scrollY - RESTORE.scroll_y) < 2;
+        }}
+        pending = pending.filter(function(selector) {{
+            var el = null;
+            try {{ el = document.querySelector(selector); }} catch (e) {{ return false; }}
+            if (!el) return true;
+            if (!el.value) setValue(el, RESTORE.fields[selector]);
+            return false;
+        }});
+        if ((!scrolled || pending.length) && ++attempts < 40) setTimeout(replay, 250);
+    }}
+    if (document.readyState === 'complete') {{
+        replay();
+    }} else {{
+        window.addEventListener('load', replay);
+    }}
+}})();
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_keeps_same_origin_back_stack() {
+        let snapshot = PageSnapshot {
+            url: "https://mail.example.com/inbox/42".to_string(),
+            history: vec![
+                "https://accounts.example.com/login".to_string(),
+                "https://mail.example.com/inbox".to_string(),
+                "https://mail.example.com/inbox/42".to_string(),
+            ],
+            scroll_x: f64::NAN,
+            scroll_y: 640.0,
+            fields: HashMap::from([("#reply".to_string(), "Thanks!".to_string())]),
+        };
+        let clean = sanitize(snapshot.clone(), false).unwrap();
+        assert_eq!(
+            clean.history,
+            vec![
+                "https://mail.example.com/inbox",
+                "https://mail.example.com/inbox/42"
+            ]
+        );
+        assert_eq!((clean.scroll_x, clean.scroll_y), (0.0, 640.0));
+        assert!(clean.fields.is_empty());
+        assert_eq!(sanitize(snapshot.clone(), true).unwrap().fields.len(), 1);
+
+        // A back stack that does not end at the page is dropped
+        let mut stale = snapshot.clone();
+        stale.url = "https://mail.example.com/sent".to_string();
+        assert_eq!(
+            sanitize(stale, false).unwrap().history,
+            vec!["https://mail.example.com/sent"]
+        );
+        let mut local = snapshot;
+        local.url = "file:///etc/passwd".to_string();
+        assert!(sanitize(local, false).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+//! Apps popped out of the main window.
+//!
+//! A detached app's webview is a child of a window of its own, labelled
+//! `popout-<app id>`, instead of the main window. The webview keeps the app id
+//! as its label, so badges, notifications and hibernation find it as before,
+//! while layouts of the main window leave it alone. The window opens and
+//! closes with the webview; closing it by hand docks the app back into the
+//! main window.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::lifecycle;
+use super::state::{AppManager, ContentBounds};
+use crate::config::manager::ConfigManager;
+use crate::config::models::AppConfig;
+
+const LABEL_PREFIX: &str = "popout-";
+const DEFAULT_WIDTH: f64 = 1000.0;
+const DEFAULT_HEIGHT: f64 = 720.0;
+
+/// Tracks when each popped-out window's geometry was last saved, for debouncing.
+pub struct PopoutWindows {
+    last_save: Mutex<HashMap<String, Instant>>,
+}
+
+impl PopoutWindows {
+    pub fn new() -> Self {
+        Self {
+            last_save: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn should_save(&self, app_id: &str) -> bool {
+        let mut last_save = self.last_save.lock().expect("popout save lock");
+        if last_save
+            .get(app_id)
+            .is_some_and(|prev| prev.elapsed().as_millis() < 500)
+        {
+            return false;
+        }
+        last_save.insert(app_id.to_string(), Instant::now());
+        true
+    }
+}
+
+/// Label of the window an app is popped out into.
+pub fn window_label(app_id: &str) -> String {
+    format!("{LABEL_PREFIX}{app_id}")
+}
+
+/// The app a popped-out window belongs to, or `None` for other windows.
+pub fn app_id_of(window_label: &str) -> Option<&str> {
+    window_label
+        .strip_prefix(LABEL_PREFIX)
+        .filter(|id| !id.is_empty())
+}
+
+/// Whether the app's webview currently lives in its own window.
+pub fn is_popped_out(app_handle: &AppHandle, app_id: &str) -> bool {
+    app_handle.get_window(&window_label(app_id)).is_some()
+}
+
+/// The app's own window, opened where it was last placed if it is not open.
+pub fn host_window(
+    app_handle: &AppHandle,
+    app_config: &AppConfig,
+) -> Result<tauri::Window, String> {
+    if let Some(window) = app_handle.get_window(&window_label(&app_config.id)) {
+        return Ok(window);
+    }
+    let geometry = &app_config.popout_window;
+    let mut builder = tauri::window::WindowBuilder::new(app_handle, window_label(&app_config.id))
+        .title(&app_config.name)
+        .inner_size(
+            geometry.width.unwrap_or(DEFAULT_WIDTH),
+            geometry.height.unwrap_or(DEFAULT_HEIGHT),
+        )
+        .min_inner_size(400.0, 300.0);
+    if let (Some(x), Some(y)) = (geometry.x, geometry.y) {
+        builder = builder.position(x, y);
+    }
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to open window: {e}"))?;
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+    Ok(window)
+}
+
+/// Logical size of a window's content, which its webview fills.
+pub fn content_size(window: &tauri::Window) -> tauri::LogicalSize<f64> {
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    window
+        .inner_size()
+        .map(|size| size.to_logical(scale_factor))
+        .unwrap_or(tauri::LogicalSize::new(DEFAULT_WIDTH, DEFAULT_HEIGHT))
+}
+
+/// Move an app out of the main window into its own. An app without a webview
+/// opens in its own window the next time it is activated.
+pub fn pop_out(app_handle: &AppHandle, app_id: &str) -> Result<(), String> {
+    let config_manager = app_handle.state::<ConfigManager>();
+    config_manager
+        .update_with("pop_out_app", |config| {
+            if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
+                app.detached = true;
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    let app_config = config_manager
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+
+    if let Some(webview) = app_handle.get_webview(app_id) {
+        let window = host_window(app_handle, &app_config)?;
+        let moved = webview
+            .reparent(&window)
+            .and_then(|()| webview.set_position(tauri::LogicalPosition::new(0.0, 0.0)))
+            .and_then(|()| webview.set_size(content_size(&window)));
+        if let Err(e) = moved {
+            log::warn!(
+                "Could not move '{}' to its own window ({}); reloading it there",
+                app_id,
+                e
+            );
+            rebuild(app_handle, &app_config, &webview, true)?;
+        }
+        lifecycle::set_webview_visible(app_handle, app_id, true, None, None)?;
+        app_handle.state::<AppManager>().set_visible(app_id, true);
+    }
+
+    let _ = app_handle.emit("app-popped-out", app_id);
+    Ok(())
+}
+
+/// Move a popped-out app back into the main window, hidden until it is
+/// activated again.
+pub fn dock(app_handle: &AppHandle, app_id: &str) -> Result<(), String> {
+    let window = app_handle.get_window(&window_label(app_id));
+    if let Some(window) = &window {
+        save_geometry(window, app_id);
+    }
+    let config_manager = app_handle.state::<ConfigManager>();
+    config_manager
+        .update_with("dock_app", |config| {
+            if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
+                app.detached = false;
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    let app_config = config_manager
+        .get_app(app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+
+    if let Some(webview) = app_handle.get_webview(app_id) {
+        let main_window = app_handle
+            .get_window("main")
+            .ok_or("Main window not found")?;
+        let bounds = app_handle.state::<ContentBounds>().get();
+        let moved = webview
+            .reparent(&main_window)
+            .and_then(|()| webview.set_position(tauri::LogicalPosition::new(bounds.x, bounds.y)))
+            .and_then(|()| webview.set_size(tauri::LogicalSize::new(bounds.width, bounds.height)));
+        if let Err(e) = moved {
+            log::warn!(
+                "Could not move '{}' back to the main window ({}); reloading it there",
+                app_id,
+                e
+            );
+            rebuild(app_handle, &app_config, &webview, false)?;
+        }
+        let _ = lifecycle::set_webview_visible(app_handle, app_id, false, None, None);
+        app_handle.state::<AppManager>().set_visible(app_id, false);
+    }
+    if let Some(window) = window {
+        let _ = window.destroy();
+    }
+
+    let _ = app_handle.emit("app-docked", app_id);
+    Ok(())
+}
+
+/// Close the app's own window, remembering where it was. Used when the app's
+/// webview is destroyed.
+pub fn close_window(app_handle: &AppHandle, app_id: &str) {
+    if let Some(window) = app_handle.get_window(&window_label(app_id)) {
+        save_geometry(&window, app_id);
+        let _ = window.destroy();
+    }
+}
+
+/// Close every popped-out window, e.g. when Orbly quits.
+pub fn close_all(app_handle: &AppHandle) {
+    for label in app_handle.windows().into_keys() {
+        if let Some(app_id) = app_id_of(&label) {
+            close_window(app_handle, app_id);
+        }
+    }
+}
+
+/// Handle an event of a popped-out window.
+pub fn on_window_event(window: &tauri::Window, app_id: &str, event: &tauri::WindowEvent) {
+    let app_handle = window.app_handle();
+    match event {
+        tauri::WindowEvent::CloseRequested { api, .. } => {
+            api.prevent_close();
+            if let Err(e) = dock(app_handle, app_id) {
+                log::warn!("Failed to dock '{}': {}", app_id, e);
+            }
+        }
+        tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+            if app_handle.state::<PopoutWindows>().should_save(app_id) {
+                save_geometry(window, app_id);
+            }
+        }
+        tauri::WindowEvent::Focused(true) => {
+            app_handle.state::<AppManager>().touch_interaction(app_id);
+        }
+        _ => {}
+    }
+}
+
+fn save_geometry(window: &tauri::Window, app_id: &str) {
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    let maximized = window.is_maximized().unwrap_or(false);
+    let position = window
+        .outer_position()
+        .ok()
+        .map(|p| p.to_logical::<f64>(scale_factor));
+    let size = window
+        .inner_size()
+        .ok()
+        .map(|s| s.to_logical::<f64>(scale_factor));
+    let config_manager = window.app_handle().state::<ConfigManager>();
+    let _ = config_manager.update_quietly("popout window state", |config| {
+        let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) else {
+            return;
+        };
+        let geometry = &mut app.popout_window;
+        geometry.maximized = maximized;
+        if maximized {
+            return;
+        }
+        if let Some(position) = position {
+            geometry.x = Some(position.x);
+            geometry.y = Some(position.y);
+        }
+        if let Some(size) = size {
+            geometry.width = Some(size.width);
+            geometry.height = Some(size.height);
+        }
+    });
+}
+
+/// Replace a webview that could not be moved with a new one at the same page,
+/// built in the window the app's config now asks for.
+fn rebuild(
+    app_handle: &AppHandle,
+    app_config: &AppConfig,
+    webview: &tauri::Webview,
+    visible: bool,
+) -> Result<(), String> {
+    let mut app_config = app_config.clone();
+    if let Ok(url) = webview.url() {
+        app_config.url = url.to_string();
+    }
+    lifecycle::destroy_app_webview(app_handle, &app_config.id)?;
+    lifecycle::reopen_app_webview(app_handle, &app_config, visible, "window changed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_labels_round_trip() {
+        assert_eq!(app_id_of(&window_label("slack")), Some("slack"));
+        assert_eq!(app_id_of("main"), None);
+        assert_eq!(app_id_of("popout-"), None);
+    }
+}
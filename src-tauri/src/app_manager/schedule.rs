@@ -0,0 +1,212 @@
+//! Time-of-day hibernation schedules.
+//!
+//! An app follows its own `schedule`, or else the schedule of the first
+//! workspace it belongs to that has one. The scheduler acts only when a
+//! boundary passes (and once at startup for the latest boundary), so an app
+//! the user wakes during its off-hours stays awake until the next boundary.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDateTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::state::{AppManager, AppRuntimeState, StateKind};
+use super::{hibernate_in_background, wake_in_background};
+use crate::config::manager::ConfigManager;
+use crate::config::models::{AppConfig, HibernationSchedule, OrblyConfig};
+use crate::notifications::handler::{day_key, parse_time};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduledAction {
+    Wake,
+    Hibernate,
+}
+
+/// The schedule `app` follows, if any.
+pub fn schedule_for<'a>(
+    config: &'a OrblyConfig,
+    app: &'a AppConfig,
+) -> Option<&'a HibernationSchedule> {
+    app.schedule.as_ref().or_else(|| {
+        config
+            .workspaces
+            .items
+            .iter()
+            .filter(|ws| ws.app_ids.contains(&app.id))
+            .find_map(|ws| ws.schedule.as_ref())
+    })
+}
+
+/// The latest boundary of `schedule` at or before `now`, looking back a week.
+pub fn last_boundary(
+    schedule: &HibernationSchedule,
+    now: NaiveDateTime,
+) -> Option<(NaiveDateTime, ScheduledAction)> {
+    let wake = parse_time(&schedule.wake_at)?;
+    let hibernate = parse_time(&schedule.hibernate_at)?;
+    let at =
+        |date: chrono::NaiveDate, minutes: u32| date.and_hms_opt(minutes / 60, minutes % 60, 0);
+    (0..=7)
+        .filter_map(|days_back| now.date().checked_sub_days(chrono::Days::new(days_back)))
+        .filter(|date| schedule.days.iter().any(|d| d == day_key(date.weekday())))
+        // Wake comes second so it wins when both fall at the same minute
+        .flat_map(|date| {
+            [
+                (at(date, hibernate), ScheduledAction::Hibernate),
+                (at(date, wake), ScheduledAction::Wake),
+            ]
+        })
+        .filter_map(|(time, action)| Some((time?, action)))
+        .filter(|(time, _)| *time <= now)
+        .max_by_key(|(time, _)| *time)
+}
+
+/// Spawns a background task that wakes and hibernates apps on their schedules.
+pub fn start_schedule_task(app_handle: AppHandle) {
+    tokio::spawn(async move {
+        // The boundary last acted on, per app
+        let mut applied: HashMap<String, NaiveDateTime> = HashMap::new();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+        loop {
+            interval.tick().await;
+
+            let config = app_handle.state::<ConfigManager>().get_config();
+            let now = chrono::Local::now().naive_local();
+            for app in config.apps.iter().filter(|a| a.enabled) {
+                let Some(schedule) = schedule_for(&config, app) else {
+                    applied.remove(&app.id);
+                    continue;
+                };
+                let Some((at, action)) = last_boundary(schedule, now) else {
+                    continue;
+                };
+                if applied.get(&app.id) == Some(&at) {
+                    continue;
+                }
+                applied.insert(app.id.clone(), at);
+                if apply(&app_handle, app, action) {
+                    let _ = app_handle.emit(
+                        "app-scheduled",
+                        serde_json::json!({
+                            "appId": app.id,
+                            "action": match action {
+                                ScheduledAction::Wake => "wake",
+                                ScheduledAction::Hibernate => "hibernate",
+                            },
+                        }),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Carry out a scheduled action. Returns whether anything changed.
+fn apply(app_handle: &AppHandle, app: &AppConfig, action: ScheduledAction) -> bool {
+    let runtime = app_handle.state::<AppManager>().get_state(&app.id);
+    match action {
+        // Apps that are not hibernated are already up, or will be created
+        // when the frontend starts unless their launch policy keeps them
+        // hibernated
+        ScheduledAction::Wake => {
+            let asleep = runtime.is_some_and(|rt| rt.state.kind() == StateKind::Hibernated);
+            if !asleep || !(app.hibernated || app.starts_hibernated()) {
+                return false;
+            }
+            match wake_in_background(app_handle, &app.id, "schedule") {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Scheduled wake of '{}' failed: {}", app.id, e);
+                    false
+                }
+            }
+        }
+        ScheduledAction::Hibernate => {
+            let Some(runtime) = runtime else {
+                return false;
+            };
+            if runtime.has_unsaved_work || runtime.is_playing_media {
+                log::info!(
+                    "Not hibernating '{}' on schedule: it is busy until the next boundary",
+                    app.id
+                );
+                return false;
+            }
+            match runtime.state {
+                AppRuntimeState::Active { .. } | AppRuntimeState::Loading { .. } => {
+                    hibernate_in_background(app_handle, &app.id, "schedule")
+                }
+                // No webview yet (e.g. at startup): just keep it from being created
+                _ if !app.hibernated => {
+                    let config_manager = app_handle.state::<ConfigManager>();
+                    config_manager
+                        .update_quietly("auto-hibernate", |config| {
+                            if let Some(a) = config.apps.iter_mut().find(|a| a.id == app.id) {
+                                a.hibernated = true;
+                            }
+                        })
+                        .is_ok()
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(wake_at: &str, hibernate_at: &str) -> HibernationSchedule {
+        HibernationSchedule {
+            wake_at: wake_at.to_string(),
+            hibernate_at: hibernate_at.to_string(),
+            days: ["mon", "tue", "wed", "thu", "fri"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_work_schedule_boundaries() {
+        let work = schedule("08:45", "18:00");
+        // 2026-10-19 is a Monday
+        assert_eq!(
+            last_boundary(&work, at("2026-10-19 10:00")),
+            Some((at("2026-10-19 08:45"), ScheduledAction::Wake))
+        );
+        assert_eq!(
+            last_boundary(&work, at("2026-10-19 18:00")),
+            Some((at("2026-10-19 18:00"), ScheduledAction::Hibernate))
+        );
+        // Weekends and Monday mornings still belong to Friday evening
+        assert_eq!(
+            last_boundary(&work, at("2026-10-24 12:00")),
+            Some((at("2026-10-23 18:00"), ScheduledAction::Hibernate))
+        );
+        assert_eq!(
+            last_boundary(&work, at("2026-10-26 07:00")),
+            Some((at("2026-10-23 18:00"), ScheduledAction::Hibernate))
+        );
+    }
+
+    #[test]
+    fn test_reversed_schedule_keeps_weekends_awake() {
+        let personal = schedule("18:00", "08:45");
+        assert_eq!(
+            last_boundary(&personal, at("2026-10-20 12:00")),
+            Some((at("2026-10-20 08:45"), ScheduledAction::Hibernate))
+        );
+        assert_eq!(
+            last_boundary(&personal, at("2026-10-25 12:00")),
+            Some((at("2026-10-23 18:00"), ScheduledAction::Wake))
+        );
+        let mut empty = personal.clone();
+        empty.days.clear();
+        assert_eq!(last_boundary(&empty, at("2026-10-25 12:00")), None);
+    }
+}
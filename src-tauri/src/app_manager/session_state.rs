@@ -15,6 +15,30 @@ pub struct SessionState {
 pub struct SessionData {
     /// Map of app_id -> last known URL
     pub active_apps: HashMap<String, String>,
+    /// Map of app_id -> latest page snapshot, kept while the app is
+    /// hibernated so it can be replayed on wake
+    #[serde(default)]
+    pub snapshots: HashMap<String, PageSnapshot>,
+    /// Map of app_id -> when the user last activated it, in Unix seconds
+    #[serde(default)]
+    pub last_used: HashMap<String, i64>,
+    /// Map of app_id -> combined badge, kept so startup can favour apps
+    /// with unread items
+    #[serde(default)]
+    pub badges: HashMap<String, i32>,
+}
+
+/// Where an app was in its page, replayed when its webview is rebuilt.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct PageSnapshot {
+    pub url: String,
+    /// Same-origin back stack, oldest first, ending with `url`
+    pub history: Vec<String>,
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    /// Form field values by CSS selector, for apps that opt in
+    pub fields: HashMap<String, String>,
 }
 
 impl SessionState {
@@ -54,7 +78,73 @@ impl SessionState {
         self.inner.lock().expect("session state lock").active_apps.clone()
     }
 
+    /// Record the latest page snapshot of an app.
+    pub fn set_snapshot(&self, app_id: &str, snapshot: PageSnapshot) {
+        let mut data = self.inner.lock().expect("session state lock");
+        if data.snapshots.get(app_id) == Some(&snapshot) {
+            return;
+        }
+        data.snapshots.insert(app_id.to_string(), snapshot);
+        self.persist(&data);
+    }
+
+    pub fn get_snapshot(&self, app_id: &str) -> Option<PageSnapshot> {
+        self.inner
+            .lock()
+            .expect("session state lock")
+            .snapshots
+            .get(app_id)
+            .cloned()
+    }
+
+    pub fn remove_snapshot(&self, app_id: &str) {
+        let mut data = self.inner.lock().expect("session state lock");
+        if data.snapshots.remove(app_id).is_some() {
+            self.persist(&data);
+        }
+    }
+
+    /// Record that the user activated an app just now.
+    pub fn touch_used(&self, app_id: &str) {
+        let mut data = self.inner.lock().expect("session state lock");
+        data.last_used
+            .insert(app_id.to_string(), chrono::Utc::now().timestamp());
+        self.persist(&data);
+    }
+
+    /// Record an app's latest combined badge.
+    pub fn set_badge(&self, app_id: &str, count: Option<i32>) {
+        let mut data = self.inner.lock().expect("session state lock");
+        let count = count.filter(|c| *c != 0);
+        if data.badges.get(app_id).copied() == count {
+            return;
+        }
+        match count {
+            Some(count) => data.badges.insert(app_id.to_string(), count),
+            None => data.badges.remove(app_id),
+        };
+        self.persist(&data);
+    }
+
+    /// When each app was last used and the badges they showed, as of the
+    /// previous session.
+    pub fn usage(&self) -> (HashMap<String, i64>, HashMap<String, i32>) {
+        let data = self.inner.lock().expect("session state lock");
+        (data.last_used.clone(), data.badges.clone())
+    }
+
+    /// Forget an app entirely (called when it is removed).
+    pub fn forget(&self, app_id: &str) {
+        let mut data = self.inner.lock().expect("session state lock");
+        data.active_apps.remove(app_id);
+        data.snapshots.remove(app_id);
+        data.last_used.remove(app_id);
+        data.badges.remove(app_id);
+        self.persist(&data);
+    }
+
     /// Clear the session state (called after successful restore).
+    /// Snapshots are kept; they are replayed as the apps are created.
     pub fn clear(&self) {
         let mut data = self.inner.lock().expect("session state lock");
         data.active_apps.clear();
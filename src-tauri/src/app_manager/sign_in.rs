@@ -0,0 +1,177 @@
+//! Signed-out detection.
+//!
+//! An app whose page lands on a sign-in page has lost its session and will
+//! stop delivering messages. Each navigation is matched against the app's
+//! login URL patterns: its own `login_url_patterns`, those of its recipe, and
+//! built-in ones for the service templates. A match marks the app signed out
+//! until it navigates anywhere else; the sidebar shows a badge for it and a
+//! single native notification is sent each time it happens.
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::state::AppManager;
+use crate::config::manager::ConfigManager;
+use crate::config::models::{AppConfig, NotificationStyle};
+use crate::notifications::handler::{is_in_dnd_schedule, send_native_notification};
+use crate::recipes::RecipeManager;
+use crate::utils::wildcard_match;
+
+/// Sign-in pages of the service templates, by service type.
+const BUILTIN_PATTERNS: &[(&str, &[&str])] = &[
+    ("gmail", &["accounts.google.com/*"]),
+    ("google-calendar", &["accounts.google.com/*"]),
+    ("google-drive", &["accounts.google.com/*"]),
+    ("youtube", &["accounts.google.com/*"]),
+    (
+        "outlook",
+        &["login.microsoftonline.com/*", "login.live.com/*"],
+    ),
+    (
+        "teams",
+        &["login.microsoftonline.com/*", "login.live.com/*"],
+    ),
+    ("slack", &["*.slack.com/signin*", "slack.com/signin*"]),
+    ("discord", &["discord.com/login*"]),
+    ("github", &["github.com/login*", "github.com/session*"]),
+    ("gitlab", &["gitlab.com/users/sign_in*"]),
+    ("notion", &["www.notion.so/login*"]),
+    ("linear", &["linear.app/login*"]),
+    ("trello", &["trello.com/login*", "id.atlassian.com/login*"]),
+    ("todoist", &["todoist.com/auth/login*"]),
+    ("figma", &["www.figma.com/login*"]),
+    ("protonmail", &["account.proton.me/login*"]),
+    ("messenger", &["www.messenger.com/login*"]),
+    ("twitter", &["x.com/i/flow/login*"]),
+    ("reddit", &["www.reddit.com/login*"]),
+    ("spotify", &["accounts.spotify.com/*"]),
+];
+
+/// Every login URL pattern that applies to an app.
+pub fn login_patterns(app_config: &AppConfig, recipe_patterns: &[String]) -> Vec<String> {
+    let builtin = BUILTIN_PATTERNS
+        .iter()
+        .find(|(service, _)| *service == app_config.service_type)
+        .map(|(_, patterns)| *patterns)
+        .unwrap_or_default();
+    app_config
+        .login_url_patterns
+        .iter()
+        .chain(recipe_patterns)
+        .map(|p| p.trim().to_string())
+        .chain(builtin.iter().map(|p| p.to_string()))
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Whether `url` is a sign-in page. A pattern starting with `/` is matched
+/// against the path, e.g. `/login`; one with a scheme against the whole URL;
+/// anything else against the host, path and query, e.g.
+/// `accounts.google.com/*`. `*` matches any run of characters.
+pub fn is_login_url(patterns: &[String], url: &str) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str() else {
+        return false;
+    };
+    let path = parsed.path();
+    let without_scheme = match parsed.query() {
+        Some(query) => format!("{host}{path}?{query}"),
+        None => format!("{host}{path}"),
+    };
+    patterns.iter().any(|pattern| {
+        if pattern.starts_with('/') {
+            wildcard_match(pattern.trim_end_matches('/'), path.trim_end_matches('/'))
+        } else if pattern.contains("://") {
+            wildcard_match(pattern, url)
+        } else {
+            wildcard_match(pattern, &without_scheme)
+        }
+    })
+}
+
+/// Update an app's signed-out status after it navigated to `url`, telling
+/// the frontend when it changes and the user when the app is signed out.
+pub fn on_navigation(app_handle: &AppHandle, app_id: &str, url: &str) {
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let Some(app_config) = config.apps.iter().find(|a| a.id == app_id) else {
+        return;
+    };
+    let recipe_patterns = app_handle
+        .try_state::<RecipeManager>()
+        .and_then(|rm| rm.get_recipe(&app_config.service_type))
+        .map(|r| r.login_url_patterns)
+        .unwrap_or_default();
+    let signed_out = is_login_url(&login_patterns(app_config, &recipe_patterns), url);
+    if !app_handle
+        .state::<AppManager>()
+        .set_signed_out(app_id, signed_out)
+    {
+        return;
+    }
+
+    let _ = app_handle.emit(
+        "app-signed-out",
+        serde_json::json!({ "appId": app_id, "signedOut": signed_out }),
+    );
+    if !signed_out {
+        log::info!("'{}' is signed in again", app_id);
+        return;
+    }
+    log::info!("'{}' landed on a sign-in page: {}", app_id, url);
+    if config.general.dnd_enabled
+        || is_in_dnd_schedule(&config)
+        || app_config.active_notification_style() == NotificationStyle::Off
+    {
+        return;
+    }
+    let _ = send_native_notification(
+        app_handle,
+        &format!("{} is signed out", app_config.name),
+        "Sign in again to keep receiving messages.",
+        app_id,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_login_url_patterns() {
+        let google = patterns(&["accounts.google.com/*"]);
+        assert!(is_login_url(
+            &google,
+            "https://accounts.google.com/v3/signin/identifier?continue=x"
+        ));
+        assert!(!is_login_url(&google, "https://mail.google.com/mail/u/0/"));
+
+        let path = patterns(&["/login"]);
+        assert!(is_login_url(&path, "https://chat.example.com/login?next=/"));
+        assert!(is_login_url(&path, "https://chat.example.com/login/"));
+        assert!(!is_login_url(&path, "https://chat.example.com/login-help"));
+        assert!(!is_login_url(&path, "https://chat.example.com/"));
+
+        let full = patterns(&["https://sso.example.com/*"]);
+        assert!(is_login_url(&full, "https://sso.example.com/auth"));
+        assert!(!is_login_url(&full, "http://sso.example.com/auth"));
+
+        assert!(!is_login_url(&google, "not a url"));
+    }
+
+    #[test]
+    fn test_login_patterns_combine_sources() {
+        let mut app = AppConfig::test_app("mail");
+        app.url = "https://mail.google.com".to_string();
+        app.service_type = "gmail".to_string();
+        app.login_url_patterns = vec!["/signin".to_string(), " ".to_string()];
+        assert_eq!(
+            login_patterns(&app, &patterns(&["*/ServiceLogin*"])),
+            patterns(&["/signin", "*/ServiceLogin*", "accounts.google.com/*"])
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Staggered startup.
+//!
+//! `frontend_ready` builds the webview of the app to show first straight
+//! away and queues the other apps. The queue keeps at most
+//! `general.startup_concurrency` pages loading at once, taking pinned apps
+//! first, then apps that showed a badge when Orbly last ran, then the most
+//! recently used. With `general.defer_idle_apps`, apps with none of those
+//! wait until Orbly is idle. Apps with a `Delayed` launch policy join the
+//! queue once their delay is up. Progress is reported as `startup-progress`.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::state::{AppManager, AppRuntimeState, ContentBounds, StateKind};
+use super::{lifecycle, set_app_state};
+use crate::config::manager::ConfigManager;
+use crate::config::models::{AppConfig, LaunchPolicy, OrblyConfig};
+
+/// Apps used within this long count as recently used.
+const RECENT_SECS: i64 = 7 * 24 * 60 * 60;
+/// A page still loading after this long no longer holds up the queue.
+const LOAD_SLOT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Deferred apps load once nothing is loading and nobody has touched an app
+/// for this long.
+const IDLE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StartupPriority {
+    Pinned,
+    Badged,
+    Recent,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueuedApp {
+    pub app_id: String,
+    pub priority: StartupPriority,
+    /// Waits until Orbly is idle
+    pub deferred: bool,
+    /// Waits this long after startup
+    pub delay_secs: u32,
+    /// The app's `hibernated` flag when the queue was built, so a flag set
+    /// since (the app was hibernated during startup) can be told apart
+    pub was_hibernated: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StartupProgress {
+    pub loaded: usize,
+    pub total: usize,
+    /// Apps waiting for Orbly to be idle
+    pub deferred: usize,
+    /// Apps waiting out their launch delay
+    pub delayed: usize,
+}
+
+/// Enabled apps that are loaded at startup. The launch policy decides, so
+/// an app's persisted `hibernated` flag only counts under `Restore`.
+fn startup_apps(config: &OrblyConfig) -> impl Iterator<Item = &AppConfig> {
+    config
+        .apps
+        .iter()
+        .filter(|a| a.enabled && !a.starts_hibernated())
+}
+
+/// The app to show first: the most recently used app of the active
+/// workspace, or its first app. Delayed apps are never shown first.
+pub fn first_app(config: &OrblyConfig, last_used: &HashMap<String, i64>) -> Option<String> {
+    let workspace = config
+        .workspaces
+        .items
+        .iter()
+        .find(|ws| ws.id == config.workspaces.active && ws.id != "default");
+    let candidates = || startup_apps(config).filter(|a| a.launch != LaunchPolicy::Delayed);
+    let in_workspace: Vec<&AppConfig> = candidates()
+        .filter(|a| workspace.is_none_or(|ws| ws.app_ids.contains(&a.id)))
+        .collect();
+    let mut most_recent: Option<(&AppConfig, i64)> = None;
+    for app in &in_workspace {
+        if let Some(at) = last_used.get(&app.id).copied() {
+            // The first of equally recent apps wins
+            if most_recent.is_none_or(|(_, best)| at > best) {
+                most_recent = Some((app, at));
+            }
+        }
+    }
+    most_recent
+        .map(|(app, _)| app)
+        .or_else(|| in_workspace.first().copied())
+        .or_else(|| candidates().next())
+        .map(|app| app.id.clone())
+}
+
+/// The apps to load after `first`, in the order to load them.
+pub fn startup_queue(
+    config: &OrblyConfig,
+    first: Option<&str>,
+    last_used: &HashMap<String, i64>,
+    badges: &HashMap<String, i32>,
+    now: i64,
+) -> Vec<QueuedApp> {
+    let mut queue: Vec<(QueuedApp, i64)> = startup_apps(config)
+        .filter(|a| Some(a.id.as_str()) != first)
+        .map(|a| {
+            let used = last_used.get(&a.id).copied();
+            let priority = if a.pinned {
+                StartupPriority::Pinned
+            } else if badges.get(&a.id).is_some_and(|c| *c != 0) {
+                StartupPriority::Badged
+            } else if used.is_some_and(|at| now - at < RECENT_SECS) {
+                StartupPriority::Recent
+            } else {
+                StartupPriority::Other
+            };
+            let delay_secs = match a.launch {
+                LaunchPolicy::Delayed => a.launch_delay_secs,
+                _ => 0,
+            };
+            let app = QueuedApp {
+                app_id: a.id.clone(),
+                priority,
+                deferred: config.general.defer_idle_apps
+                    && priority == StartupPriority::Other
+                    && delay_secs == 0,
+                delay_secs,
+                was_hibernated: a.hibernated,
+            };
+            (app, used.unwrap_or(i64::MIN))
+        })
+        .collect();
+    // Stable, so apps used equally long ago keep their sidebar order
+    queue.sort_by(|(a, a_used), (b, b_used)| a.priority.cmp(&b.priority).then(b_used.cmp(a_used)));
+    queue.into_iter().map(|(app, _)| app).collect()
+}
+
+/// Apps `new` enables that `old` had disabled and that should load now:
+/// those whose launch policy does not keep them hibernated.
+pub fn reenabled_queue(old: &OrblyConfig, new: &OrblyConfig) -> Vec<QueuedApp> {
+    startup_apps(new)
+        .filter(|a| old.apps.iter().any(|o| o.id == a.id && !o.enabled))
+        .map(|a| QueuedApp {
+            app_id: a.id.clone(),
+            priority: StartupPriority::Other,
+            deferred: false,
+            delay_secs: 0,
+            was_hibernated: a.hibernated,
+        })
+        .collect()
+}
+
+/// Spawns a background task that loads `queue`, hidden, a few apps at a time.
+pub fn start_queue(app_handle: AppHandle, queue: Vec<QueuedApp>) {
+    if queue.is_empty() {
+        return;
+    }
+    // Also called from synchronous commands, outside the runtime
+    tauri::async_runtime::spawn(async move {
+        let concurrency = app_handle
+            .state::<ConfigManager>()
+            .get_config()
+            .general
+            .startup_concurrency
+            .max(1) as usize;
+        let total = queue.len();
+        let queue_started = Instant::now();
+        let (mut delayed, queue): (Vec<QueuedApp>, Vec<QueuedApp>) =
+            queue.into_iter().partition(|app| app.delay_secs > 0);
+        let (deferred, now): (Vec<QueuedApp>, Vec<QueuedApp>) =
+            queue.into_iter().partition(|app| app.deferred);
+        let mut pending: VecDeque<QueuedApp> = now.into();
+        let mut deferred: VecDeque<QueuedApp> = deferred.into();
+        let mut loading: Vec<(String, Instant)> = Vec::new();
+        // Deferred apps load one at a time
+        let mut idle_phase = false;
+        let mut last_progress = None;
+        let mut interval = tokio::time::interval(Duration::from_millis(250));
+        loop {
+            interval.tick().await;
+
+            let app_manager = app_handle.state::<AppManager>();
+            loading.retain(|(app_id, started)| {
+                started.elapsed() < LOAD_SLOT_TIMEOUT
+                    && app_manager
+                        .get_state(app_id)
+                        .is_some_and(|r| r.state.kind() == StateKind::Loading)
+            });
+
+            let elapsed = queue_started.elapsed();
+            let (due, waiting): (Vec<QueuedApp>, Vec<QueuedApp>) = delayed
+                .into_iter()
+                .partition(|app| elapsed >= Duration::from_secs(app.delay_secs.into()));
+            delayed = waiting;
+            pending.extend(due);
+
+            if pending.is_empty() && loading.is_empty() && is_idle(&app_manager) {
+                if let Some(app) = deferred.pop_front() {
+                    pending.push_back(app);
+                    idle_phase = true;
+                }
+            }
+            let limit = if idle_phase { 1 } else { concurrency };
+            while loading.len() < limit {
+                let Some(app) = pending.pop_front() else {
+                    break;
+                };
+                if start_app(&app_handle, &app) {
+                    loading.push((app.app_id, Instant::now()));
+                }
+            }
+
+            let progress = StartupProgress {
+                loaded: total - pending.len() - deferred.len() - delayed.len() - loading.len(),
+                total,
+                deferred: deferred.len(),
+                delayed: delayed.len(),
+            };
+            if last_progress.as_ref() != Some(&progress) {
+                let _ = app_handle.emit("startup-progress", &progress);
+                last_progress = Some(progress);
+            }
+            if pending.is_empty() && deferred.is_empty() && delayed.is_empty() && loading.is_empty()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// Nothing is loading and no app has been used for a while.
+fn is_idle(app_manager: &AppManager) -> bool {
+    let apps = app_manager.apps.lock().expect("apps lock");
+    !apps.values().any(|r| {
+        r.state.kind() == StateKind::Loading
+            || r.last_interaction
+                .is_some_and(|at| at.elapsed() < IDLE_AFTER)
+    })
+}
+
+/// Build the webview of a queued app, hidden, at the URL it is waiting at.
+/// Returns false if the app no longer needs loading, e.g. because the user
+/// opened or hibernated it in the meantime.
+fn start_app(app_handle: &AppHandle, queued: &QueuedApp) -> bool {
+    let app_id = queued.app_id.as_str();
+    if app_handle.get_webview(app_id).is_some() {
+        return false;
+    }
+    let Some(mut app_config) = app_handle.state::<ConfigManager>().get_app(app_id) else {
+        return false;
+    };
+    if !app_config.enabled || (app_config.hibernated && !queued.was_hibernated) {
+        return false;
+    }
+    let app_manager = app_handle.state::<AppManager>();
+    let Some(AppRuntimeState::Hibernated { last_url }) =
+        app_manager.get_state(app_id).map(|r| r.state)
+    else {
+        return false;
+    };
+    if !last_url.is_empty() {
+        app_config.url = last_url;
+    }
+    if !set_app_state(
+        app_handle,
+        app_id,
+        AppRuntimeState::Loading {
+            target_url: app_config.url.clone(),
+        },
+        "startup",
+    ) {
+        return false;
+    }
+
+    let bounds = app_handle.state::<ContentBounds>().get();
+    let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
+    let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+    if let Err(e) = lifecycle::create_app_webview(app_handle, &app_config, position, size) {
+        log::error!("Failed to create webview for {}: {}", app_id, e);
+        set_app_state(
+            app_handle,
+            app_id,
+            AppRuntimeState::Error { message: e },
+            "startup",
+        );
+        return false;
+    }
+    let _ = lifecycle::set_webview_visible(app_handle, app_id, false, None, None);
+    app_manager.set_visible(app_id, false);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::models::Workspace;
+
+    fn ids(queue: &[QueuedApp]) -> Vec<&str> {
+        queue.iter().map(|a| a.app_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_startup_order() {
+        let now = 1_000_000_000;
+        let mut config = OrblyConfig::default();
+        config.apps = ["mail", "chat", "docs", "music", "old", "off"]
+            .map(AppConfig::test_app)
+            .to_vec();
+        config.apps[3].pinned = true;
+        config.apps[5].enabled = false;
+        config.workspaces.items.push(Workspace {
+            id: "work".to_string(),
+            name: "Work".to_string(),
+            app_ids: vec!["chat".to_string(), "docs".to_string()],
+            tiling_layout: String::new(),
+            tile_assignments: Vec::new(),
+            schedule: None,
+        });
+        config.workspaces.active = "work".to_string();
+        let last_used = HashMap::from([
+            ("mail".to_string(), now - 10),
+            ("docs".to_string(), now - 60),
+            ("old".to_string(), now - 30 * 24 * 60 * 60),
+        ]);
+        let badges = HashMap::from([("chat".to_string(), 3)]);
+
+        // Mail was used last, but docs is the latest of the workspace
+        let first = first_app(&config, &last_used);
+        assert_eq!(first.as_deref(), Some("docs"));
+        assert_eq!(first_app(&config, &HashMap::new()).as_deref(), Some("chat"));
+
+        let queue = startup_queue(&config, first.as_deref(), &last_used, &badges, now);
+        assert_eq!(ids(&queue), vec!["music", "chat", "mail", "old"]);
+        assert!(queue.iter().all(|a| !a.deferred));
+
+        config.general.defer_idle_apps = true;
+        let queue = startup_queue(&config, first.as_deref(), &last_used, &badges, now);
+        assert_eq!(queue.last().unwrap().priority, StartupPriority::Other);
+        assert_eq!(
+            queue
+                .iter()
+                .filter(|a| a.deferred)
+                .map(|a| a.app_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["old"]
+        );
+    }
+
+    #[test]
+    fn test_launch_policies() {
+        let mut config = OrblyConfig::default();
+        config.apps = ["mail", "chat", "docs", "music"]
+            .map(AppConfig::test_app)
+            .to_vec();
+        config.apps[0].launch = LaunchPolicy::Delayed;
+        config.apps[0].launch_delay_secs = 60;
+        config.apps[0].hibernated = true;
+        config.apps[1].launch = LaunchPolicy::Hibernated;
+        config.apps[2].launch = LaunchPolicy::Active;
+        config.apps[2].hibernated = true;
+        config.apps[3].hibernated = true;
+        config.general.defer_idle_apps = true;
+
+        // Docs starts active despite having been hibernated; music stays as it was
+        assert!(config
+            .apps
+            .iter()
+            .map(|a| a.starts_hibernated())
+            .eq([false, true, false, true]));
+
+        // Mail comes first in the sidebar but waits out its delay
+        assert_eq!(first_app(&config, &HashMap::new()).as_deref(), Some("docs"));
+        let queue = startup_queue(&config, Some("docs"), &HashMap::new(), &HashMap::new(), 0);
+        assert_eq!(ids(&queue), vec!["mail"]);
+        assert_eq!(queue[0].delay_secs, 60);
+        // Its stale flag does not keep it from loading
+        assert!(queue[0].was_hibernated);
+        assert!(!queue[0].deferred);
+    }
+
+    #[test]
+    fn test_reenabled_apps() {
+        let mut old = OrblyConfig {
+            apps: ["mail", "chat", "docs", "music"]
+                .map(AppConfig::test_app)
+                .to_vec(),
+            ..Default::default()
+        };
+        for app in &mut old.apps[..3] {
+            app.enabled = false;
+        }
+        let mut new = old.clone();
+        for app in &mut new.apps {
+            app.enabled = true;
+        }
+        new.apps[1].launch = LaunchPolicy::Hibernated;
+        new.apps[2].hibernated = true;
+
+        // Chat and docs come back hibernated; music was never disabled
+        let queue = reenabled_queue(&old, &new);
+        assert_eq!(ids(&queue), vec!["mail"]);
+        assert!(queue.iter().all(|a| !a.deferred && a.delay_secs == 0));
+        assert!(reenabled_queue(&new, &new).is_empty());
+    }
+}
@@ -1,6 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Transitions kept per app in the state history.
+const MAX_HISTORY: usize = 50;
 
 #[derive(Debug, Clone)]
 pub enum AppRuntimeState {
@@ -18,15 +23,100 @@ pub enum AppRuntimeState {
     Crashed,
 }
 
+impl AppRuntimeState {
+    pub fn kind(&self) -> StateKind {
+        match self {
+            Self::Loading { .. } => StateKind::Loading,
+            Self::Active { .. } => StateKind::Active,
+            Self::Hibernated { .. } => StateKind::Hibernated,
+            Self::Disabled => StateKind::Disabled,
+            Self::Error { .. } => StateKind::Error,
+            Self::Crashed => StateKind::Crashed,
+        }
+    }
+}
+
+/// An `AppRuntimeState` without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateKind {
+    Loading,
+    Active,
+    Hibernated,
+    Disabled,
+    Error,
+    Crashed,
+}
+
+impl StateKind {
+    /// Whether an app may move from `self` to `to`. Staying within a kind
+    /// (e.g. an active app navigating to a new URL) is always allowed. An app
+    /// without a webview (hibernated, disabled or crashed) has to go through
+    /// `Loading` before it can be active again.
+    pub fn can_become(self, to: StateKind) -> bool {
+        use StateKind::*;
+        self == to
+            || matches!(
+                (self, to),
+                (Loading, Active | Hibernated | Disabled | Error | Crashed)
+                    | (Active, Loading | Hibernated | Disabled | Error | Crashed)
+                    | (Hibernated, Loading | Disabled)
+                    | (Disabled, Loading)
+                    | (Error, Loading | Active | Hibernated | Disabled | Crashed)
+                    | (Crashed, Loading | Hibernated | Disabled | Error)
+            )
+    }
+}
+
+/// Combine badge counts: the sum of the known counts, or -1 ("unread, count
+/// unknown") if none is positive but one is, or `None` if there are none.
+pub fn combine_badges(counts: impl IntoIterator<Item = i32>) -> Option<i32> {
+    let mut known = false;
+    let mut total = 0;
+    let mut unread = false;
+    for count in counts {
+        known = true;
+        if count > 0 {
+            total += count;
+        } else if count < 0 {
+            unread = true;
+        }
+    }
+    match (known, total, unread) {
+        (false, _, _) => None,
+        (true, 0, true) => Some(-1),
+        (true, total, _) => Some(total),
+    }
+}
+
+/// A recorded change of an app's state kind.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateTransition {
+    pub from: StateKind,
+    pub to: StateKind,
+    pub at: String,
+    pub reason: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct AppRuntime {
     pub state: AppRuntimeState,
+    /// Combined badge of all accounts
     pub badge_count: Option<i32>,
+    /// Last badge seen per account id ("" for the default account). Only the
+    /// signed-in account is live; the others keep their last count.
+    pub account_badges: HashMap<String, i32>,
     pub last_interaction: Option<Instant>,
     pub last_heartbeat: Option<Instant>,
     pub is_playing_media: bool,
     pub is_visible: bool,
     pub has_unsaved_work: bool,
+    /// Last landed on a sign-in page
+    pub signed_out: bool,
+    /// Crashes since launch
+    pub crash_count: u32,
+    /// When recent crashes happened, for crash-loop detection
+    pub recent_crashes: Vec<Instant>,
 }
 
 pub struct AppManager {
@@ -34,6 +124,8 @@ pub struct AppManager {
     pub apps: Mutex<HashMap<String, AppRuntime>>,
     /// URLs queued for navigation after an app is activated (e.g., from link routing to hibernated apps)
     pub pending_navigations: Mutex<HashMap<String, String>>,
+    /// Recent state transitions per app, oldest first
+    history: Mutex<HashMap<String, VecDeque<StateTransition>>>,
 }
 
 #[allow(dead_code)]
@@ -42,6 +134,7 @@ impl AppManager {
         Self {
             apps: Mutex::new(HashMap::new()),
             pending_navigations: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
         }
     }
 
@@ -51,7 +144,7 @@ impl AppManager {
         for app in apps {
             let state = if !app.enabled {
                 AppRuntimeState::Disabled
-            } else if app.hibernated {
+            } else if app.starts_hibernated() {
                 AppRuntimeState::Hibernated {
                     last_url: app.url.clone(),
                 }
@@ -66,11 +159,15 @@ impl AppManager {
                 AppRuntime {
                     state,
                     badge_count: None,
+                    account_badges: HashMap::new(),
                     last_interaction: None,
                     last_heartbeat: None,
                     is_playing_media: false,
                     is_visible: false,
                     has_unsaved_work: false,
+                    signed_out: false,
+                    crash_count: 0,
+                    recent_crashes: Vec::new(),
                 },
             );
         }
@@ -80,30 +177,149 @@ impl AppManager {
         self.apps.lock().expect("apps lock").get(app_id).cloned()
     }
 
-    pub fn set_state(&self, app_id: &str, state: AppRuntimeState) {
-        if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
-            runtime.state = state;
+    /// Move an app to `state`, recording `reason` in its history. Fails if
+    /// the app is unknown or the transition table does not allow the move.
+    /// Returns the recorded transition, or `None` when the app stayed in the
+    /// same kind of state.
+    pub fn transition(
+        &self,
+        app_id: &str,
+        state: AppRuntimeState,
+        reason: &str,
+    ) -> Result<Option<StateTransition>, String> {
+        self.transition_inner(app_id, None, state, reason)
+    }
+
+    /// Like `transition`, but only if the app is still in one of `expected`.
+    /// For changes decided on an earlier look at the state, which may have
+    /// moved on since.
+    pub fn transition_if(
+        &self,
+        app_id: &str,
+        expected: &[StateKind],
+        state: AppRuntimeState,
+        reason: &str,
+    ) -> Result<Option<StateTransition>, String> {
+        self.transition_inner(app_id, Some(expected), state, reason)
+    }
+
+    fn transition_inner(
+        &self,
+        app_id: &str,
+        expected: Option<&[StateKind]>,
+        state: AppRuntimeState,
+        reason: &str,
+    ) -> Result<Option<StateTransition>, String> {
+        let mut apps = self.apps.lock().expect("apps lock");
+        let runtime = apps
+            .get_mut(app_id)
+            .ok_or_else(|| format!("App '{}' has no runtime state", app_id))?;
+        let from = runtime.state.kind();
+        let to = state.kind();
+        if expected.is_some_and(|kinds| !kinds.contains(&from)) {
+            return Err(format!(
+                "App '{}' is {:?}, no longer {:?}",
+                app_id,
+                from,
+                expected.unwrap_or_default()
+            ));
+        }
+        if !from.can_become(to) {
+            return Err(format!(
+                "App '{}' cannot go from {:?} to {:?} ({})",
+                app_id, from, to, reason
+            ));
+        }
+        runtime.state = state;
+        drop(apps);
+        if from == to {
+            return Ok(None);
         }
+
+        let transition = StateTransition {
+            from,
+            to,
+            at: chrono::Utc::now().to_rfc3339(),
+            reason: reason.to_string(),
+        };
+        let mut history = self.history.lock().expect("history lock");
+        let entries = history.entry(app_id.to_string()).or_default();
+        entries.push_back(transition.clone());
+        if entries.len() > MAX_HISTORY {
+            entries.pop_front();
+        }
+        Ok(Some(transition))
+    }
+
+    /// State transitions of an app, oldest first.
+    pub fn history(&self, app_id: &str) -> Vec<StateTransition> {
+        self.history
+            .lock()
+            .expect("history lock")
+            .get(app_id)
+            .map(|h| h.iter().cloned().collect())
+            .unwrap_or_default()
     }
 
-    pub fn set_badge_count(&self, app_id: &str, count: Option<i32>) {
+    /// Set the badge of one of an app's accounts and recombine the app's badge.
+    pub fn set_badge_count(&self, app_id: &str, account_id: &str, count: Option<i32>) {
         if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
-            runtime.badge_count = count;
+            match count {
+                Some(count) => runtime.account_badges.insert(account_id.to_string(), count),
+                None => runtime.account_badges.remove(account_id),
+            };
+            runtime.badge_count = combine_badges(runtime.account_badges.values().copied());
         }
     }
 
+    /// Drop the badge of an account that no longer exists.
+    pub fn forget_account_badge(&self, app_id: &str, account_id: &str) {
+        self.set_badge_count(app_id, account_id, None);
+    }
+
     pub fn touch_interaction(&self, app_id: &str) {
         if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
             runtime.last_interaction = Some(Instant::now());
         }
     }
 
+    /// Count a crash and return how many happened within `window`,
+    /// including this one.
+    pub fn record_crash(&self, app_id: &str, window: Duration) -> usize {
+        let mut apps = self.apps.lock().expect("apps lock");
+        let Some(runtime) = apps.get_mut(app_id) else {
+            return 0;
+        };
+        runtime.crash_count += 1;
+        runtime.recent_crashes.retain(|t| t.elapsed() < window);
+        runtime.recent_crashes.push(Instant::now());
+        runtime.recent_crashes.len()
+    }
+
+    /// Forget recent crashes, e.g. when the user reloads a parked app.
+    pub fn clear_recent_crashes(&self, app_id: &str) {
+        if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
+            runtime.recent_crashes.clear();
+        }
+    }
+
     pub fn touch_heartbeat(&self, app_id: &str) {
         if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
             runtime.last_heartbeat = Some(Instant::now());
         }
     }
 
+    /// Record whether an app is signed out. Returns whether that changed.
+    pub fn set_signed_out(&self, app_id: &str, signed_out: bool) -> bool {
+        match self.apps.lock().expect("apps lock").get_mut(app_id) {
+            Some(runtime) if runtime.signed_out != signed_out => {
+                runtime.signed_out = signed_out;
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn set_visible(&self, app_id: &str, visible: bool) {
         if let Some(runtime) = self.apps.lock().expect("apps lock").get_mut(app_id) {
             runtime.is_visible = visible;
@@ -112,6 +328,7 @@ impl AppManager {
 
     pub fn remove(&self, app_id: &str) {
         self.apps.lock().expect("apps lock").remove(app_id);
+        self.history.lock().expect("history lock").remove(app_id);
     }
 
     pub fn set_pending_navigation(&self, app_id: &str, url: String) {
@@ -165,3 +382,131 @@ impl ContentBounds {
         *self.inner.lock().expect("content bounds lock")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::models::AppConfig;
+
+    fn manager_with_app() -> AppManager {
+        let manager = AppManager::new();
+        manager.init_from_config(&[AppConfig::test_app("mail")]);
+        manager
+    }
+
+    fn active(url: &str) -> AppRuntimeState {
+        AppRuntimeState::Active {
+            current_url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hibernated_app_cannot_become_active_without_loading() {
+        let manager = manager_with_app();
+        assert!(manager
+            .transition("mail", active("https://mail.example.com/"), "navigation")
+            .is_err());
+
+        let loading = AppRuntimeState::Loading {
+            target_url: "https://mail.example.com".to_string(),
+        };
+        manager.transition("mail", loading, "activate_app").unwrap();
+        let recorded = manager
+            .transition("mail", active("https://mail.example.com/"), "navigation")
+            .unwrap();
+        assert_eq!(recorded.map(|t| t.to), Some(StateKind::Active));
+
+        // Navigating within Active updates the URL but is not history
+        assert!(manager
+            .transition(
+                "mail",
+                active("https://mail.example.com/inbox"),
+                "navigation"
+            )
+            .unwrap()
+            .is_none());
+        let history = manager.history("mail");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].from, StateKind::Hibernated);
+        assert_eq!(history[1].reason, "navigation");
+    }
+
+    #[test]
+    fn test_transition_if_rejects_stale_decisions() {
+        let manager = manager_with_app();
+        let loading = AppRuntimeState::Loading {
+            target_url: "https://mail.example.com".to_string(),
+        };
+        manager.transition("mail", loading, "activate_app").unwrap();
+        manager
+            .transition("mail", active("https://mail.example.com/"), "navigation")
+            .unwrap();
+
+        // A load timeout decided while the app was still loading
+        let error = AppRuntimeState::Error {
+            message: "Page took too long to load".to_string(),
+        };
+        assert!(manager
+            .transition_if("mail", &[StateKind::Loading], error, "load timeout")
+            .is_err());
+        assert_eq!(
+            manager.get_state("mail").unwrap().state.kind(),
+            StateKind::Active
+        );
+    }
+
+    #[test]
+    fn test_record_crash_counts_within_window() {
+        let manager = manager_with_app();
+        let window = Duration::from_secs(600);
+        assert_eq!(manager.record_crash("mail", window), 1);
+        assert_eq!(manager.record_crash("mail", window), 2);
+        // Crashes older than the window no longer count towards a loop
+        assert_eq!(manager.record_crash("mail", Duration::ZERO), 1);
+        manager.clear_recent_crashes("mail");
+        assert_eq!(manager.get_state("mail").unwrap().crash_count, 3);
+        assert!(manager.get_state("mail").unwrap().recent_crashes.is_empty());
+        assert_eq!(manager.record_crash("unknown", window), 0);
+    }
+
+    #[test]
+    fn test_badges_combine_across_accounts() {
+        let manager = manager_with_app();
+        manager.set_badge_count("mail", "", Some(0));
+        manager.set_badge_count("mail", "work", Some(-1));
+        assert_eq!(manager.get_state("mail").unwrap().badge_count, Some(-1));
+        manager.set_badge_count("mail", "", Some(3));
+        manager.set_badge_count("mail", "home", Some(2));
+        assert_eq!(manager.get_state("mail").unwrap().badge_count, Some(5));
+        manager.forget_account_badge("mail", "home");
+        manager.set_badge_count("mail", "", None);
+        assert_eq!(manager.get_state("mail").unwrap().badge_count, Some(-1));
+        manager.forget_account_badge("mail", "work");
+        assert_eq!(manager.get_state("mail").unwrap().badge_count, None);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let manager = manager_with_app();
+        for i in 0..(MAX_HISTORY + 10) {
+            let state = if i % 2 == 0 {
+                AppRuntimeState::Loading {
+                    target_url: String::new(),
+                }
+            } else {
+                AppRuntimeState::Hibernated {
+                    last_url: String::new(),
+                }
+            };
+            manager
+                .transition("mail", state, &format!("step {i}"))
+                .unwrap();
+        }
+        let history = manager.history("mail");
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(
+            history.last().unwrap().reason,
+            format!("step {}", MAX_HISTORY + 9)
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Unsaved work.
+//!
+//! Each app's page reports whether leaving it now would lose work: the user
+//! has typed into a form field or editable area that has not been submitted or
+//! emptied since, or has typed on a page that registers `beforeunload`
+//! handlers. The handlers are tracked, never run. Hibernating or
+//! reloading such an app by hand fails unless forced, Orbly's own hibernation
+//! leaves it alone, and quitting lists every such app and waits for the user
+//! to confirm.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use super::state::AppManager;
+use crate::config::manager::ConfigManager;
+use crate::config::models::OrblyConfig;
+
+/// Whether the user has agreed to quit despite unsaved work, and whether
+/// they are being asked.
+pub struct QuitGuard {
+    confirmed: AtomicBool,
+    asking: AtomicBool,
+}
+
+impl QuitGuard {
+    pub fn new() -> Self {
+        Self {
+            confirmed: AtomicBool::new(false),
+            asking: AtomicBool::new(false),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsavedApp {
+    pub id: String,
+    pub name: String,
+}
+
+/// Whether leaving the app's page now would lose work. An app without a
+/// webview has nothing to lose, whatever its page last reported.
+pub fn has_unsaved_work(app_handle: &AppHandle, app_id: &str) -> bool {
+    app_handle.get_webview(app_id).is_some()
+        && app_handle
+            .state::<AppManager>()
+            .get_state(app_id)
+            .is_some_and(|r| r.has_unsaved_work)
+}
+
+/// Fails with a message for the user if the app has unsaved work, unless
+/// `force` is set.
+pub fn check(app_handle: &AppHandle, app_id: &str, force: bool) -> Result<(), String> {
+    if force || !has_unsaved_work(app_handle, app_id) {
+        return Ok(());
+    }
+    let name = app_handle
+        .state::<ConfigManager>()
+        .get_app(app_id)
+        .map(|a| a.name)
+        .unwrap_or_else(|| app_id.to_string());
+    Err(format!("{name} has unsaved work"))
+}
+
+/// The apps `is_unsaved` picks out, in sidebar order.
+pub fn unsaved_apps(config: &OrblyConfig, is_unsaved: impl Fn(&str) -> bool) -> Vec<UnsavedApp> {
+    let mut apps: Vec<_> = config.apps.iter().filter(|a| is_unsaved(&a.id)).collect();
+    apps.sort_by_key(|a| a.position);
+    apps.into_iter()
+        .map(|a| UnsavedApp {
+            id: a.id.clone(),
+            name: a.name.clone(),
+        })
+        .collect()
+}
+
+/// Hold off quitting if any app has unsaved work and the user has not
+/// confirmed yet, asking them instead. Orbly quits once they confirm.
+/// Returns whether quitting was held off.
+pub fn hold_quit(app_handle: &AppHandle) -> bool {
+    let guard = app_handle.state::<QuitGuard>();
+    if guard.confirmed.load(Ordering::SeqCst) {
+        return false;
+    }
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let apps = unsaved_apps(&config, |id| has_unsaved_work(app_handle, id));
+    if apps.is_empty() {
+        return false;
+    }
+    if guard.asking.swap(true, Ordering::SeqCst) {
+        return true;
+    }
+
+    let names: Vec<String> = apps.iter().map(|a| format!("• {}", a.name)).collect();
+    let mut dialog = app_handle
+        .dialog()
+        .message(format!(
+            "These apps have unsaved work that will be lost:\n\n{}",
+            names.join("\n")
+        ))
+        .title("Quit Orbly?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Quit Anyway".to_string(),
+            "Cancel".to_string(),
+        ));
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        dialog = dialog.parent(&window);
+    }
+    let app_handle = app_handle.clone();
+    dialog.show(move |confirmed| {
+        let guard = app_handle.state::<QuitGuard>();
+        guard.asking.store(false, Ordering::SeqCst);
+        if confirmed {
+            guard.confirmed.store(true, Ordering::SeqCst);
+            quit(&app_handle);
+        }
+    });
+    true
+}
+
+/// Shut down cleanly, as closing the main window does.
+fn quit(app_handle: &AppHandle) {
+    if let Some(session_state) = app_handle.try_state::<super::session_state::SessionState>() {
+        session_state.clear();
+    }
+    super::popout::close_all(app_handle);
+    super::auth_popup::close_all(app_handle);
+    app_handle.exit(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::models::AppConfig;
+
+    #[test]
+    fn test_unsaved_apps_in_sidebar_order() {
+        let mut config = OrblyConfig::default();
+        for (id, position) in [("mail", 2), ("chat", 0), ("docs", 1)] {
+            let mut app = AppConfig::test_app(id);
+            app.name = id.to_uppercase();
+            app.position = position;
+            config.apps.push(app);
+        }
+        let unsaved = unsaved_apps(&config, |id| id != "chat");
+        assert_eq!(
+            unsaved,
+            vec![
+                UnsavedApp {
+                    id: "docs".to_string(),
+                    name: "DOCS".to_string(),
+                },
+                UnsavedApp {
+                    id: "mail".to_string(),
+                    name: "MAIL".to_string(),
+                },
+            ]
+        );
+        assert!(unsaved_apps(&config, |_| false).is_empty());
+    }
+}
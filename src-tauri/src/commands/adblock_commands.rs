@@ -14,7 +14,7 @@ pub fn toggle_adblock(
     crate::commands::require_main_webview(&webview)?;
     let mut new_state: Option<bool> = None;
     config_manager
-        .update_with(|config| {
+        .update_with("toggle_adblock", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.adblock_enabled = !app.adblock_enabled;
                 new_state = Some(app.adblock_enabled);
@@ -35,6 +35,8 @@ pub fn toggle_adblock(
             let _ = webview.eval(script);
         }
     }
+    #[cfg(target_os = "linux")]
+    crate::adblock::content_rules::attach_filter(&app_handle, &app_id);
 
     Ok(new_state)
 }
@@ -68,7 +70,7 @@ pub fn update_filter_lists(webview: tauri::Webview, app_handle: AppHandle) -> Re
                 // Update last_updated timestamp
                 let config_manager = app_handle.state::<ConfigManager>();
                 let now = chrono::Utc::now().to_rfc3339();
-                let _ = config_manager.update_with(|config| {
+                let _ = config_manager.update_quietly("update_filter_lists", |config| {
                     config.adblock.last_updated = now.clone();
                 });
 
@@ -89,6 +91,7 @@ pub fn update_filter_lists(webview: tauri::Webview, app_handle: AppHandle) -> Re
 pub fn add_custom_adblock_rule(
     rule: String,
     webview: tauri::Webview,
+    app_handle: AppHandle,
     config_manager: State<'_, ConfigManager>,
     adblock_state: State<'_, AdblockState>,
 ) -> Result<(), String> {
@@ -96,7 +99,7 @@ pub fn add_custom_adblock_rule(
     let mut updated_rules: Option<Vec<String>> = None;
 
     config_manager
-        .update_with(|config| {
+        .update_with("add_custom_adblock_rule", |config| {
             config.adblock.custom_rules.push(rule.clone());
             updated_rules = Some(config.adblock.custom_rules.clone());
         })
@@ -108,6 +111,7 @@ pub fn add_custom_adblock_rule(
         .unwrap_or_default();
     if let Some(rules) = updated_rules {
         adblock_state.load_rules(&rules_text, &rules);
+        let _ = app_handle.emit("content-rules-updated", ());
     }
 
     Ok(())
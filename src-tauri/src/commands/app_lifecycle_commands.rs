@@ -2,8 +2,13 @@ use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::app_manager::certificate::CertificateExceptions;
 use crate::app_manager::lifecycle;
-use crate::app_manager::session_state::SessionState;
-use crate::app_manager::state::{AppManager, AppRuntimeState, ContentBounds};
+use crate::app_manager::page_snapshot;
+use crate::app_manager::popout;
+use crate::app_manager::session_state::{PageSnapshot, SessionState};
+use crate::app_manager::startup;
+use crate::app_manager::set_app_state;
+use crate::app_manager::state::{AppManager, AppRuntimeState, ContentBounds, StateKind, StateTransition};
+use crate::app_manager::unsaved_work;
 use crate::config::manager::ConfigManager;
 
 #[derive(serde::Serialize, Clone)]
@@ -12,8 +17,14 @@ pub struct AppStateInfo {
     pub name: String,
     pub state: String, // "active", "hibernated", "disabled"
     pub badge_count: Option<i32>,
+    /// Last badge per account id ("" for the default account)
+    pub account_badges: std::collections::HashMap<String, i32>,
     pub current_url: Option<String>,
     pub error_message: Option<String>,
+    /// Crashes since launch
+    pub crash_count: u32,
+    /// Last landed on a sign-in page
+    pub signed_out: bool,
 }
 
 #[tauri::command]
@@ -57,19 +68,78 @@ pub fn get_app_states(
             let badge_count = apps_lock
                 .get(&app_config.id)
                 .and_then(|r| r.badge_count);
+            let account_badges = apps_lock
+                .get(&app_config.id)
+                .map(|r| r.account_badges.clone())
+                .unwrap_or_default();
+            let crash_count = apps_lock
+                .get(&app_config.id)
+                .map(|r| r.crash_count)
+                .unwrap_or(0);
+            let signed_out = apps_lock
+                .get(&app_config.id)
+                .is_some_and(|r| r.signed_out);
 
             AppStateInfo {
                 id: app_config.id.clone(),
                 name: app_config.name.clone(),
                 state: state_str,
                 badge_count,
+                account_badges,
                 current_url,
                 error_message,
+                crash_count,
+                signed_out,
             }
         })
         .collect()
 }
 
+/// Recent state transitions of an app, oldest first.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_app_state_history(
+    app_id: String,
+    webview: tauri::Webview,
+    app_manager: State<'_, AppManager>,
+) -> Result<Vec<StateTransition>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(app_manager.history(&app_id))
+}
+
+/// Sign an app in with another of its accounts ("" for the default).
+#[tauri::command(rename_all = "snake_case")]
+pub fn switch_app_account(
+    app_id: String,
+    account_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    crate::app_manager::accounts::switch_account(&app_handle, &app_id, &account_id)
+}
+
+/// Move an app out of the main window into a window of its own.
+#[tauri::command(rename_all = "snake_case")]
+pub fn pop_out_app(
+    app_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    popout::pop_out(&app_handle, &app_id)
+}
+
+/// Move a popped-out app back into the main window.
+#[tauri::command(rename_all = "snake_case")]
+pub fn dock_app(
+    app_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    popout::dock(&app_handle, &app_id)
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub async fn activate_app(
     app_id: String,
@@ -115,31 +185,36 @@ pub async fn activate_app(
     let is_new_webview = app_handle.get_webview(&app_id).is_none();
     if is_new_webview {
         // Set Loading state before creating webview so UI shows spinner
-        app_manager.set_state(
+        set_app_state(
+            &app_handle,
             &app_id,
             AppRuntimeState::Loading {
                 target_url: load_url.clone(),
             },
+            "activated",
         );
-        let _ = app_handle.emit("app-state-changed", &app_id);
 
         let mut wake_config = app_config.clone();
         wake_config.url = load_url.clone();
         lifecycle::create_app_webview(&app_handle, &wake_config, position, size)?;
     }
 
-    // Hide all other webviews
-    let apps_lock = app_manager.apps.lock().expect("apps lock");
-    let other_ids: Vec<String> = apps_lock
-        .keys()
-        .filter(|id| *id != &app_id)
-        .cloned()
-        .collect();
-    drop(apps_lock);
+    // Hide all other webviews, unless the app only comes forward in its own
+    // window and the main window keeps showing what it was showing
+    let popped_out = popout::is_popped_out(&app_handle, &app_id);
+    if !popped_out {
+        let apps_lock = app_manager.apps.lock().expect("apps lock");
+        let other_ids: Vec<String> = apps_lock
+            .keys()
+            .filter(|id| *id != &app_id && !popout::is_popped_out(&app_handle, id))
+            .cloned()
+            .collect();
+        drop(apps_lock);
 
-    for other_id in &other_ids {
-        let _ = lifecycle::set_webview_visible(&app_handle, other_id, false, None, None);
-        app_manager.set_visible(other_id, false);
+        for other_id in &other_ids {
+            let _ = lifecycle::set_webview_visible(&app_handle, other_id, false, None, None);
+            app_manager.set_visible(other_id, false);
+        }
     }
 
     // Show this webview
@@ -149,23 +224,28 @@ pub async fn activate_app(
     // For new or still-loading webviews, keep Loading state until on_url_changed fires.
     // For existing active webviews, set Active immediately.
     if !is_new_webview && !is_still_loading {
-        app_manager.set_state(
+        set_app_state(
+            &app_handle,
             &app_id,
             AppRuntimeState::Active {
                 current_url: load_url.clone(),
             },
+            "activated",
         );
     }
     app_manager.touch_interaction(&app_id);
 
-    // Track active app for crash recovery
+    // Track active app for crash recovery; ephemeral apps are not recovered
     if let Some(session_state) = app_handle.try_state::<SessionState>() {
-        session_state.set_active(&app_id, &load_url);
+        if !app_config.ephemeral {
+            session_state.set_active(&app_id, &load_url);
+        }
+        session_state.touch_used(&app_id);
     }
 
     // Clear persisted hibernated flag
     if app_config.hibernated {
-        let _ = config_manager.update_with(|config| {
+        let _ = config_manager.update_quietly("activate_app", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.hibernated = false;
             }
@@ -183,7 +263,9 @@ pub async fn activate_app(
         }
     }
 
-    let _ = app_handle.emit("app-activated", &app_id);
+    if !popped_out {
+        let _ = app_handle.emit("app-activated", &app_id);
+    }
 
     Ok(())
 }
@@ -215,6 +297,14 @@ pub fn ensure_webview_exists(
     let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
     let size = tauri::LogicalSize::new(bounds.width, bounds.height);
 
+    set_app_state(
+        &app_handle,
+        &app_id,
+        AppRuntimeState::Loading {
+            target_url: app_config.url.clone(),
+        },
+        "webview requested",
+    );
     lifecycle::create_app_webview(&app_handle, &app_config, position, size)?;
     let _ = lifecycle::set_webview_visible(&app_handle, &app_id, false, None, None);
     app_manager.set_visible(&app_id, false);
@@ -222,15 +312,25 @@ pub fn ensure_webview_exists(
     Ok(())
 }
 
+/// Hibernate an app. Fails if it has unsaved work, unless `force` is set.
 #[tauri::command(rename_all = "snake_case")]
 pub fn hibernate_app(
     app_id: String,
+    force: bool,
     webview: tauri::Webview,
     app_handle: AppHandle,
     app_manager: State<'_, AppManager>,
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
+    // A disabled app has nothing to hibernate
+    if app_manager
+        .get_state(&app_id)
+        .is_some_and(|rt| rt.state.kind() == StateKind::Disabled)
+    {
+        return Ok(());
+    }
+    unsaved_work::check(&app_handle, &app_id, force)?;
     let last_url = lifecycle::destroy_app_webview(&app_handle, &app_id)?;
 
     let config = config_manager.get_config();
@@ -243,11 +343,11 @@ pub fn hibernate_app(
 
     let url = last_url.unwrap_or(fallback_url);
 
-    app_manager.set_state(
+    set_app_state(
+        &app_handle,
         &app_id,
-        AppRuntimeState::Hibernated {
-            last_url: url,
-        },
+        AppRuntimeState::Hibernated { last_url: url },
+        "hibernated by user",
     );
 
     // Remove from session state for crash recovery
@@ -258,7 +358,7 @@ pub fn hibernate_app(
     // Update persisted config
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("hibernate_app", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.hibernated = true;
                 found = true;
@@ -279,13 +379,12 @@ pub fn disable_app(
     app_id: String,
     webview: tauri::Webview,
     app_handle: AppHandle,
-    app_manager: State<'_, AppManager>,
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     let _ = lifecycle::destroy_app_webview(&app_handle, &app_id)?;
 
-    app_manager.set_state(&app_id, AppRuntimeState::Disabled);
+    set_app_state(&app_handle, &app_id, AppRuntimeState::Disabled, "disabled by user");
 
     // Remove from session state for crash recovery
     if let Some(session_state) = app_handle.try_state::<SessionState>() {
@@ -295,7 +394,7 @@ pub fn disable_app(
     // Update persisted config
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("disable_app", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.enabled = false;
                 found = true;
@@ -325,7 +424,7 @@ pub async fn enable_app(
     // Update persisted config
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("enable_app", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.enabled = true;
                 app.hibernated = false;
@@ -346,20 +445,22 @@ pub async fn enable_app(
         .ok_or_else(|| format!("App '{}' not found in config", app_id))?
         .clone();
 
-    // Only create webview if it doesn't already exist
+    // Only create webview if it doesn't already exist; it becomes Active
+    // once on_url_changed fires
     if app_handle.get_webview(&app_id).is_none() {
         let bounds = content_bounds.get();
         let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
         let size = tauri::LogicalSize::new(bounds.width, bounds.height);
+        set_app_state(
+            &app_handle,
+            &app_id,
+            AppRuntimeState::Loading {
+                target_url: app_config.url.clone(),
+            },
+            "enabled by user",
+        );
         lifecycle::create_app_webview(&app_handle, &app_config, position, size)?;
     }
-
-    app_manager.set_state(
-        &app_id,
-        AppRuntimeState::Active {
-            current_url: app_config.url.clone(),
-        },
-    );
     app_manager.touch_interaction(&app_id);
 
     let _ = app_handle.emit("app-enabled", &app_id);
@@ -368,9 +469,12 @@ pub async fn enable_app(
     Ok(())
 }
 
+/// Reload an app, or rebuild its webview if it has none. Fails if it has
+/// unsaved work, unless `force` is set.
 #[tauri::command(rename_all = "snake_case")]
 pub fn reload_app(
     app_id: String,
+    force: bool,
     webview: tauri::Webview,
     app_handle: AppHandle,
     app_manager: State<'_, AppManager>,
@@ -378,6 +482,9 @@ pub fn reload_app(
     content_bounds: State<'_, ContentBounds>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
+    unsaved_work::check(&app_handle, &app_id, force)?;
+    // A reload by hand gives a parked crash loop a fresh start
+    app_manager.clear_recent_crashes(&app_id);
     if let Some(wv) = app_handle.get_webview(&app_id) {
         wv.eval("location.reload()")
             .map_err(|e| format!("Failed to reload webview: {e}"))?;
@@ -396,13 +503,14 @@ pub fn reload_app(
         let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
         let size = tauri::LogicalSize::new(bounds.width, bounds.height);
 
-        app_manager.set_state(
+        set_app_state(
+            &app_handle,
             &app_id,
             AppRuntimeState::Loading {
                 target_url: app_config.url.clone(),
             },
+            "reloaded by user",
         );
-        let _ = app_handle.emit("app-state-changed", &app_id);
 
         lifecycle::create_app_webview(&app_handle, &app_config, position, size)?;
         app_manager.touch_interaction(&app_id);
@@ -437,6 +545,9 @@ pub fn set_content_area_bounds(
 
     let apps_lock = app_manager.apps.lock().expect("apps lock");
     for (app_id, runtime) in apps_lock.iter() {
+        if popout::is_popped_out(&app_handle, app_id) {
+            continue;
+        }
         if let AppRuntimeState::Active { .. } = &runtime.state {
             if let Some(webview) = app_handle.get_webview(app_id) {
                 let _ = webview.set_position(tauri::LogicalPosition::from(position));
@@ -507,82 +618,97 @@ pub async fn frontend_ready(
     let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
     let size = tauri::LogicalSize::new(bounds.width, bounds.height);
 
-    let mut first_active_id: Option<String> = None;
-    let mut created_ids: Vec<String> = Vec::new();
-
-    // Create webviews for all enabled, non-hibernated apps
-    for app_config in &config.apps {
-        if app_config.enabled && !app_config.hibernated {
-            if app_handle.get_webview(&app_config.id).is_none() {
-                let mut wake_config = app_config.clone();
-                if let Some(recovered_url) = previous_session.get(&app_config.id) {
-                    if !recovered_url.is_empty() {
-                        wake_config.url = recovered_url.clone();
-                    }
-                }
-                if let Err(e) = lifecycle::create_app_webview(&app_handle, &wake_config, position, size) {
-                    log::error!("Failed to create webview for {}: {}", app_config.id, e);
-                    continue;
-                }
-            }
-            created_ids.push(app_config.id.clone());
+    // Show the app the user most likely wants first; the rest load behind it
+    let (last_used, badges) = app_handle
+        .try_state::<SessionState>()
+        .map(|session_state| session_state.usage())
+        .unwrap_or_default();
+    let first_active_id = startup::first_app(&config, &last_used);
+    let queue = startup::startup_queue(
+        &config,
+        first_active_id.as_deref(),
+        &last_used,
+        &badges,
+        chrono::Utc::now().timestamp(),
+    );
 
-            if first_active_id.is_none() {
-                first_active_id = Some(app_config.id.clone());
+    if let Some(ref first_id) = first_active_id {
+        let app_config = config
+            .apps
+            .iter()
+            .find(|a| a.id == *first_id)
+            .ok_or_else(|| format!("App '{}' not found in config", first_id))?;
+        let url = previous_session
+            .get(first_id)
+            .filter(|u| !u.is_empty())
+            .cloned()
+            .unwrap_or_else(|| app_config.url.clone());
+        let mut created = true;
+        if app_handle.get_webview(first_id).is_none() {
+            let mut wake_config = app_config.clone();
+            wake_config.url = url.clone();
+            set_app_state(
+                &app_handle,
+                first_id,
+                AppRuntimeState::Loading {
+                    target_url: url.clone(),
+                },
+                "startup",
+            );
+            if let Err(e) = lifecycle::create_app_webview(&app_handle, &wake_config, position, size) {
+                log::error!("Failed to create webview for {}: {}", first_id, e);
+                set_app_state(
+                    &app_handle,
+                    first_id,
+                    AppRuntimeState::Error { message: e },
+                    "startup",
+                );
+                created = false;
             }
         }
-    }
-
-    // Clear session state after successful restore
-    if let Some(session_state) = app_handle.try_state::<SessionState>() {
-        session_state.clear();
-    }
-
-    // Emit session recovery notification
-    if !previous_session.is_empty() {
-        let _ = app_handle.emit("session-recovered", previous_session.len());
-    }
 
-    // Hide all webviews initially
-    for id in &created_ids {
-        let _ = lifecycle::set_webview_visible(&app_handle, id, false, None, None);
-    }
-
-    // Show only the first active app
-    if let Some(ref first_id) = first_active_id {
-        let _ = lifecycle::set_webview_visible(&app_handle, first_id, true, Some(position), Some(size));
-        app_manager.set_visible(first_id, true);
-
-        // Update runtime state
-        if let Some(app_config) = config.apps.iter().find(|a| a.id == *first_id) {
-            let url = previous_session.get(first_id).cloned().unwrap_or_else(|| app_config.url.clone());
-            app_manager.set_state(
+        if created {
+            let _ = lifecycle::set_webview_visible(&app_handle, first_id, true, Some(position), Some(size));
+            app_manager.set_visible(first_id, true);
+            set_app_state(
+                &app_handle,
                 first_id,
                 AppRuntimeState::Active {
                     current_url: url,
                 },
+                "startup",
             );
             app_manager.touch_interaction(first_id);
+            let _ = app_handle.emit("app-activated", first_id.as_str());
         }
-
-        let _ = app_handle.emit("app-activated", first_id.as_str());
     }
 
-    // Update state for other created apps
-    for id in &created_ids {
-        if Some(id) != first_active_id.as_ref() {
-            if let Some(app_config) = config.apps.iter().find(|a| a.id == *id) {
-                let url = previous_session.get(id).cloned().unwrap_or_else(|| app_config.url.clone());
-                app_manager.set_state(
-                    id,
-                    AppRuntimeState::Active {
-                        current_url: url,
-                    },
-                );
-            }
+    // Queued apps wake at the URL they were at before a crash
+    for app in &queue {
+        if let Some(url) = previous_session.get(&app.app_id).filter(|u| !u.is_empty()) {
+            set_app_state(
+                &app_handle,
+                &app.app_id,
+                AppRuntimeState::Hibernated {
+                    last_url: url.clone(),
+                },
+                "startup",
+            );
         }
     }
 
+    // Clear session state after successful restore
+    if let Some(session_state) = app_handle.try_state::<SessionState>() {
+        session_state.clear();
+    }
+
+    // Emit session recovery notification
+    if !previous_session.is_empty() {
+        let _ = app_handle.emit("session-recovered", previous_session.len());
+    }
+
+    startup::start_queue(app_handle.clone(), queue);
+
     // Aggregate badge counts on startup for tray badge
     {
         let apps_lock = app_manager.apps.lock().expect("apps lock");
@@ -645,24 +771,35 @@ pub fn on_url_changed(
     app_id: String,
     url: String,
     app_handle: AppHandle,
-    app_manager: State<'_, AppManager>,
     adblock_state: State<'_, crate::adblock::engine::AdblockState>,
     config_manager: State<'_, crate::config::manager::ConfigManager>,
 ) -> Result<(), String> {
     let current_app_id = app_id.clone();
-    app_manager.set_state(
+    // A late navigation event from a webview that has since been hibernated,
+    // disabled or killed must not bring the app back to life
+    if !set_app_state(
+        &app_handle,
         &app_id,
         AppRuntimeState::Active {
             current_url: url.clone(),
         },
-    );
+        "page loaded",
+    ) {
+        return Ok(());
+    }
+    let ephemeral = config_manager
+        .get_app(&current_app_id)
+        .is_some_and(|a| a.ephemeral);
     if let Some(session_state) = app_handle.try_state::<SessionState>() {
-        session_state.set_active(&current_app_id, &url);
+        if !ephemeral {
+            session_state.set_active(&current_app_id, &url);
+        }
     }
     let _ = app_handle.emit("url-changed", serde_json::json!({
         "appId": app_id,
         "url": url,
     }));
+    crate::app_manager::sign_in::on_navigation(&app_handle, &app_id, &url);
 
     // Update cosmetic filters for the new URL
     let config = config_manager.get_config();
@@ -719,21 +856,20 @@ pub fn check_unsaved_work(
     app_id: String,
     app_handle: AppHandle,
 ) -> Result<bool, String> {
-    if let Some(app_manager) = app_handle.try_state::<crate::app_manager::state::AppManager>() {
-        let apps = app_manager.apps.lock().expect("apps lock");
-        if let Some(runtime) = apps.get(&app_id) {
-            return Ok(runtime.has_unsaved_work);
-        }
-    }
-    Ok(false)
+    Ok(unsaved_work::has_unsaved_work(&app_handle, &app_id))
 }
 
+/// Called by an app's own page whenever it gains or loses unsaved work.
 #[tauri::command(rename_all = "snake_case")]
 pub fn set_has_unsaved_work(
     app_id: String,
     has_unsaved: bool,
+    webview: tauri::Webview,
     app_manager: State<'_, crate::app_manager::state::AppManager>,
 ) -> Result<(), String> {
+    if webview.label() != app_id {
+        return Err("Access denied: an app can only report its own unsaved work".to_string());
+    }
     let mut apps = app_manager.apps.lock().expect("apps lock");
     if let Some(runtime) = apps.get_mut(&app_id) {
         runtime.has_unsaved_work = has_unsaved;
@@ -741,6 +877,34 @@ pub fn set_has_unsaved_work(
     Ok(())
 }
 
+/// Called by an app's own page with its latest snapshot.
+#[tauri::command(rename_all = "snake_case")]
+pub fn save_page_snapshot(
+    app_id: String,
+    snapshot: PageSnapshot,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<(), String> {
+    // Snapshots may hold form text, so one app cannot write another's
+    if webview.label() != app_id {
+        return Err("Access denied: an app can only save its own snapshot".to_string());
+    }
+    let app_config = config_manager
+        .get_app(&app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    if app_config.ephemeral {
+        return Ok(());
+    }
+    let Some(snapshot) = page_snapshot::sanitize(snapshot, app_config.restore_form_fields) else {
+        return Ok(());
+    };
+    if let Some(session_state) = app_handle.try_state::<SessionState>() {
+        session_state.set_snapshot(&app_id, snapshot);
+    }
+    Ok(())
+}
+
 #[tauri::command(rename_all = "snake_case")]
 pub fn remove_certificate_exception(
     host: String,
@@ -768,11 +932,20 @@ pub fn apply_layout(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
 
+    // Popped-out apps have windows of their own
+    let layout: Vec<AppLayoutInfo> = layout
+        .into_iter()
+        .filter(|l| !popout::is_popped_out(&app_handle, &l.app_id))
+        .collect();
     let layout_ids: std::collections::HashSet<String> = layout.iter().map(|l| l.app_id.clone()).collect();
 
     // Hide apps not in the layout
     let apps_lock = app_manager.apps.lock().expect("apps lock");
-    let all_ids: Vec<String> = apps_lock.keys().cloned().collect();
+    let all_ids: Vec<String> = apps_lock
+        .keys()
+        .filter(|id| !popout::is_popped_out(&app_handle, id))
+        .cloned()
+        .collect();
     drop(apps_lock);
 
     for id in &all_ids {
@@ -805,17 +978,19 @@ pub fn on_page_load_error(
     app_id: String,
     message: String,
     app_handle: AppHandle,
-    app_manager: State<'_, crate::app_manager::state::AppManager>,
 ) -> Result<(), String> {
-    app_manager.set_state(
+    if set_app_state(
+        &app_handle,
         &app_id,
         AppRuntimeState::Error {
             message: message.clone(),
         },
-    );
-    let _ = app_handle.emit(
-        "app-error",
-        serde_json::json!({ "appId": app_id, "message": message }),
-    );
+        "page load error",
+    ) {
+        let _ = app_handle.emit(
+            "app-error",
+            serde_json::json!({ "appId": app_id, "message": message }),
+        );
+    }
     Ok(())
 }
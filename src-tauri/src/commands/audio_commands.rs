@@ -110,7 +110,7 @@ pub fn set_audio_muted(
     crate::commands::require_main_webview(&webview)?;
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("set_audio_muted", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.audio_muted = muted;
                 found = true;
@@ -160,7 +160,7 @@ pub fn toggle_global_mute(
         clear_global_mute_snapshot(&app_handle);
 
         config_manager
-            .update_with(|config| {
+            .update_with("toggle_global_mute", |config| {
                 for (app_id, muted) in &restore_states {
                     if let Some(app) = config.apps.iter_mut().find(|a| a.id == *app_id) {
                         app.audio_muted = *muted;
@@ -203,7 +203,7 @@ pub fn toggle_global_mute(
             .collect();
 
         config_manager
-            .update_with(|config| {
+            .update_with("toggle_global_mute", |config| {
                 for app in config.apps.iter_mut() {
                     if app.enabled {
                         app.audio_muted = true;
@@ -0,0 +1,64 @@
+use tauri::{AppHandle, Manager};
+
+use crate::app_manager::auth_popup::{self, AuthPopups};
+
+/// Relay a `postMessage` from a sign-in popup to the page that opened it.
+#[tauri::command(rename_all = "snake_case")]
+pub fn auth_popup_message(
+    popup_id: String,
+    data: serde_json::Value,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    if webview.label() != auth_popup::window_label(&popup_id) {
+        return Err("Access denied: not this popup".to_string());
+    }
+    let app_id = app_handle
+        .state::<AuthPopups>()
+        .opener(&popup_id)
+        .ok_or("Popup not found")?;
+    let origin = webview
+        .url()
+        .map(|u| u.origin().ascii_serialization())
+        .unwrap_or_default();
+    auth_popup::notify_opener(&app_handle, &app_id, &popup_id, "message", &data, &origin);
+    Ok(())
+}
+
+/// Relay a `postMessage` from an app's page to the sign-in popup it opened.
+#[tauri::command(rename_all = "snake_case")]
+pub fn post_to_auth_popup(
+    popup_id: String,
+    data: serde_json::Value,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let app_id = app_handle
+        .state::<AuthPopups>()
+        .opener(&popup_id)
+        .ok_or("Popup not found")?;
+    if webview.label() != app_id {
+        return Err("Access denied: not this popup's opener".to_string());
+    }
+    auth_popup::post_to_popup(&app_handle, &popup_id, &data);
+    Ok(())
+}
+
+/// Close a sign-in popup, on behalf of the popup itself or its opener.
+#[tauri::command(rename_all = "snake_case")]
+pub fn close_auth_popup(
+    popup_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let app_id = app_handle
+        .state::<AuthPopups>()
+        .opener(&popup_id)
+        .ok_or("Popup not found")?;
+    let label = webview.label();
+    if label != app_id && label != auth_popup::window_label(&popup_id) {
+        return Err("Access denied: not this popup or its opener".to_string());
+    }
+    auth_popup::close(&app_handle, &popup_id);
+    Ok(())
+}
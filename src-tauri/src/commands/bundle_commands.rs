@@ -0,0 +1,68 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::app_manager::state::AppManager;
+use crate::config::bundle::{self, BundleImport, BundlePreview};
+use crate::config::manager::ConfigManager;
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn export_app_bundle(
+    app_id: String,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<String, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let bundle = bundle::export_app(&config_manager.get_config(), &app_id)?;
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn export_workspace_bundle(
+    workspace_id: String,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<String, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let bundle = bundle::export_workspace(&config_manager.get_config(), &workspace_id)?;
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+/// Show what importing a bundle would add and what it overlaps with.
+#[tauri::command]
+pub fn preview_bundle_import(
+    json: String,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<BundlePreview, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let bundle = bundle::parse(&json)?;
+    Ok(bundle::preview(&config_manager.get_config(), &bundle))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn import_bundle(
+    json: String,
+    reuse_existing_apps: bool,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    app_manager: State<'_, AppManager>,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<BundleImport, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let bundle = bundle::parse(&json)?;
+    let import = config_manager
+        .try_update_with("import_bundle", |config| {
+            bundle::apply(config, &bundle, reuse_existing_apps)
+        })
+        .map_err(|e| e.to_string())?;
+
+    let config = config_manager.get_config();
+    let new_apps: Vec<_> = config
+        .apps
+        .into_iter()
+        .filter(|a| import.app_ids.contains(&a.id))
+        .collect();
+    app_manager.init_from_config(&new_apps);
+    crate::tray::rebuild_tray_menu(&app_handle);
+    let _ = app_handle.emit("config-updated", ());
+    Ok(import)
+}
@@ -2,6 +2,9 @@ use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::config::manager::ConfigManager;
 use crate::config::models::{AppConfig, GeneralConfig, OrblyConfig};
+use crate::config::policy::PolicyInfo;
+use crate::config::snapshots::{ConfigChange, SnapshotInfo};
+use crate::config::validation::{self, ValidationReport};
 
 #[tauri::command]
 pub fn get_config(
@@ -12,6 +15,40 @@ pub fn get_config(
     Ok(config_manager.get_config())
 }
 
+/// Returns why the config is read-only (e.g. it was written by a newer Orbly),
+/// or `None` if changes can be saved.
+#[tauri::command]
+pub fn get_config_read_only_reason(
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<Option<String>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(config_manager.read_only_reason())
+}
+
+/// Settings locked by the administrator policy and the app allow/deny lists.
+#[tauri::command]
+pub fn get_policy(
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<PolicyInfo, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(config_manager.policy().info())
+}
+
+/// Validate `config`, or the current config when none is given, without
+/// saving anything.
+#[tauri::command]
+pub fn validate_config(
+    config: Option<OrblyConfig>,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<ValidationReport, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let config = config.unwrap_or_else(|| config_manager.get_config());
+    Ok(validation::validate_config(&config))
+}
+
 #[tauri::command]
 pub fn get_apps(
     webview: tauri::Webview,
@@ -39,7 +76,7 @@ pub fn add_app(
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
-    config_manager.add_app(app).map_err(|e| e.to_string())?;
+    config_manager.add_app("add_app", app).map_err(|e| e.to_string())?;
     crate::tray::rebuild_tray_menu(&app_handle);
     Ok(())
 }
@@ -53,41 +90,28 @@ pub fn update_app(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     let old = config_manager.get_app(&app.id);
-    config_manager.update_app(app.clone()).map_err(|e| e.to_string())?;
+    config_manager.update_app("update_app", app.clone()).map_err(|e| e.to_string())?;
+    // Continue with the stored copy, whose credentials have been moved to the secret store
+    let app = config_manager.get_app(&app.id).unwrap_or(app);
     crate::tray::rebuild_tray_menu(&app_handle);
 
+    if let (Some(old_app), Some(app_manager)) = (
+        &old,
+        app_handle.try_state::<crate::app_manager::state::AppManager>(),
+    ) {
+        for removed in old_app
+            .accounts
+            .iter()
+            .filter(|old| !app.accounts.iter().any(|a| a.id == old.id))
+        {
+            app_manager.forget_account_badge(&app.id, &removed.id);
+        }
+    }
+
     // If properties that require webview recreation changed, destroy and recreate
     if let Some(old_app) = old {
-        let needs_recreate = old_app.url != app.url
-            || old_app.user_agent != app.user_agent
-            || old_app.custom_css != app.custom_css
-            || old_app.custom_js != app.custom_js
-            || old_app.proxy != app.proxy
-            || old_app.service_type != app.service_type;
-
-        if needs_recreate {
-            if let Some(existing) = app_handle.get_webview(&app.id) {
-                let _ = existing.close();
-                if let Some(app_manager) = app_handle.try_state::<crate::app_manager::state::AppManager>() {
-                    let content_bounds = app_handle.state::<crate::app_manager::state::ContentBounds>();
-                    let bounds = content_bounds.get();
-                    let position = tauri::LogicalPosition::new(bounds.x, bounds.y);
-                    let size = tauri::LogicalSize::new(bounds.width, bounds.height);
-
-                    app_manager.set_state(
-                        &app.id,
-                        crate::app_manager::state::AppRuntimeState::Loading {
-                            target_url: app.url.clone(),
-                        },
-                    );
-                    app_manager.touch_interaction(&app.id);
-                    let _ = app_handle.emit("app-state-changed", &app.id);
-
-                    if let Err(e) = crate::app_manager::lifecycle::create_app_webview(&app_handle, &app, position, size) {
-                        log::error!("Failed to recreate webview for {}: {}", app.id, e);
-                    }
-                }
-            }
+        if crate::app_manager::lifecycle::needs_webview_recreate(&old_app, &app) {
+            crate::app_manager::lifecycle::recreate_app_webview(&app_handle, &app);
         } else {
             // Apply zoom changes live
             if old_app.zoom_level != app.zoom_level {
@@ -96,6 +120,10 @@ pub fn update_app(
                     let _ = wv.set_zoom(scale);
                 }
             }
+            #[cfg(target_os = "linux")]
+            if old_app.adblock_enabled != app.adblock_enabled {
+                crate::adblock::content_rules::attach_filter(&app_handle, &app.id);
+            }
         }
     }
 
@@ -111,7 +139,7 @@ pub fn remove_app(
     config_manager: State<'_, ConfigManager>,
 ) -> Result<Option<AppConfig>, String> {
     crate::commands::require_main_webview(&webview)?;
-    let result = config_manager.remove_app(&app_id).map_err(|e| e.to_string())?;
+    let result = config_manager.remove_app("remove_app", &app_id).map_err(|e| e.to_string())?;
 
     // Always close the webview if it exists
     if let Some(webview) = app_handle.get_webview(&app_id) {
@@ -120,19 +148,21 @@ pub fn remove_app(
         }
         let _ = webview.close();
     }
+    crate::app_manager::popout::close_window(&app_handle, &app_id);
+    crate::app_manager::data_store::discard_ephemeral_store(&app_handle, &app_id);
 
     if delete_data {
         // Best-effort filesystem cleanup of data store
         if let Some(ref removed_app) = result {
-            let data_dir = app_handle
-                .path()
-                .app_data_dir()
-                .ok();
-            if let Some(dir) = data_dir {
-                let store_dir = dir.join("WebKit").join(removed_app.data_store_uuid.to_string());
-                if store_dir.exists() {
-                    let _ = std::fs::remove_dir_all(&store_dir);
-                    log::info!("Cleaned up data store for app {}", app_id);
+            if let Some(root) = crate::app_manager::data_store::stores_root(&app_handle) {
+                let stores = std::iter::once(removed_app.data_store_uuid)
+                    .chain(removed_app.accounts.iter().map(|a| a.data_store_uuid));
+                for store in stores {
+                    let store_dir = root.join(store.to_string());
+                    if store_dir.exists() {
+                        let _ = std::fs::remove_dir_all(&store_dir);
+                        log::info!("Cleaned up data store {} for app {}", store, app_id);
+                    }
                 }
             }
         }
@@ -142,6 +172,9 @@ pub fn remove_app(
     if let Some(app_manager) = app_handle.try_state::<crate::app_manager::state::AppManager>() {
         app_manager.remove(&app_id);
     }
+    if let Some(session_state) = app_handle.try_state::<crate::app_manager::session_state::SessionState>() {
+        session_state.forget(&app_id);
+    }
 
     crate::tray::rebuild_tray_menu(&app_handle);
     Ok(result)
@@ -157,7 +190,7 @@ pub fn update_general_config(
     crate::commands::require_main_webview(&webview)?;
     let old_manifest_url = config_manager.get_config().general.recipe_manifest_url.clone();
     config_manager
-        .update_with(|config| {
+        .update_with("update_general_config", |config| {
             config.general = general.clone();
         })
         .map_err(|e| e.to_string())?;
@@ -179,10 +212,11 @@ pub fn update_adblock_config(
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
-    let old_enabled = config_manager.get_config().adblock.enabled;
+    let old_adblock = config_manager.get_config().adblock;
+    let old_enabled = old_adblock.enabled;
     let adblock_cfg = adblock.clone();
     config_manager
-        .update_with(|config| {
+        .update_with("update_adblock_config", |config| {
             config.adblock = adblock.clone();
         })
         .map_err(|e| e.to_string())?;
@@ -196,7 +230,11 @@ pub fn update_adblock_config(
             state.load_rules("", &[]);
         }
     }
-    if old_enabled != adblock_cfg.enabled {
+    // Webviews recompile their content rules on this, so only send it when
+    // the rules they get actually changed
+    let rules_changed = old_enabled != adblock_cfg.enabled
+        || (adblock_cfg.enabled && old_adblock.custom_rules != adblock_cfg.custom_rules);
+    if rules_changed {
         let _ = app_handle.emit("content-rules-updated", ());
     }
     Ok(())
@@ -210,7 +248,7 @@ pub fn update_downloads_config(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     config_manager
-        .update_with(|config| {
+        .update_with("update_downloads_config", |config| {
             config.downloads = downloads.clone();
         })
         .map_err(|e| e.to_string())
@@ -224,7 +262,7 @@ pub fn update_link_routing_config(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     config_manager
-        .update_with(|config| {
+        .update_with("update_link_routing_config", |config| {
             config.link_routing = link_routing.clone();
         })
         .map_err(|e| e.to_string())
@@ -236,6 +274,9 @@ pub fn test_link_route(
     config_manager: State<'_, ConfigManager>,
 ) -> String {
     let config = config_manager.get_config();
+    if crate::app_manager::auth_popup::is_auth_url(&config.link_routing.rules, &url) {
+        return "popup".to_string();
+    }
     for rule in &config.link_routing.rules {
         if crate::utils::wildcard_match(&rule.pattern, &url) {
             return rule.target.clone();
@@ -250,7 +291,9 @@ pub fn export_config_json(
     config_manager: State<'_, ConfigManager>,
 ) -> Result<String, String> {
     crate::commands::require_main_webview(&webview)?;
-    let config = config_manager.get_config();
+    let mut config = config_manager.get_config();
+    // Secret handles only resolve on this machine; never export them
+    crate::config::secrets::SecretStore::redact(&mut config);
     serde_json::to_string_pretty(&config).map_err(|e| e.to_string())
 }
 
@@ -261,8 +304,48 @@ pub fn import_config_json(
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
-    let config: OrblyConfig = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-    config_manager.save_config(config).map_err(|e| e.to_string())
+    let mut config: OrblyConfig = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    // Imported values for locked settings give way to the policy
+    config_manager.policy().apply(&mut config)?;
+    config_manager.snapshot("before import_config_json");
+    config_manager.save_config("import_config_json", config).map_err(|e| e.to_string())
+}
+
+/// Config snapshots, newest first.
+#[tauri::command]
+pub fn list_config_snapshots(
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(config_manager.list_snapshots())
+}
+
+/// Changes from a snapshot to another snapshot, or to the current config.
+#[tauri::command(rename_all = "snake_case")]
+pub fn diff_config_snapshot(
+    snapshot_id: String,
+    against_id: Option<String>,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<Vec<ConfigChange>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    config_manager.diff_snapshot(&snapshot_id, against_id.as_deref())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn restore_config_snapshot(
+    snapshot_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    let reload = config_manager
+        .restore_snapshot(&snapshot_id)
+        .map_err(|e| e.to_string())?;
+    crate::config::watcher::apply_reload(&app_handle, &reload.old, &reload.new);
+    Ok(())
 }
 
 #[tauri::command]
@@ -274,7 +357,7 @@ pub fn update_shortcuts_config(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     config_manager
-        .update_with(|config| {
+        .update_with("update_shortcuts_config", |config| {
             config.shortcuts = shortcuts.clone();
         })
         .map_err(|e| e.to_string())?;
@@ -290,7 +373,7 @@ pub fn update_workspaces_config(
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     config_manager
-        .update_with(|config| {
+        .update_with("update_workspaces_config", |config| {
             config.workspaces = workspaces.clone();
         })
         .map_err(|e| e.to_string())
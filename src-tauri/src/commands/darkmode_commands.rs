@@ -21,7 +21,7 @@ pub fn toggle_dark_mode(
     let mut text_color = String::new();
     let mut custom_css = String::new();
     config_manager
-        .update_with(|config| {
+        .update_with("toggle_dark_mode", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 // Cycle: off -> dynamic -> filter -> static -> off
                 app.dark_mode = match app.dark_mode {
@@ -81,7 +81,7 @@ pub fn update_dark_mode_settings(
     crate::commands::require_main_webview(&webview)?;
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("update_dark_mode_settings", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.dark_mode = match mode.as_str() {
                     "dynamic" => DarkModeType::Dynamic,
@@ -0,0 +1,64 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::app_manager::data_store::{self, DataCategory, DataStoreSize};
+
+/// On-disk size of every app's and account's data store.
+#[tauri::command]
+pub async fn get_data_store_sizes(
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<Vec<DataStoreSize>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    // Walking the stores can take a while; keep it off the async executor
+    tauri::async_runtime::spawn_blocking(move || data_store::store_sizes(&app_handle))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Delete cookies, cache, site storage or everything from the app's current
+/// data store. Returns the bytes freed.
+#[tauri::command(rename_all = "snake_case")]
+pub fn clear_app_data(
+    app_id: String,
+    category: DataCategory,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<u64, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let freed = data_store::clear_app_data(&app_handle, &app_id, category)?;
+    log::info!(
+        "Cleared {:?} of app '{}' ({} bytes)",
+        category,
+        app_id,
+        freed
+    );
+    let _ = app_handle.emit("app-data-cleared", &app_id);
+    Ok(freed)
+}
+
+/// Give the app's current account a fresh data store, deleting the old one.
+#[tauri::command(rename_all = "snake_case")]
+pub fn reset_app_data(
+    app_id: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<u64, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let freed = data_store::reset_app_data(&app_handle, &app_id)?;
+    log::info!("Reset data store of app '{}' ({} bytes)", app_id, freed);
+    let _ = app_handle.emit("app-data-cleared", &app_id);
+    Ok(freed)
+}
+
+/// Delete data stores that no app or account uses. Returns the bytes freed.
+#[tauri::command]
+pub async fn clean_orphaned_data_stores(
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+) -> Result<u64, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || data_store::clean_orphaned_stores(&handle))
+        .await
+        .map_err(|e| e.to_string())
+}
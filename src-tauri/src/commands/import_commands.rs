@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::app_manager::state::AppManager;
+use crate::config::manager::ConfigManager;
+use crate::config::models::AppConfig;
+use crate::importers::{self, DetectedSource, ImportPlan, ImportSource};
+
+/// Franz, Ferdium and Rambox installs found in their standard locations.
+#[tauri::command]
+pub fn detect_service_imports(webview: tauri::Webview) -> Result<Vec<DetectedSource>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(importers::detect())
+}
+
+/// Read another app's services into a plan for the user to review. Nothing
+/// is changed until `apply_service_import` is called.
+#[tauri::command]
+pub fn plan_service_import(
+    source: ImportSource,
+    path: Option<String>,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<ImportPlan, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let path = path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| PathBuf::from(shellexpand::tilde(&p).to_string()));
+    importers::plan(&config_manager.get_config(), source, path.as_deref())
+}
+
+#[tauri::command]
+pub fn apply_service_import(
+    apps: Vec<AppConfig>,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    app_manager: State<'_, AppManager>,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<Vec<String>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let ids = config_manager
+        .try_update_with("apply_service_import", |config| {
+            Ok(importers::apply(config, apps))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let config = config_manager.get_config();
+    let new_apps: Vec<_> = config
+        .apps
+        .into_iter()
+        .filter(|a| ids.contains(&a.id))
+        .collect();
+    app_manager.init_from_config(&new_apps);
+    crate::tray::rebuild_tray_menu(&app_handle);
+    let _ = app_handle.emit("config-updated", ());
+    Ok(ids)
+}
@@ -1,5 +1,6 @@
 use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::app_manager::auth_popup;
 use crate::app_manager::state::AppManager;
 use crate::config::manager::ConfigManager;
 use crate::utils::wildcard_match;
@@ -8,6 +9,8 @@ use crate::utils::wildcard_match;
 pub fn route_link(
     url: String,
     source_app_id: String,
+    popup_id: Option<String>,
+    webview: tauri::Webview,
     app_handle: AppHandle,
     config_manager: State<'_, ConfigManager>,
 ) -> Result<(), String> {
@@ -24,6 +27,33 @@ pub fn route_link(
 
     let config = config_manager.get_config();
 
+    // Sign-in pages stay with the app: opened windows become a popup sharing
+    // its data store, followed links are loaded in place
+    if auth_popup::is_auth_url(&config.link_routing.rules, &url) {
+        if webview.label() != source_app_id {
+            return Err("Sign-in pages can only be opened by their app".to_string());
+        }
+        if popup_id.is_some() {
+            return auth_popup::open(&app_handle, &source_app_id, &url, popup_id);
+        }
+        let nav_js = format!(
+            "window.location.href = {};",
+            serde_json::to_string(&url).unwrap_or_default()
+        );
+        return webview.eval(&nav_js).map_err(|e| e.to_string());
+    }
+    // Any other window the page opened goes elsewhere, so it is closed at once
+    if let Some(popup_id) = popup_id {
+        auth_popup::notify_opener(
+            &app_handle,
+            &source_app_id,
+            &popup_id,
+            "closed",
+            &serde_json::Value::Null,
+            "",
+        );
+    }
+
     for rule in &config.link_routing.rules {
         if wildcard_match(&rule.pattern, &url)
         {
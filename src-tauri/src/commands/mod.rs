@@ -1,15 +1,20 @@
 pub mod adblock_commands;
 pub mod app_lifecycle_commands;
 pub mod audio_commands;
+pub mod auth_popup_commands;
+pub mod bundle_commands;
 pub mod config_commands;
 pub mod darkmode_commands;
+pub mod data_store_commands;
 pub mod download_commands;
 pub mod favicon_commands;
 pub mod find_commands;
+pub mod import_commands;
 pub mod link_routing_commands;
 pub mod native_integration_commands;
 pub mod recipe_commands;
 pub mod resource_commands;
+pub mod sync_commands;
 pub mod tray_commands;
 pub mod useragent_commands;
 pub mod workspace_commands;
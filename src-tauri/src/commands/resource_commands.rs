@@ -1,8 +1,8 @@
 use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::app_manager::lifecycle;
+use crate::app_manager::{lifecycle, set_app_state};
 use crate::app_manager::session_state::SessionState;
-use crate::app_manager::state::{AppManager, AppRuntimeState};
+use crate::app_manager::state::AppRuntimeState;
 use crate::resource_monitor::{AppResourceUsage, ResourceMonitor};
 
 #[tauri::command]
@@ -17,12 +17,11 @@ pub fn kill_app(
     app_id: String,
     webview: tauri::Webview,
     app_handle: AppHandle,
-    app_manager: State<'_, AppManager>,
 ) -> Result<(), String> {
     crate::commands::require_main_webview(&webview)?;
     let _destroyed_url = lifecycle::destroy_app_webview(&app_handle, &app_id)?;
 
-    app_manager.set_state(&app_id, AppRuntimeState::Crashed);
+    set_app_state(&app_handle, &app_id, AppRuntimeState::Crashed, "killed by user");
 
     // Remove from session state for crash recovery
     if let Some(session_state) = app_handle.try_state::<SessionState>() {
@@ -0,0 +1,107 @@
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::config::manager::ConfigManager;
+use crate::config::merge::SyncConflict;
+use crate::config::models::SyncConfig;
+use crate::config::sync::{SyncManager, SyncStatus};
+
+#[tauri::command]
+pub fn update_sync_config(
+    sync: SyncConfig,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    // Saving with sync enabled queues a push to the new backend
+    config_manager
+        .update_with("update_sync_config", |config| {
+            config.sync = sync.clone();
+        })
+        .map_err(|e| e.to_string())?;
+    let _ = app_handle.emit("sync-status-changed", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_sync_status(
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<SyncStatus, String> {
+    crate::commands::require_main_webview(&webview)?;
+    let settings = config_manager.get_config().sync;
+    Ok(config_manager.sync_manager().status(&settings))
+}
+
+/// Pull the synced config, merge it with local changes and push the result.
+#[tauri::command]
+pub async fn sync_now(
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<SyncStatus, String> {
+    crate::commands::require_main_webview(&webview)?;
+    if !config_manager.get_config().sync.enabled {
+        return Err("Sync is disabled".to_string());
+    }
+    // Network and file I/O are blocking; keep them off the async executor
+    let handle = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let config_manager = handle.state::<ConfigManager>();
+        config_manager.sync_now().map_err(|e| e.to_string())?;
+        Ok(config_manager
+            .sync_manager()
+            .status(&config_manager.get_config().sync))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let _ = app_handle.emit("sync-status-changed", ());
+    result
+}
+
+#[tauri::command]
+pub fn get_sync_conflicts(
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<Vec<SyncConflict>, String> {
+    crate::commands::require_main_webview(&webview)?;
+    Ok(config_manager.sync_manager().conflicts())
+}
+
+/// Settle a sync conflict. `resolution` is "local" or "remote".
+#[tauri::command(rename_all = "snake_case")]
+pub fn resolve_sync_conflict(
+    conflict_id: String,
+    resolution: String,
+    webview: tauri::Webview,
+    app_handle: AppHandle,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<(), String> {
+    crate::commands::require_main_webview(&webview)?;
+    let use_remote = match resolution.as_str() {
+        "local" => false,
+        "remote" => true,
+        other => return Err(format!("Unknown resolution '{}'", other)),
+    };
+    config_manager
+        .resolve_sync_conflict(&conflict_id, use_remote)
+        .map_err(|e| e.to_string())?;
+    let _ = app_handle.emit("sync-status-changed", ());
+    Ok(())
+}
+
+/// Check that a backend configuration is reachable. Returns whether a synced
+/// config already exists there.
+#[tauri::command]
+pub async fn test_sync_connection(
+    sync: SyncConfig,
+    webview: tauri::Webview,
+    config_manager: State<'_, ConfigManager>,
+) -> Result<bool, String> {
+    crate::commands::require_main_webview(&webview)?;
+    // An unchanged password arrives as its secret handle
+    let sync = config_manager.secrets().resolve_sync(&sync);
+    tauri::async_runtime::spawn_blocking(move || SyncManager::test_connection(&sync))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -19,7 +19,7 @@ pub fn set_launch_at_login(
     }
 
     config_manager
-        .update_with(|config| {
+        .update_with("set_launch_at_login", |config| {
             config.general.launch_at_login = enabled;
         })
         .map_err(|e| e.to_string())?;
@@ -24,7 +24,7 @@ pub fn set_user_agent(
 ) -> Result<(), String> {
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("set_user_agent", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.user_agent = user_agent.clone();
                 found = true;
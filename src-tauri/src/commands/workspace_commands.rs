@@ -38,7 +38,7 @@ pub fn switch_workspace(
     let should_auto_hibernate = current_config.workspaces.auto_hibernate_on_workspace_switch;
 
     config_manager
-        .update_with(|config| {
+        .update_with("switch_workspace", |config| {
             config.workspaces.active = workspace_id.clone();
         })
         .map_err(|e| e.to_string())?;
@@ -53,34 +53,17 @@ pub fn switch_workspace(
             .collect();
         drop(apps_lock);
 
-        let mut hibernated_ids: Vec<String> = Vec::new();
         for app_id in &active_ids {
-            if !target_ws.app_ids.contains(app_id) {
-                let last_url = crate::app_manager::lifecycle::destroy_app_webview(&app_handle, app_id)
-                    .unwrap_or(None);
-                let fallback_url = current_config.apps.iter().find(|a| a.id == *app_id)
-                    .map(|a| a.url.clone()).unwrap_or_default();
-                let url = last_url.unwrap_or(fallback_url);
-
-                app_manager.set_state(app_id, crate::app_manager::state::AppRuntimeState::Hibernated {
-                    last_url: url,
-                });
-
-                hibernated_ids.push(app_id.clone());
-
+            // Apps with unsaved work stay up until it is dealt with
+            if !target_ws.app_ids.contains(app_id)
+                && crate::app_manager::hibernate_in_background(&app_handle, app_id, "workspace switch")
+            {
                 // Remove from session state
                 if let Some(session_state) = app_handle.try_state::<crate::app_manager::session_state::SessionState>() {
                     session_state.remove(app_id);
                 }
             }
         }
-        let _ = config_manager.update_with(|config| {
-            for app in config.apps.iter_mut() {
-                if hibernated_ids.iter().any(|id| id == &app.id) {
-                    app.hibernated = true;
-                }
-            }
-        });
     }
 
     let _ = app_handle.emit("workspace-switched", &workspace_id);
@@ -109,10 +92,11 @@ pub fn create_workspace(
         app_ids,
         tiling_layout: String::new(),
         tile_assignments: vec![],
+        schedule: None,
     };
 
     config_manager
-        .update_with(|config| {
+        .update_with("create_workspace", |config| {
             config.workspaces.items.push(workspace.clone());
         })
         .map_err(|e| e.to_string())?;
@@ -128,7 +112,7 @@ pub fn update_workspace(
     crate::commands::require_main_webview(&webview)?;
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("update_workspace", |config| {
             if let Some(ws) = config
                 .workspaces
                 .items
@@ -157,7 +141,7 @@ pub fn update_workspace_tiling(
     crate::commands::require_main_webview(&webview)?;
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("update_workspace_tiling", |config| {
             if let Some(ws) = config.workspaces.items.iter_mut().find(|w| w.id == workspace_id) {
                 ws.tiling_layout = tiling_layout.clone();
                 ws.tile_assignments = tile_assignments.clone();
@@ -184,7 +168,7 @@ pub fn delete_workspace(
     }
     let mut was_active = false;
     config_manager
-        .update_with(|config| {
+        .update_with("delete_workspace", |config| {
             config.workspaces.items.retain(|w| w.id != workspace_id);
             was_active = config.workspaces.active == workspace_id;
             if was_active {
@@ -32,7 +32,7 @@ fn set_zoom_inner(
 
     let mut found = false;
     config_manager
-        .update_with(|config| {
+        .update_with("set_zoom_level", |config| {
             if let Some(app) = config.apps.iter_mut().find(|a| a.id == app_id) {
                 app.zoom_level = zoom;
                 found = true;
@@ -0,0 +1,554 @@
+//! Shareable `.orbly` bundles of a single app or a whole workspace.
+//!
+//! A bundle carries app settings (URL, icon, custom CSS/JS, dark mode, ...)
+//! without anything tied to the exporting machine: ids, data stores, secret
+//! handles and download directories are left out. Apps are referenced by
+//! their index in the bundle, and importing assigns fresh ids and data stores.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::defaults::default_workspace;
+use super::merge::MACHINE_LOCAL_APP_FIELDS;
+use super::models::{AppConfig, LinkRoutingRule, OrblyConfig, Workspace};
+
+pub const BUNDLE_FORMAT: &str = "orbly-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// App fields that only make sense in the exporting config. Accounts are
+/// the exporter's own sign-ins.
+const UNSHARED_APP_FIELDS: &[&str] = &[
+    "id",
+    "workspace",
+    "position",
+    "hibernated",
+    "accounts",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub exported_at: String,
+    /// App settings with the unshared fields removed.
+    pub apps: Vec<Value>,
+    #[serde(default)]
+    pub workspace: Option<BundleWorkspace>,
+    #[serde(default)]
+    pub link_routing: Vec<BundleLinkRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleWorkspace {
+    pub name: String,
+    #[serde(default)]
+    pub tiling_layout: String,
+    /// Index into `Bundle::apps` for each tile, `None` for an empty tile.
+    #[serde(default)]
+    pub tile_assignments: Vec<Option<usize>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleLinkRule {
+    pub pattern: String,
+    /// Index into `Bundle::apps` of the app links open in.
+    pub app: usize,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleConflictKind {
+    /// An app with the same URL is already configured.
+    App,
+    /// A workspace with the same name exists; the import is renamed.
+    Workspace,
+    /// A link routing rule with the same pattern exists and is kept.
+    LinkRule,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BundleConflict {
+    pub kind: BundleConflictKind,
+    /// Name of the bundled app or workspace, or the rule pattern.
+    pub item: String,
+    /// Name of the existing app or workspace, or the existing rule's target.
+    pub existing: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BundlePreview {
+    pub name: String,
+    pub apps: Vec<String>,
+    pub workspace: Option<String>,
+    pub link_rules: usize,
+    pub conflicts: Vec<BundleConflict>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct BundleImport {
+    /// Ids of the apps that were created.
+    pub app_ids: Vec<String>,
+    pub workspace_id: Option<String>,
+}
+
+/// Bundle a single app and the link routing rules that target it.
+pub fn export_app(config: &OrblyConfig, app_id: &str) -> Result<Bundle, String> {
+    let app = config
+        .apps
+        .iter()
+        .find(|a| a.id == app_id)
+        .ok_or_else(|| format!("App '{}' not found", app_id))?;
+    build(config, &app.name, &[app], None)
+}
+
+/// Bundle a workspace with its apps, tiling layout and link routing rules.
+pub fn export_workspace(config: &OrblyConfig, workspace_id: &str) -> Result<Bundle, String> {
+    let workspace = config
+        .workspaces
+        .items
+        .iter()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    // The default workspace shows every app
+    let apps: Vec<&AppConfig> = if workspace.id == default_workspace() {
+        config.apps.iter().collect()
+    } else {
+        config
+            .apps
+            .iter()
+            .filter(|a| workspace.app_ids.contains(&a.id))
+            .collect()
+    };
+    build(config, &workspace.name, &apps, Some(workspace))
+}
+
+fn build(
+    config: &OrblyConfig,
+    name: &str,
+    apps: &[&AppConfig],
+    workspace: Option<&Workspace>,
+) -> Result<Bundle, String> {
+    let index_of = |id: &str| apps.iter().position(|a| a.id == id);
+
+    let mut entries = Vec::with_capacity(apps.len());
+    for app in apps {
+        let mut value = serde_json::to_value(app).map_err(|e| e.to_string())?;
+        if let Some(obj) = value.as_object_mut() {
+            for field in UNSHARED_APP_FIELDS.iter().chain(MACHINE_LOCAL_APP_FIELDS) {
+                obj.remove(*field);
+            }
+        }
+        entries.push(value);
+    }
+
+    let link_routing = config
+        .link_routing
+        .rules
+        .iter()
+        .filter_map(|rule| {
+            index_of(&rule.target).map(|app| BundleLinkRule {
+                pattern: rule.pattern.clone(),
+                app,
+            })
+        })
+        .collect();
+
+    let workspace = workspace.map(|ws| BundleWorkspace {
+        name: ws.name.clone(),
+        tiling_layout: ws.tiling_layout.clone(),
+        tile_assignments: ws.tile_assignments.iter().map(|id| index_of(id)).collect(),
+    });
+
+    Ok(Bundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        name: name.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        apps: entries,
+        workspace,
+        link_routing,
+    })
+}
+
+/// Parse and sanity-check a bundle file.
+pub fn parse(json: &str) -> Result<Bundle, String> {
+    let bundle: Bundle =
+        serde_json::from_str(json).map_err(|e| format!("Not an Orbly bundle: {e}"))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("Not an Orbly bundle".to_string());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "This bundle was made by a newer version of Orbly (format {})",
+            bundle.version
+        ));
+    }
+    let count = bundle.apps.len();
+    let in_range = |idx: &usize| *idx < count;
+    if !bundle.link_routing.iter().all(|r| in_range(&r.app))
+        || !bundle
+            .workspace
+            .iter()
+            .flat_map(|w| w.tile_assignments.iter().flatten())
+            .all(in_range)
+    {
+        return Err("Bundle refers to an app it does not contain".to_string());
+    }
+    // Make sure every app can be created before anything is imported
+    for idx in 0..count {
+        app_from_bundle(&bundle, idx, String::new())?;
+    }
+    Ok(bundle)
+}
+
+fn app_from_bundle(bundle: &Bundle, idx: usize, workspace: String) -> Result<AppConfig, String> {
+    let mut value = bundle.apps[idx].clone();
+    let obj = value
+        .as_object_mut()
+        .ok_or_else(|| format!("App {} in bundle is not an object", idx + 1))?;
+    obj.insert("id".to_string(), Value::String(Uuid::new_v4().to_string()));
+    obj.insert(
+        "data_store_uuid".to_string(),
+        Value::String(Uuid::new_v4().to_string()),
+    );
+    obj.insert("workspace".to_string(), Value::String(workspace));
+    serde_json::from_value(value).map_err(|e| format!("App {} in bundle is invalid: {e}", idx + 1))
+}
+
+fn app_name(bundle: &Bundle, idx: usize) -> String {
+    bundle.apps[idx]
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn app_url(bundle: &Bundle, idx: usize) -> &str {
+    bundle.apps[idx]
+        .get("url")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/')
+        .eq_ignore_ascii_case(b.trim_end_matches('/'))
+}
+
+/// Describe what importing `bundle` into `config` would add and where it
+/// overlaps with what is already there.
+pub fn preview(config: &OrblyConfig, bundle: &Bundle) -> BundlePreview {
+    let mut conflicts = Vec::new();
+    for idx in 0..bundle.apps.len() {
+        if let Some(existing) = config
+            .apps
+            .iter()
+            .find(|a| same_url(&a.url, app_url(bundle, idx)))
+        {
+            conflicts.push(BundleConflict {
+                kind: BundleConflictKind::App,
+                item: app_name(bundle, idx),
+                existing: existing.name.clone(),
+            });
+        }
+    }
+    if let Some(ws) = &bundle.workspace {
+        if let Some(existing) = config
+            .workspaces
+            .items
+            .iter()
+            .find(|w| w.name.eq_ignore_ascii_case(&ws.name))
+        {
+            conflicts.push(BundleConflict {
+                kind: BundleConflictKind::Workspace,
+                item: ws.name.clone(),
+                existing: existing.name.clone(),
+            });
+        }
+    }
+    for rule in &bundle.link_routing {
+        if let Some(existing) = config
+            .link_routing
+            .rules
+            .iter()
+            .find(|r| r.pattern == rule.pattern)
+        {
+            conflicts.push(BundleConflict {
+                kind: BundleConflictKind::LinkRule,
+                item: rule.pattern.clone(),
+                existing: existing.target.clone(),
+            });
+        }
+    }
+
+    BundlePreview {
+        name: bundle.name.clone(),
+        apps: (0..bundle.apps.len())
+            .map(|i| app_name(bundle, i))
+            .collect(),
+        workspace: bundle.workspace.as_ref().map(|w| w.name.clone()),
+        link_rules: bundle.link_routing.len(),
+        conflicts,
+    }
+}
+
+/// Add the bundle's apps, workspace and link routing rules to `config`.
+///
+/// With `reuse_existing_apps`, a bundled app whose URL is already configured
+/// is not duplicated; the existing app joins the imported workspace instead.
+/// Link rules whose pattern already exists are skipped, and the others are
+/// placed ahead of the existing rules so a catch-all rule does not shadow them.
+pub fn apply(
+    config: &mut OrblyConfig,
+    bundle: &Bundle,
+    reuse_existing_apps: bool,
+) -> Result<BundleImport, String> {
+    let workspace_id = bundle
+        .workspace
+        .as_ref()
+        .map(|ws| unique_workspace_id(config, &ws.name));
+    let app_workspace = workspace_id.clone().unwrap_or_else(default_workspace);
+
+    // Resolve every bundled app to an id before changing anything
+    let mut ids = Vec::with_capacity(bundle.apps.len());
+    let mut new_apps = Vec::new();
+    let mut next_position = config
+        .apps
+        .iter()
+        .map(|a| a.position + 1)
+        .max()
+        .unwrap_or(0);
+    for idx in 0..bundle.apps.len() {
+        let existing = config
+            .apps
+            .iter()
+            .find(|a| same_url(&a.url, app_url(bundle, idx)))
+            .filter(|_| reuse_existing_apps);
+        match existing {
+            Some(app) => ids.push(app.id.clone()),
+            None => {
+                let mut app = app_from_bundle(bundle, idx, app_workspace.clone())?;
+                app.position = next_position;
+                next_position += 1;
+                ids.push(app.id.clone());
+                new_apps.push(app);
+            }
+        }
+    }
+
+    let mut import = BundleImport {
+        app_ids: new_apps.iter().map(|a| a.id.clone()).collect(),
+        workspace_id: None,
+    };
+    config.apps.extend(new_apps);
+
+    if let (Some(ws), Some(id)) = (&bundle.workspace, workspace_id) {
+        let mut name = ws.name.clone();
+        let mut n = 2;
+        while config
+            .workspaces
+            .items
+            .iter()
+            .any(|w| w.name.eq_ignore_ascii_case(&name))
+        {
+            name = format!("{} ({})", ws.name, n);
+            n += 1;
+        }
+        let mut app_ids: Vec<String> = Vec::new();
+        for id in &ids {
+            if !app_ids.contains(id) {
+                app_ids.push(id.clone());
+            }
+        }
+        config.workspaces.items.push(Workspace {
+            id: id.clone(),
+            name,
+            app_ids,
+            tiling_layout: ws.tiling_layout.clone(),
+            tile_assignments: ws
+                .tile_assignments
+                .iter()
+                .map(|tile| tile.map(|idx| ids[idx].clone()).unwrap_or_default())
+                .collect(),
+            schedule: None,
+        });
+        import.workspace_id = Some(id);
+    }
+
+    let rules: Vec<LinkRoutingRule> = bundle
+        .link_routing
+        .iter()
+        .filter(|rule| {
+            !config
+                .link_routing
+                .rules
+                .iter()
+                .any(|r| r.pattern == rule.pattern)
+        })
+        .map(|rule| LinkRoutingRule {
+            pattern: rule.pattern.clone(),
+            target: ids[rule.app].clone(),
+        })
+        .collect();
+    config.link_routing.rules.splice(0..0, rules);
+
+    Ok(import)
+}
+
+/// Workspace ids are derived from the name, as when creating one in the UI.
+fn unique_workspace_id(config: &OrblyConfig, name: &str) -> String {
+    let base = name.to_lowercase().replace(' ', "-");
+    let base = if base.is_empty() {
+        "workspace".to_string()
+    } else {
+        base
+    };
+    let mut id = base.clone();
+    let mut n = 2;
+    while config.workspaces.items.iter().any(|w| w.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engineering_config() -> OrblyConfig {
+        let mut config = OrblyConfig::default();
+        for (id, url) in [
+            ("gh", "https://github.com"),
+            ("linear", "https://linear.app"),
+            ("music", "https://music.example.com"),
+        ] {
+            let mut app = AppConfig::test_app(id);
+            app.url = url.to_string();
+            app.custom_css = format!("/* {id} */");
+            app.download_directory = "/home/someone/Downloads".to_string();
+            app.proxy_credentials = "secret:abc".to_string();
+            config.apps.push(app);
+        }
+        config.workspaces.items.push(Workspace {
+            id: "eng".to_string(),
+            name: "Engineering".to_string(),
+            app_ids: vec!["gh".to_string(), "linear".to_string()],
+            tiling_layout: "split-vertical".to_string(),
+            tile_assignments: vec!["linear".to_string(), "gh".to_string()],
+            schedule: None,
+        });
+        config.link_routing.rules.insert(
+            0,
+            LinkRoutingRule {
+                pattern: "*github.com/*".to_string(),
+                target: "gh".to_string(),
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_export_strips_machine_fields() {
+        let config = engineering_config();
+        let bundle = export_workspace(&config, "eng").expect("export");
+
+        assert_eq!(bundle.apps.len(), 2);
+        for app in &bundle.apps {
+            for field in [
+                "id",
+                "data_store_uuid",
+                "proxy_credentials",
+                "download_directory",
+                "accounts",
+            ] {
+                assert!(app.get(field).is_none(), "{field} was exported");
+            }
+            assert!(app.get("custom_css").is_some());
+        }
+        let ws = bundle.workspace.as_ref().expect("workspace");
+        assert_eq!(ws.tile_assignments, vec![Some(1), Some(0)]);
+        assert_eq!(bundle.link_routing.len(), 1);
+        assert_eq!(bundle.link_routing[0].app, 0);
+    }
+
+    #[test]
+    fn test_import_remaps_ids() {
+        let bundle = export_workspace(&engineering_config(), "eng").expect("export");
+        let json = serde_json::to_string(&bundle).unwrap();
+        let bundle = parse(&json).expect("parse");
+
+        let mut config = OrblyConfig::default();
+        let import = apply(&mut config, &bundle, false).expect("apply");
+
+        assert_eq!(import.app_ids.len(), 2);
+        assert_eq!(import.workspace_id.as_deref(), Some("engineering"));
+        let gh = config
+            .apps
+            .iter()
+            .find(|a| a.url == "https://github.com")
+            .unwrap();
+        assert_ne!(gh.id, "gh");
+        assert!(!gh.data_store_uuid.is_nil());
+        assert_eq!(gh.workspace, "engineering");
+
+        let ws = config
+            .workspaces
+            .items
+            .iter()
+            .find(|w| w.id == "engineering")
+            .unwrap();
+        assert_eq!(ws.app_ids, import.app_ids);
+        assert_eq!(ws.tile_assignments[1], gh.id);
+        assert_eq!(config.link_routing.rules[0].target, gh.id);
+        assert!(crate::config::validation::validate_config(&config).is_valid());
+    }
+
+    #[test]
+    fn test_preview_and_reuse_existing_apps() {
+        let source = engineering_config();
+        let bundle = export_workspace(&source, "eng").expect("export");
+
+        let mut config = source.clone();
+        let preview = preview(&config, &bundle);
+        let kinds: Vec<_> = preview.conflicts.iter().map(|c| c.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BundleConflictKind::App,
+                BundleConflictKind::App,
+                BundleConflictKind::Workspace,
+                BundleConflictKind::LinkRule,
+            ]
+        );
+
+        let rules_before = config.link_routing.rules.len();
+        let import = apply(&mut config, &bundle, true).expect("apply");
+        assert!(import.app_ids.is_empty());
+        assert_eq!(config.apps.len(), 3);
+        let ws = config
+            .workspaces
+            .items
+            .iter()
+            .find(|w| Some(&w.id) == import.workspace_id.as_ref())
+            .unwrap();
+        assert_eq!(ws.id, "engineering");
+        assert_eq!(ws.name, "Engineering (2)");
+        assert_eq!(ws.app_ids, vec!["gh".to_string(), "linear".to_string()]);
+        assert_eq!(config.link_routing.rules.len(), rules_before);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_bundles() {
+        assert!(parse("{}").is_err());
+        let mut bundle = export_app(&engineering_config(), "gh").expect("export");
+        bundle.link_routing[0].app = 5;
+        assert!(parse(&serde_json::to_string(&bundle).unwrap()).is_err());
+        bundle.link_routing.clear();
+        bundle.version = BUNDLE_VERSION + 1;
+        assert!(parse(&serde_json::to_string(&bundle).unwrap()).is_err());
+    }
+}
@@ -36,6 +36,22 @@ pub fn default_notification_style() -> NotificationStyle {
     NotificationStyle::Full
 }
 
+pub fn default_schedule_wake_at() -> String {
+    "08:45".to_string()
+}
+
+pub fn default_schedule_hibernate_at() -> String {
+    "18:00".to_string()
+}
+
+pub fn default_crash_recovery() -> CrashRecovery {
+    CrashRecovery::Reload
+}
+
+pub fn default_launch_policy() -> LaunchPolicy {
+    LaunchPolicy::Restore
+}
+
 pub fn default_download_dir() -> String {
     "~/Downloads".to_string()
 }
@@ -66,6 +82,10 @@ pub fn default_recipe_cache_ttl_hours() -> u64 {
     24
 }
 
+pub fn default_startup_concurrency() -> u32 {
+    2
+}
+
 pub fn default_filter_lists() -> Vec<String> {
     vec![
         "https://easylist.to/easylist/easylist.txt".to_string(),
@@ -115,6 +135,7 @@ pub fn default_workspace_items() -> Vec<Workspace> {
         app_ids: vec![],
         tiling_layout: String::new(),
         tile_assignments: vec![],
+        schedule: None,
     }]
 }
 
@@ -142,7 +163,7 @@ impl Default for OrblyConfig {
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
-            config_version: 1,
+            config_version: super::migrations::CURRENT_CONFIG_VERSION,
             theme: ThemeMode::System,
             dnd_enabled: false,
             dnd_schedule_enabled: false,
@@ -159,9 +180,12 @@ impl Default for GeneralConfig {
             window_state: WindowState::default(),
             local_scripts_only: false,
             cpu_alert_threshold: default_cpu_alert_threshold(),
+            memory_budget_mb: 0,
             recipe_cache_ttl_hours: default_recipe_cache_ttl_hours(),
             sidebar_hover_expand: true,
             recipe_manifest_url: None,
+            startup_concurrency: default_startup_concurrency(),
+            defer_idle_apps: false,
         }
     }
 }
@@ -222,9 +246,3 @@ impl Default for DownloadGlobalConfig {
         }
     }
 }
-
-impl Default for SyncConfig {
-    fn default() -> Self {
-        Self { enabled: false }
-    }
-}
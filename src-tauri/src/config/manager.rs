@@ -1,63 +1,237 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::merge::{self, SyncConflict};
+use super::migrations::{self, MigrationError};
 use super::models::{AppConfig, OrblyConfig};
+use super::policy::{Policy, UserValues};
+use super::secrets::SecretStore;
+use super::snapshots::{self, ConfigChange, SnapshotInfo, SnapshotStore};
+use super::sync::SyncManager;
+use super::validation;
+
+/// Result of merging a pulled config into the local one.
+struct MergedRemote {
+    config: OrblyConfig,
+    conflicts: Vec<SyncConflict>,
+    /// The remote config as it would be pushed, for comparison with ours.
+    remote_payload: serde_json::Value,
+}
+
+/// A config replaced by an edit made outside Orbly.
+pub struct ConfigReload {
+    pub old: OrblyConfig,
+    pub new: OrblyConfig,
+}
+
+/// What Orbly last saw in `config.toml`, used to tell external edits apart
+/// from its own writes.
+#[derive(Default)]
+struct DiskState {
+    /// Hash of the contents last read or written by Orbly.
+    fingerprint: Option<String>,
+    /// Hash of an external edit that failed to load, so it is reported once.
+    rejected: Option<String>,
+    /// Why that edit failed to load.
+    error: Option<String>,
+}
 
 pub struct ConfigManager {
+    /// The config in effect, with the administrator policy applied.
     config: Mutex<OrblyConfig>,
+    /// The user's own values of the fields the policy locks. They are what
+    /// `config.toml` keeps, so they come back if the policy is lifted.
+    user_values: Mutex<UserValues>,
     config_path: PathBuf,
+    disk_state: Mutex<DiskState>,
+    sync: SyncManager,
+    secrets: SecretStore,
+    snapshots: SnapshotStore,
+    policy: Policy,
+    /// Set when the config on disk was written by a newer Orbly. All saves are
+    /// refused so the newer file is never downgraded. Also set when the
+    /// administrator policy cannot be read, so nothing it locks is changed.
+    read_only_reason: Option<String>,
 }
 
 impl ConfigManager {
     pub fn new(app_data_dir: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_policy_file(app_data_dir, &Policy::system_path())
+    }
+
+    /// Like `new`, with the administrator policy read from `policy_path`
+    /// instead of the system location.
+    pub fn with_policy_file(
+        app_data_dir: PathBuf,
+        policy_path: &Path,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let config_path = Self::config_path(&app_data_dir);
 
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let mut config = Self::read_from_disk(&config_path)?;
-        if config.sync.enabled {
-            if let Some(cloud_json) = crate::config::icloud::load_from_icloud("orbly_config") {
-                if let Ok(cloud_config) = serde_json::from_str::<OrblyConfig>(&cloud_json) {
-                    config = cloud_config;
-                }
+        let (mut config, mut read_only_reason) = Self::read_from_disk(&config_path)?;
+        let policy = match Policy::load(policy_path) {
+            Ok(policy) => policy,
+            Err(e) => {
+                log::error!("{}", e);
+                read_only_reason.get_or_insert(format!("{}; changes will not be saved", e));
+                Policy::default()
+            }
+        };
+        let secrets = SecretStore::new(&app_data_dir);
+        if read_only_reason.is_none() {
+            // Move credentials left in plaintext (e.g. by older versions) out of the file
+            match secrets.seal(&mut config) {
+                Ok(true) => Self::write_to_disk(&config_path, &config)?,
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to move secrets out of config: {}", e),
+            }
+        }
+
+        // The synced config is pulled later, off the startup path: see `pull_synced`
+        let sync = SyncManager::new(&app_data_dir);
+
+        // Only in memory: the file keeps the user's own values
+        let user_values = policy.user_values(&config).unwrap_or_else(|e| {
+            log::warn!("Failed to read locked settings: {}", e);
+            UserValues::default()
+        });
+        if let Err(e) = policy.apply(&mut config) {
+            log::warn!("Failed to apply policy: {}", e);
+        }
+
+        let report = validation::validate_config(&config);
+        for issue in &report.errors {
+            log::warn!("Config error: {}", issue);
+        }
+        for issue in &report.warnings {
+            log::info!("Config warning: {}", issue);
+        }
+
+        let disk_state = DiskState {
+            fingerprint: Self::file_fingerprint(&config_path),
+            ..DiskState::default()
+        };
+        // Also catches edits made while Orbly was not running
+        let snapshots = SnapshotStore::new(&app_data_dir);
+        if let Ok(contents) = fs::read_to_string(&config_path) {
+            if let Err(e) = snapshots.record("startup", &contents) {
+                log::warn!("Failed to record config snapshot: {}", e);
             }
         }
-        Self::migrate_config(&mut config);
 
         Ok(Self {
             config: Mutex::new(config),
+            user_values: Mutex::new(user_values),
             config_path,
+            disk_state: Mutex::new(disk_state),
+            sync,
+            secrets,
+            snapshots,
+            policy,
+            read_only_reason,
         })
     }
 
+    pub fn sync_manager(&self) -> &SyncManager {
+        &self.sync
+    }
+
+    pub fn secrets(&self) -> &SecretStore {
+        &self.secrets
+    }
+
+    /// The administrator policy applied over the config.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Why the config cannot be saved, if it was loaded read-only.
+    pub fn read_only_reason(&self) -> Option<String> {
+        self.read_only_reason.clone()
+    }
+
+    fn ensure_writable(&self) -> Result<(), Box<dyn std::error::Error>> {
+        match &self.read_only_reason {
+            Some(reason) => Err(reason.clone().into()),
+            None => Ok(()),
+        }
+    }
+
     pub fn get_config(&self) -> OrblyConfig {
         self.config.lock().expect("config lock").clone()
     }
 
-    pub fn save_config(&self, config: OrblyConfig) -> Result<(), Box<dyn std::error::Error>> {
+    /// `reason` is recorded with the config snapshot, usually the name of the
+    /// command making the change.
+    pub fn save_config(
+        &self,
+        reason: &str,
+        mut config: OrblyConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_writable()?;
         let mut guard = self.config.lock().expect("config lock");
-        Self::persist_config(&self.config_path, &config)?;
+        self.persist(Some(reason), &guard, &mut config)?;
         *guard = config;
         Ok(())
     }
 
-    pub fn update_with<F>(&self, updater: F) -> Result<(), Box<dyn std::error::Error>>
+    pub fn update_with<F>(&self, reason: &str, updater: F) -> Result<(), Box<dyn std::error::Error>>
     where
         F: FnOnce(&mut OrblyConfig),
     {
+        self.ensure_writable()?;
         let mut guard = self.config.lock().expect("config lock");
         let mut updated = guard.clone();
         updater(&mut updated);
-        Self::persist_config(&self.config_path, &updated)?;
+        self.persist(Some(reason), &guard, &mut updated)?;
         *guard = updated;
         Ok(())
     }
 
+    /// Like `update_with`, for routine changes Orbly makes on its own, such as
+    /// window geometry, hibernation flags and timestamps. They are saved and
+    /// synced but not recorded as snapshots, which would crowd out the ones
+    /// worth rolling back to.
+    pub fn update_quietly<F>(&self, reason: &str, updater: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut OrblyConfig),
+    {
+        self.ensure_writable()?;
+        let mut guard = self.config.lock().expect("config lock");
+        let mut updated = guard.clone();
+        updater(&mut updated);
+        log::debug!("Saving config ({})", reason);
+        self.persist(None, &guard, &mut updated)?;
+        *guard = updated;
+        Ok(())
+    }
+
+    /// Like `update_with`, but the updater can return a value or fail, in
+    /// which case nothing is saved.
+    pub fn try_update_with<F, T>(
+        &self,
+        reason: &str,
+        updater: F,
+    ) -> Result<T, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut OrblyConfig) -> Result<T, String>,
+    {
+        self.ensure_writable()?;
+        let mut guard = self.config.lock().expect("config lock");
+        let mut updated = guard.clone();
+        let value = updater(&mut updated)?;
+        self.persist(Some(reason), &guard, &mut updated)?;
+        *guard = updated;
+        Ok(value)
+    }
+
     pub fn get_app(&self, app_id: &str) -> Option<AppConfig> {
         self.config
             .lock()
@@ -68,22 +242,40 @@ impl ConfigManager {
             .cloned()
     }
 
-    pub fn add_app(&self, mut app: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
-        if app.data_store_uuid.is_nil() {
-            app.data_store_uuid = Uuid::new_v4();
-        }
+    pub fn add_app(
+        &self,
+        reason: &str,
+        mut app: AppConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.policy.check_url(&app.url)?;
+        Self::assign_app_data_stores(&mut app);
 
-        self.update_with(move |config| {
+        self.update_with(reason, move |config| {
             config.apps.push(app);
         })
     }
 
-    pub fn update_app(&self, app: AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    /// Replace an app's config. The active account is kept unless it was
+    /// removed; it is only changed by switching accounts. Whether the app is
+    /// popped out, and where, is likewise only changed by its window.
+    pub fn update_app(
+        &self,
+        reason: &str,
+        mut app: AppConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_writable()?;
         let mut guard = self.config.lock().expect("config lock");
         let mut updated = guard.clone();
         if let Some(existing) = updated.apps.iter_mut().find(|a| a.id == app.id) {
+            app.active_account = existing.active_account.clone();
+            if app.current_account().is_none() {
+                app.active_account.clear();
+            }
+            app.detached = existing.detached;
+            app.popout_window = existing.popout_window.clone();
+            Self::assign_app_data_stores(&mut app);
             *existing = app;
-            Self::persist_config(&self.config_path, &updated)?;
+            self.persist(Some(reason), &guard, &mut updated)?;
             *guard = updated;
             Ok(())
         } else {
@@ -93,14 +285,16 @@ impl ConfigManager {
 
     pub fn remove_app(
         &self,
+        reason: &str,
         app_id: &str,
     ) -> Result<Option<AppConfig>, Box<dyn std::error::Error>> {
+        self.ensure_writable()?;
         let mut guard = self.config.lock().expect("config lock");
         let mut updated = guard.clone();
         let pos = updated.apps.iter().position(|a| a.id == app_id);
         if let Some(idx) = pos {
             let removed = updated.apps.remove(idx);
-            Self::persist_config(&self.config_path, &updated)?;
+            self.persist(Some(reason), &guard, &mut updated)?;
             *guard = updated;
             Ok(Some(removed))
         } else {
@@ -108,40 +302,511 @@ impl ConfigManager {
         }
     }
 
-    fn persist_config(
-        config_path: &PathBuf,
-        config: &OrblyConfig,
+    /// Write `config` (replacing `old`) to disk and queue a sync push,
+    /// recording a snapshot labelled `snapshot` if given. Refused if the
+    /// change introduces validation errors or goes against the administrator
+    /// policy. Plaintext secrets are moved into the secret store first.
+    fn persist(
+        &self,
+        snapshot: Option<&str>,
+        old: &OrblyConfig,
+        config: &mut OrblyConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let user_values = self.user_values.lock().expect("user values lock").clone();
+        self.persist_with(snapshot, old, config, user_values)
+    }
+
+    /// Like `persist`, with `user_values` replacing the user's own values of
+    /// locked fields, e.g. when a restored snapshot brings its own.
+    fn persist_with(
+        &self,
+        snapshot: Option<&str>,
+        old: &OrblyConfig,
+        config: &mut OrblyConfig,
+        user_values: UserValues,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        Self::write_to_disk(config_path, config)?;
+        self.policy.check(old, config)?;
+        Self::check_valid(old, config)?;
+        self.secrets.seal(config)?;
+        let on_disk = self.policy.unapply(config, &user_values)?;
+        self.write_tracked(snapshot, &on_disk)?;
+        *self.user_values.lock().expect("user values lock") = user_values;
+        self.secrets.release_unused(old, config);
         if config.sync.enabled {
-            if let Ok(json) = serde_json::to_string(config) {
-                crate::config::icloud::save_to_icloud("orbly_config", &json);
+            if let Some(json) = Self::sync_payload(&on_disk) {
+                self.sync.queue_push(&self.secrets.resolve_sync(&config.sync), json);
+            }
+        }
+        Ok(())
+    }
+
+    /// `config` as `config.toml` holds it: with the user's own values in the
+    /// fields the policy locks.
+    fn on_disk(&self, config: &OrblyConfig) -> Result<OrblyConfig, String> {
+        let user_values = self.user_values.lock().expect("user values lock");
+        self.policy.unapply(config, &user_values)
+    }
+
+    /// Write `config`, as returned by `on_disk`, to disk unless `config.toml`
+    /// holds an external edit that has not been loaded yet, which would
+    /// otherwise be overwritten, and record a snapshot of it labelled
+    /// `snapshot` if given.
+    fn write_tracked(
+        &self,
+        snapshot: Option<&str>,
+        config: &OrblyConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut disk_state = self.disk_state.lock().expect("disk state lock");
+        let current = Self::file_fingerprint(&self.config_path);
+        if current.is_some() && current != disk_state.fingerprint {
+            return Err(match &disk_state.error {
+                Some(e) => format!(
+                    "config.toml has an invalid edit ({}); fix or revert it before changing settings",
+                    e
+                ),
+                None => "config.toml was changed outside Orbly and has not been loaded yet".to_string(),
+            }
+            .into());
+        }
+        Self::write_to_disk(&self.config_path, config)?;
+        disk_state.fingerprint = Self::file_fingerprint(&self.config_path);
+        drop(disk_state);
+        if let Some(reason) = snapshot {
+            self.record_snapshot(reason, config);
+        }
+        Ok(())
+    }
+
+    fn record_snapshot(&self, reason: &str, config: &OrblyConfig) {
+        let result = toml::to_string_pretty(config)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| self.snapshots.record(reason, &contents));
+        if let Err(e) = result {
+            log::warn!("Failed to record config snapshot: {}", e);
+        }
+    }
+
+    /// Record the current config, e.g. before an import replaces it.
+    pub fn snapshot(&self, reason: &str) {
+        match self.on_disk(&self.get_config()) {
+            Ok(config) => self.record_snapshot(reason, &config),
+            Err(e) => log::warn!("Failed to record config snapshot: {}", e),
+        }
+    }
+
+    /// Config snapshots, newest first.
+    pub fn list_snapshots(&self) -> Vec<SnapshotInfo> {
+        self.snapshots.list()
+    }
+
+    fn load_snapshot(&self, id: &str) -> Result<OrblyConfig, String> {
+        let contents = self.snapshots.read(id)?;
+        Self::parse_external(&contents)
+    }
+
+    /// Changes from snapshot `id` to snapshot `against`, or to the current
+    /// config when `against` is `None`.
+    pub fn diff_snapshot(
+        &self,
+        id: &str,
+        against: Option<&str>,
+    ) -> Result<Vec<ConfigChange>, String> {
+        let before = self.load_snapshot(id)?;
+        let after = match against {
+            Some(other) => self.load_snapshot(other)?,
+            None => self.on_disk(&self.get_config())?,
+        };
+        let to_value = |c: &OrblyConfig| serde_json::to_value(c).map_err(|e| e.to_string());
+        Ok(snapshots::diff(&to_value(&before)?, &to_value(&after)?))
+    }
+
+    /// Replace the config with snapshot `id`, recording the current config
+    /// first so the restore can be undone. Credentials deleted since the
+    /// snapshot was taken are not brought back.
+    pub fn restore_snapshot(&self, id: &str) -> Result<ConfigReload, Box<dyn std::error::Error>> {
+        let mut restored = self.load_snapshot(id)?;
+        Self::assign_missing_data_stores(&mut restored);
+        let user_values = self.policy.user_values(&restored)?;
+        self.policy.apply(&mut restored)?;
+        self.ensure_writable()?;
+        self.snapshot("before restore_config_snapshot");
+        let mut guard = self.config.lock().expect("config lock");
+        let old = guard.clone();
+        self.persist_with(Some("restore_config_snapshot"), &old, &mut restored, user_values)?;
+        *guard = restored.clone();
+        Ok(ConfigReload { old, new: restored })
+    }
+
+    /// Fail with the validation errors `config` has that `old` did not.
+    fn check_valid(old: &OrblyConfig, config: &OrblyConfig) -> Result<(), String> {
+        let report = validation::validate_config(config);
+        if report.is_valid() {
+            return Ok(());
+        }
+        let previous = validation::validate_config(old);
+        let new_errors = report.new_errors(&previous);
+        if new_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid config: {}", validation::describe(&new_errors)))
+        }
+    }
+
+    fn file_fingerprint(path: &Path) -> Option<String> {
+        let contents = fs::read(path).ok()?;
+        Some(hex::encode(Sha256::digest(&contents)))
+    }
+
+    /// Load `config.toml` if it was edited outside Orbly since it was last
+    /// read or written. Returns the old and new config when the edit was
+    /// applied, `None` when there is nothing new, and an error (reported once
+    /// per edit) when the edit cannot be loaded; the in-memory config and the
+    /// file are then left alone.
+    pub fn reload_from_disk(&self) -> Result<Option<ConfigReload>, String> {
+        if self.read_only_reason.is_some() {
+            return Ok(None);
+        }
+        let mut guard = self.config.lock().expect("config lock");
+        let Ok(contents) = fs::read_to_string(&self.config_path) else {
+            return Ok(None);
+        };
+        let fingerprint = hex::encode(Sha256::digest(contents.as_bytes()));
+        {
+            let disk_state = self.disk_state.lock().expect("disk state lock");
+            if disk_state.fingerprint.as_ref() == Some(&fingerprint)
+                || disk_state.rejected.as_ref() == Some(&fingerprint)
+            {
+                return Ok(None);
+            }
+        }
+
+        // Locked fields edited in the file keep the policy's values in
+        // memory rather than being refused; the file keeps the edit
+        let parsed = Self::parse_external(&contents).and_then(|config| {
+            let user_values = self.policy.user_values(&config)?;
+            let mut applied = config.clone();
+            self.policy.apply(&mut applied)?;
+            Self::check_valid(&guard, &applied).map(|_| (config, user_values))
+        });
+        let (mut on_disk, user_values) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let mut disk_state = self.disk_state.lock().expect("disk state lock");
+                disk_state.rejected = Some(fingerprint);
+                disk_state.error = Some(e.clone());
+                return Err(e);
+            }
+        };
+
+        {
+            let mut disk_state = self.disk_state.lock().expect("disk state lock");
+            disk_state.fingerprint = Some(fingerprint);
+            disk_state.rejected = None;
+            disk_state.error = None;
+        }
+        let old = guard.clone();
+        // Plaintext credentials typed into the file are moved to the secret store
+        let sealed = self.secrets.seal(&mut on_disk).unwrap_or_else(|e| {
+            log::warn!("Failed to move secrets out of config: {}", e);
+            false
+        });
+        let mut updated = on_disk.clone();
+        if let Err(e) = self.policy.apply(&mut updated) {
+            log::warn!("Failed to apply policy: {}", e);
+        }
+        *self.user_values.lock().expect("user values lock") = user_values;
+        if sealed {
+            if let Err(e) = self.write_tracked(Some("external edit"), &on_disk) {
+                log::warn!("Failed to rewrite config.toml: {}", e);
+            }
+        } else {
+            self.record_snapshot("external edit", &on_disk);
+        }
+        self.secrets.release_unused(&old, &updated);
+        if updated.sync.enabled {
+            if let Some(json) = Self::sync_payload(&on_disk) {
+                self.sync.queue_push(&self.secrets.resolve_sync(&updated.sync), json);
+            }
+        }
+        *guard = updated.clone();
+        Ok(Some(ConfigReload { old, new: updated }))
+    }
+
+    /// Parse an externally edited config in memory, migrating older versions
+    /// without writing anything back.
+    fn parse_external(contents: &str) -> Result<OrblyConfig, String> {
+        let mut value: toml::Value = toml::from_str(contents).map_err(|e| e.to_string())?;
+        migrations::migrate(&mut value, |_, _| Ok(())).map_err(|e| e.to_string())?;
+        value.try_into().map_err(|e: toml::de::Error| e.to_string())
+    }
+
+    /// The config as pushed to sync backends, without machine-local fields.
+    pub fn sync_payload(config: &OrblyConfig) -> Option<serde_json::Value> {
+        let mut value = serde_json::to_value(config).ok()?;
+        merge::strip_machine_local(&mut value);
+        Some(value)
+    }
+
+    /// Three-way merge a pulled config into `local`. Machine-local fields are
+    /// kept from `local`; apps new to this machine get a fresh data store.
+    fn merge_remote(
+        local: &OrblyConfig,
+        remote_json: &serde_json::Value,
+        base: Option<&serde_json::Value>,
+    ) -> Result<MergedRemote, Box<dyn std::error::Error>> {
+        // Round-trip the remote through OrblyConfig so migrations and defaults
+        // apply and it compares cleanly with the local payload
+        let remote = Self::parse_cloud_config(remote_json)?;
+        let remote_payload = Self::sync_payload(&remote).ok_or("Failed to serialize synced config")?;
+        let local_payload = Self::sync_payload(local).ok_or("Failed to serialize config")?;
+
+        let result = merge::merge_configs(base, &local_payload, &remote_payload);
+        let mut merged = result.merged;
+        merge::restore_machine_local(&mut merged, &serde_json::to_value(local)?);
+        let mut config: OrblyConfig = serde_json::from_value(merged)?;
+        Self::assign_missing_data_stores(&mut config);
+        Ok(MergedRemote {
+            config,
+            conflicts: result.conflicts,
+            remote_payload,
+        })
+    }
+
+    fn assign_missing_data_stores(config: &mut OrblyConfig) {
+        for app in config.apps.iter_mut() {
+            Self::assign_app_data_stores(app);
+        }
+    }
+
+    /// Give the app and each of its accounts a data store if they lack one.
+    fn assign_app_data_stores(app: &mut AppConfig) {
+        if app.data_store_uuid.is_nil() {
+            app.data_store_uuid = Uuid::new_v4();
+        }
+        for account in app.accounts.iter_mut() {
+            if account.data_store_uuid.is_nil() {
+                account.data_store_uuid = Uuid::new_v4();
             }
         }
+    }
+
+    /// Pull the synced config and merge it into the local one, as Orbly does
+    /// once after starting. Blocking, as a WebDAV server can take a while to
+    /// answer, so it runs off the main thread. Returns the config it replaced
+    /// if the merge changed anything.
+    pub fn pull_synced(&self) -> Option<ConfigReload> {
+        if self.read_only_reason.is_some() {
+            return None;
+        }
+        let settings = self.secrets.resolve_sync(&self.get_config().sync);
+        if !settings.enabled {
+            return None;
+        }
+        let envelope = match self.sync.pull(&settings) {
+            Ok(Some(envelope)) => envelope,
+            Ok(None) => {
+                log::info!("No synced config found, keeping local config");
+                return None;
+            }
+            Err(e) => {
+                log::warn!("Failed to pull synced config: {}", e);
+                return None;
+            }
+        };
+
+        let mut guard = self.config.lock().expect("config lock");
+        let (local, merged, user_values) = match self.merge_pulled(&guard, &envelope.config) {
+            Ok(merged) => merged,
+            Err(e) => {
+                log::warn!("Ignoring synced config: {}", e);
+                return None;
+            }
+        };
+        if !merged.conflicts.is_empty() {
+            log::warn!("Config sync found {} conflict(s)", merged.conflicts.len());
+        }
+        self.sync.set_conflicts(merged.conflicts);
+        let on_disk = merged.config;
+        let mut config = on_disk.clone();
+        if let Err(e) = self.policy.apply(&mut config) {
+            log::warn!("Failed to apply policy: {}", e);
+        }
+        let changed = serde_json::to_value(&on_disk).ok() != serde_json::to_value(&local).ok();
+        if changed {
+            if let Err(e) = self.write_tracked(Some("sync pull"), &on_disk) {
+                log::warn!("Failed to save synced config: {}", e);
+                return None;
+            }
+            *self.user_values.lock().expect("user values lock") = user_values;
+        }
+        if let Some(json) = Self::sync_payload(&on_disk) {
+            if json == merged.remote_payload {
+                self.sync.set_base(&json);
+            } else {
+                // Push so other devices pick up the local side of the merge
+                self.sync.queue_push(&settings, json);
+            }
+        }
+        if !changed {
+            return None;
+        }
+        let old = std::mem::replace(&mut *guard, config.clone());
+        Some(ConfigReload { old, new: config })
+    }
+
+    /// Merge a pulled config into the user's own settings, as `config.toml`
+    /// holds them; the policy goes back on top afterwards. Returns those
+    /// settings, the merge and the user's values of the locked fields in it.
+    fn merge_pulled(
+        &self,
+        current: &OrblyConfig,
+        remote_json: &serde_json::Value,
+    ) -> Result<(OrblyConfig, MergedRemote, UserValues), Box<dyn std::error::Error>> {
+        let local = self.on_disk(current)?;
+        let merged = Self::merge_remote(&local, remote_json, self.sync.base().as_ref())?;
+        let user_values = self.policy.user_values(&merged.config)?;
+        Ok((local, merged, user_values))
+    }
+
+    /// Pull the synced config, merge it with local changes and push the
+    /// result. Blocking; returns the conflicts awaiting a decision.
+    pub fn sync_now(&self) -> Result<Vec<SyncConflict>, Box<dyn std::error::Error>> {
+        self.ensure_writable()?;
+        let settings = self.secrets.resolve_sync(&self.get_config().sync);
+        if !settings.enabled {
+            return Err("Sync is disabled".into());
+        }
+        let envelope = self.sync.pull(&settings)?;
+        let (payload, remote_payload) = {
+            let mut guard = self.config.lock().expect("config lock");
+            let mut remote_payload = None;
+            let mut local = self.on_disk(&guard)?;
+            if let Some(envelope) = envelope {
+                let (_, merged, user_values) = self.merge_pulled(&guard, &envelope.config)?;
+                self.sync.set_conflicts(merged.conflicts);
+                let mut config = merged.config.clone();
+                self.policy.apply(&mut config)?;
+                self.write_tracked(Some("sync_now"), &merged.config)?;
+                *self.user_values.lock().expect("user values lock") = user_values;
+                *guard = config;
+                local = merged.config;
+                remote_payload = Some(merged.remote_payload);
+            }
+            let payload = Self::sync_payload(&local).ok_or("Failed to serialize config")?;
+            (payload, remote_payload)
+        };
+        // Re-uploading an identical config would only make other devices
+        // think there is something new to merge
+        if remote_payload.as_ref() == Some(&payload) {
+            self.sync.set_base(&payload);
+        } else {
+            self.sync.push_blocking(&settings, payload)?;
+        }
+        Ok(self.sync.conflicts())
+    }
+
+    /// Settle a sync conflict by keeping the local value or taking the remote one.
+    pub fn resolve_sync_conflict(
+        &self,
+        conflict_id: &str,
+        use_remote: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_writable()?;
+        let conflict = self
+            .sync
+            .conflicts()
+            .into_iter()
+            .find(|c| c.id == conflict_id)
+            .ok_or_else(|| format!("No sync conflict '{}'", conflict_id))?;
+        if use_remote {
+            let mut guard = self.config.lock().expect("config lock");
+            let mut value = serde_json::to_value(self.on_disk(&guard)?)?;
+            merge::apply_resolution(&mut value, &conflict, &conflict.remote)?;
+            let mut updated: OrblyConfig = serde_json::from_value(value)?;
+            Self::assign_missing_data_stores(&mut updated);
+            let user_values = self.policy.user_values(&updated)?;
+            self.policy.apply(&mut updated)?;
+            self.persist_with(Some("resolve_sync_conflict"), &guard, &mut updated, user_values)?;
+            *guard = updated;
+        }
+        self.sync.remove_conflict(conflict_id);
         Ok(())
     }
 
-    fn migrate_config(config: &mut OrblyConfig) {
-        loop {
-            match config.general.config_version {
-                0 => {
-                    // v0 → v1: initial migration
-                    config.general.config_version = 1;
+    /// Parse a synced JSON config, running it through the same migrations as
+    /// the on-disk file. A synced config from a newer Orbly is rejected.
+    fn parse_cloud_config(json: &serde_json::Value) -> Result<OrblyConfig, Box<dyn std::error::Error>> {
+        let mut value = migrations::json_to_toml(json.clone())?;
+        migrations::migrate(&mut value, |_, _| Ok(()))?;
+        Ok(value.try_into()?)
+    }
+
+    /// Parse config TOML, migrating it to the current version if needed.
+    /// Before each migration step the pre-step contents are written to
+    /// `config.v<N>.toml.bak` next to `path`, and the migrated config is then
+    /// written back to `path`. A config from a newer Orbly is returned together
+    /// with a read-only reason instead of being migrated.
+    fn load_from_str(
+        path: &PathBuf,
+        contents: &str,
+    ) -> Result<(OrblyConfig, Option<String>), Box<dyn std::error::Error>> {
+        let mut value: toml::Value = toml::from_str(contents)?;
+        let original_version = migrations::config_version(&value);
+
+        let snapshots = path.parent().map(SnapshotStore::new);
+        let result = migrations::migrate(&mut value, |from, pre_step| {
+            let backup = Self::migration_backup_path(path, from);
+            let text = if from == original_version {
+                contents.to_string()
+            } else {
+                toml::to_string_pretty(pre_step).map_err(|e| e.to_string())?
+            };
+            if let Some(snapshots) = &snapshots {
+                let reason = format!("before migration from config version {}", from);
+                if let Err(e) = snapshots.record(&reason, &text) {
+                    log::warn!("Failed to record config snapshot: {}", e);
                 }
-                1 => break,
-                v => {
-                    log::warn!("Unknown config version {v}, skipping migration");
-                    break;
+            }
+            fs::write(&backup, text).map_err(|e| format!("failed to write {:?}: {}", backup, e))
+        });
+
+        match result {
+            Ok(applied) => {
+                let config: OrblyConfig = value.try_into()?;
+                if !applied.is_empty() {
+                    Self::write_to_disk(path, &config)?;
                 }
+                Ok((config, None))
+            }
+            Err(MigrationError::NewerVersion(version)) => {
+                log::warn!(
+                    "Config version {} is newer than supported version {}; opening read-only",
+                    version,
+                    migrations::CURRENT_CONFIG_VERSION
+                );
+                let config: OrblyConfig = value.try_into()?;
+                let reason = format!(
+                    "config.toml was written by a newer version of Orbly (config version {}, this build supports {}); changes will not be saved",
+                    version,
+                    migrations::CURRENT_CONFIG_VERSION
+                );
+                Ok((config, Some(reason)))
             }
+            Err(e) => Err(Box::new(e)),
         }
     }
 
+    fn migration_backup_path(path: &Path, from_version: u32) -> PathBuf {
+        path.with_extension(format!("v{}.toml.bak", from_version))
+    }
+
     fn config_path(app_data_dir: &PathBuf) -> PathBuf {
         app_data_dir.join("config.toml")
     }
 
-    fn read_from_disk(path: &PathBuf) -> Result<OrblyConfig, Box<dyn std::error::Error>> {
+    fn read_from_disk(
+        path: &PathBuf,
+    ) -> Result<(OrblyConfig, Option<String>), Box<dyn std::error::Error>> {
         let backup_path = path.with_extension("toml.bak");
 
         if !path.exists() {
@@ -151,27 +816,29 @@ impl ConfigManager {
                 let _ = fs::rename(&backup_path, path);
                 if path.exists() {
                     let contents = fs::read_to_string(path)?;
-                    return Ok(toml::from_str(&contents)?);
+                    return Self::load_from_str(path, &contents);
                 }
             }
             let config = OrblyConfig::default();
             Self::write_to_disk(path, &config)?;
-            return Ok(config);
+            return Ok((config, None));
         }
 
         let contents = fs::read_to_string(path)?;
-        match toml::from_str(&contents) {
-            Ok(config) => Ok(config),
+        match Self::load_from_str(path, &contents) {
+            Ok(loaded) => Ok(loaded),
             Err(e) => {
                 log::warn!("Config file is corrupt: {}. Attempting backup recovery.", e);
                 if backup_path.exists() {
                     let backup_contents = fs::read_to_string(&backup_path)?;
-                    let config: OrblyConfig = toml::from_str(&backup_contents)?;
+                    let (config, read_only_reason) = Self::load_from_str(path, &backup_contents)?;
                     // Restore the good backup as the main config
-                    Self::write_to_disk(path, &config)?;
-                    Ok(config)
+                    if read_only_reason.is_none() {
+                        Self::write_to_disk(path, &config)?;
+                    }
+                    Ok((config, read_only_reason))
                 } else {
-                    Err(Box::new(e))
+                    Err(e)
                 }
             }
         }
@@ -212,6 +879,7 @@ impl ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::models::ThemeMode;
 
     #[test]
     fn test_default_config_serialization_roundtrip() {
@@ -232,21 +900,317 @@ mod tests {
         assert_eq!(config.apps.len(), 0);
     }
 
+    fn temp_config_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("orbly-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir.join("config.toml")
+    }
+
+    /// A manager for `dir` under no administrator policy, whatever this
+    /// machine has installed.
+    fn test_manager(dir: &Path) -> ConfigManager {
+        ConfigManager::with_policy_file(dir.to_path_buf(), &dir.join("policy.toml"))
+            .expect("manager")
+    }
+
     #[test]
     fn test_migrate_v0_to_v1() {
-        let mut config = OrblyConfig::default();
-        config.general.config_version = 0;
-        ConfigManager::migrate_config(&mut config);
-        assert_eq!(config.general.config_version, 1);
+        let path = temp_config_path();
+        let original = "# hand-edited\n[general]\nconfig_version = 0\ntray_mode = false\n";
+        fs::write(&path, original).expect("write");
+
+        let (config, read_only) = ConfigManager::read_from_disk(&path).expect("load");
+        assert_eq!(config.general.config_version, migrations::CURRENT_CONFIG_VERSION);
+        assert!(!config.general.tray_mode);
+        assert!(read_only.is_none());
+
+        // Pre-migration file is kept verbatim, and the migrated config is written back
+        let backup = ConfigManager::migration_backup_path(&path, 0);
+        assert_eq!(fs::read_to_string(&backup).expect("backup"), original);
+        let on_disk: OrblyConfig =
+            toml::from_str(&fs::read_to_string(&path).expect("read")).expect("parse");
+        assert_eq!(on_disk.general.config_version, migrations::CURRENT_CONFIG_VERSION);
+        let snapshots = SnapshotStore::new(path.parent().unwrap());
+        let history = snapshots.list();
+        assert_eq!(history[0].reason, "before migration from config version 0");
+        assert_eq!(snapshots.read(&history[0].id).expect("snapshot"), original);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
     }
 
     #[test]
     fn test_migrate_unknown_version() {
-        let mut config = OrblyConfig::default();
-        config.general.config_version = 99;
-        ConfigManager::migrate_config(&mut config);
-        // Should not crash, version stays as-is
+        let path = temp_config_path();
+        let original = "[general]\nconfig_version = 99\nfuture_field = true\n";
+        fs::write(&path, original).expect("write");
+
+        let (config, read_only) = ConfigManager::read_from_disk(&path).expect("load");
+        // Should not crash, version stays as-is and the config is read-only
         assert_eq!(config.general.config_version, 99);
+        assert!(read_only.is_some());
+
+        let manager = ConfigManager {
+            config: Mutex::new(config.clone()),
+            user_values: Mutex::new(UserValues::default()),
+            config_path: path.clone(),
+            disk_state: Mutex::new(DiskState {
+                fingerprint: ConfigManager::file_fingerprint(&path),
+                ..DiskState::default()
+            }),
+            sync: SyncManager::new(path.parent().unwrap()),
+            secrets: SecretStore::new(path.parent().unwrap()),
+            snapshots: SnapshotStore::new(path.parent().unwrap()),
+            policy: Policy::default(),
+            read_only_reason: read_only,
+        };
+        assert!(manager.save_config("test", config).is_err());
+        assert!(manager.update_with("test", |c| c.general.tray_mode = false).is_err());
+        assert_eq!(fs::read_to_string(&path).expect("read"), original);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_current_version_not_rewritten() {
+        let path = temp_config_path();
+        let original = format!(
+            "[general]\nconfig_version = {}\n",
+            migrations::CURRENT_CONFIG_VERSION
+        );
+        fs::write(&path, &original).expect("write");
+        let (_, read_only) = ConfigManager::read_from_disk(&path).expect("load");
+        assert!(read_only.is_none());
+        assert_eq!(fs::read_to_string(&path).expect("read"), original);
+        assert!(!ConfigManager::migration_backup_path(&path, 0).exists());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_startup_merges_synced_config() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        let sync_dir = dir.join("synced");
+        let app_toml = |id: &str, url: &str, uuid: &str| {
+            format!(
+                "[[apps]]\nid = \"{id}\"\nname = \"{id}\"\nurl = \"{url}\"\ndata_store_uuid = \"{uuid}\"\n"
+            )
+        };
+        let local_uuid = Uuid::new_v4().to_string();
+
+        // Base: what both machines last agreed on
+        let base_toml = app_toml("a", "https://a", &local_uuid);
+        let base: OrblyConfig = toml::from_str(&base_toml).expect("base");
+        SyncManager::new(&dir).set_base(&ConfigManager::sync_payload(&base).unwrap());
+
+        // Local edit made offline: theme and window position
+        let local_toml = format!(
+            "[general]\ntheme = \"dark\"\n[general.window_state]\nx = 10.0\n\n[sync]\nenabled = true\nbackend = \"folder\"\nfolder_path = {:?}\n\n{}",
+            sync_dir.to_string_lossy(),
+            base_toml
+        );
+        fs::write(&path, local_toml).expect("write local");
+
+        // Remote edit from another machine: changed URL and a new app
+        let mut remote: OrblyConfig = toml::from_str(&format!(
+            "{}\n{}",
+            app_toml("a", "https://a.remote", &Uuid::new_v4().to_string()),
+            app_toml("b", "https://b", &Uuid::new_v4().to_string())
+        ))
+        .expect("remote");
+        remote.general.window_state.x = Some(500.0);
+        let envelope = serde_json::json!({
+            "format": 1,
+            "device_id": "other-device",
+            "updated_at": "2026-01-01T00:00:00Z",
+            "config": ConfigManager::sync_payload(&remote).unwrap(),
+        });
+        fs::create_dir_all(&sync_dir).expect("sync dir");
+        fs::write(sync_dir.join("orbly-sync.json"), envelope.to_string()).expect("write remote");
+
+        let manager = test_manager(&dir);
+        // Nothing is pulled while starting up
+        assert_eq!(manager.get_config().apps.len(), 1);
+        let reload = manager.pull_synced().expect("merged");
+        assert_eq!(reload.old.apps.len(), 1);
+        let config = manager.get_config();
+        assert_eq!(reload.new.apps.len(), 2);
+        assert_eq!(config.general.theme, ThemeMode::Dark);
+        assert_eq!(config.general.window_state.x, Some(10.0));
+        assert_eq!(config.apps.len(), 2);
+        assert_eq!(config.apps[0].url, "https://a.remote");
+        assert_eq!(config.apps[0].data_store_uuid.to_string(), local_uuid);
+        assert!(!config.apps[1].data_store_uuid.is_nil());
+        assert!(manager.sync_manager().conflicts().is_empty());
+
+        // The merge is saved locally
+        let on_disk: OrblyConfig =
+            toml::from_str(&fs::read_to_string(&path).expect("read")).expect("parse");
+        assert_eq!(on_disk.apps.len(), 2);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_external_edit_is_reloaded() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        let manager = test_manager(&dir);
+
+        // Orbly's own writes are not reported as external edits
+        manager.update_with("test", |c| c.general.tray_mode = false).expect("save");
+        assert!(manager.reload_from_disk().expect("reload").is_none());
+
+        let edited = fs::read_to_string(&path)
+            .expect("read")
+            .replace("theme = \"system\"", "theme = \"dark\"");
+        fs::write(&path, edited).expect("write");
+        let reload = manager.reload_from_disk().expect("reload").expect("changed");
+        assert_eq!(reload.old.general.theme, ThemeMode::System);
+        assert_eq!(reload.new.general.theme, ThemeMode::Dark);
+        assert_eq!(manager.get_config().general.theme, ThemeMode::Dark);
+        assert!(manager.reload_from_disk().expect("reload").is_none());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_invalid_external_edit_is_not_clobbered() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        let manager = test_manager(&dir);
+
+        let broken = "[general\ntheme = \"dark\"\n";
+        fs::write(&path, broken).expect("write");
+        assert!(manager.reload_from_disk().is_err());
+        // Reported once, then ignored until the file changes again
+        assert!(manager.reload_from_disk().expect("reload").is_none());
+        assert_eq!(manager.get_config().general.theme, ThemeMode::System);
+
+        // Saving would overwrite the user's edit, so it is refused
+        assert!(manager.update_with("test", |c| c.general.tray_mode = false).is_err());
+        assert_eq!(fs::read_to_string(&path).expect("read"), broken);
+
+        fs::write(&path, "[general]\ntheme = \"light\"\n").expect("write");
+        assert!(manager.reload_from_disk().expect("reload").is_some());
+        assert!(manager.update_with("test", |c| c.general.tray_mode = false).is_ok());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_save_with_new_validation_error_is_refused() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        fs::write(&path, "[general]\nconfig_version = 1\ndnd_schedule_end = \"8\"\n").expect("write");
+        let manager = test_manager(&dir);
+
+        let err = manager
+            .update_with("test", |c| c.general.dnd_schedule_start = "9am".to_string())
+            .expect_err("invalid time");
+        assert!(err.to_string().contains("general.dnd_schedule_start"));
+        assert_eq!(manager.get_config().general.dnd_schedule_start, "18:00");
+
+        // The error already on disk does not block unrelated changes
+        assert!(manager.update_with("test", |c| c.general.tray_mode = false).is_ok());
+
+        // An external edit that adds an error is rejected like a parse error
+        let edited = fs::read_to_string(&path)
+            .expect("read")
+            .replace("cpu_alert_threshold = 30.0", "cpu_alert_threshold = -1.0");
+        fs::write(&path, edited).expect("write");
+        let Err(err) = manager.reload_from_disk() else {
+            panic!("invalid edit was applied");
+        };
+        assert!(err.contains("general.cpu_alert_threshold"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_restore_snapshot() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        let manager = test_manager(&dir);
+
+        manager
+            .update_with("update_general_config", |c| c.general.theme = ThemeMode::Dark)
+            .expect("save");
+        manager
+            .update_with("update_general_config_again", |c| c.general.tray_mode = false)
+            .expect("save");
+        let history = manager.list_snapshots();
+        assert_eq!(history[0].reason, "update_general_config_again");
+        let dark = &history[1];
+        assert_eq!(dark.reason, "update_general_config");
+
+        let changes = manager.diff_snapshot(&dark.id, None).expect("diff");
+        let paths: Vec<_> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["general.tray_mode"]);
+
+        let reload = manager.restore_snapshot(&dark.id).expect("restore");
+        assert!(!reload.old.general.tray_mode);
+        assert!(manager.get_config().general.tray_mode);
+        assert_eq!(manager.get_config().general.theme, ThemeMode::Dark);
+        let history = manager.list_snapshots();
+        assert_eq!(history[0].reason, "restore_config_snapshot");
+        // The pre-restore config matched the latest snapshot, so it was not recorded twice
+        assert_eq!(history[1].reason, "update_general_config_again");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_quiet_updates_are_not_snapshotted() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        let manager = test_manager(&dir);
+
+        manager
+            .update_with("update_general_config", |c| c.general.theme = ThemeMode::Dark)
+            .expect("save");
+        manager
+            .update_quietly("window state", |c| c.general.window_state.x = Some(10.0))
+            .expect("save");
+        manager
+            .update_quietly("auto-hibernate", |c| c.general.window_state.y = Some(20.0))
+            .expect("save");
+        let history = manager.list_snapshots();
+        assert_eq!(history[0].reason, "update_general_config");
+        assert!(history.iter().all(|s| s.reason != "window state"));
+        // Quiet updates are still saved
+        let saved = fs::read_to_string(&path).expect("read");
+        assert!(saved.contains("x = 10.0"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_policy_leaves_user_settings_on_disk() {
+        let path = temp_config_path();
+        let dir = path.parent().unwrap().to_path_buf();
+        fs::write(
+            &path,
+            "[general]\ndeveloper_mode = true\n\n[adblock]\nenabled = false\n",
+        )
+        .expect("write");
+        let policy_path = dir.join("policy.toml");
+        fs::write(
+            &policy_path,
+            "[settings.general]\ndeveloper_mode = false\n\n[settings.adblock]\nenabled = true\n",
+        )
+        .expect("write policy");
+
+        let manager = test_manager(&dir);
+        let config = manager.get_config();
+        assert!(!config.general.developer_mode);
+        assert!(config.adblock.enabled);
+        assert!(manager
+            .update_with("test", |c| c.general.developer_mode = true)
+            .is_err());
+        manager
+            .update_with("test", |c| c.general.theme = ThemeMode::Dark)
+            .expect("save");
+        drop(manager);
+
+        // Lifting the policy brings the user's own values back
+        fs::remove_file(&policy_path).expect("remove policy");
+        let config = test_manager(&dir).get_config();
+        assert!(config.general.developer_mode);
+        assert!(!config.adblock.enabled);
+        assert_eq!(config.general.theme, ThemeMode::Dark);
+        let _ = fs::remove_dir_all(dir);
     }
 
     #[test]
@@ -0,0 +1,604 @@
+//! Three-way merge of synced configs.
+//!
+//! Configs are merged as JSON values so the merge does not need to know every
+//! field. Apps and workspaces are matched by id and merged field by field;
+//! other sections are merged field by field. A field changed differently on
+//! both sides since the last common ancestor is a conflict: the local value is
+//! kept and the conflict is reported so the user can pick a side.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Lists of items that are matched by their `id` rather than compared whole,
+/// as (section, path from the config root).
+const KEYED_LISTS: &[(&str, &[&str])] = &[
+    ("apps", &["apps"]),
+    ("workspaces", &["workspaces", "items"]),
+];
+
+/// Fields that describe this machine rather than the user's setup. They are
+/// stripped before pushing and always kept from the local config.
+const MACHINE_LOCAL_FIELDS: &[&[&str]] = &[
+    &["sync"],
+    &["general", "window_state"],
+    &["downloads", "default_directory"],
+    &["workspaces", "active"],
+];
+
+/// Per-app fields that are machine-local. Secret handles only resolve on the
+/// machine that stored them, and each machine picks its own active account
+/// and places its own windows.
+pub const MACHINE_LOCAL_APP_FIELDS: &[&str] = &[
+    "download_directory",
+    "data_store_uuid",
+    "proxy_credentials",
+    "active_account",
+    "detached",
+    "popout_window",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SyncConflict {
+    /// Stable identifier, e.g. `apps.<app id>.url` or `general.theme`.
+    pub id: String,
+    /// Top-level section, e.g. "general", "apps" or "workspaces".
+    pub section: String,
+    /// Id of the app or workspace the conflict belongs to.
+    pub item_id: Option<String>,
+    /// Display name of the app or workspace.
+    pub item_name: Option<String>,
+    /// Conflicting field, or `None` when one side deleted the whole item.
+    pub field: Option<String>,
+    /// Local value (`null` if deleted locally).
+    pub local: Value,
+    /// Remote value (`null` if deleted remotely).
+    pub remote: Value,
+}
+
+#[derive(Debug)]
+pub struct MergeResult {
+    pub merged: Value,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Whether a serialized app is ephemeral. Ephemeral apps are throwaway
+/// sessions and never leave this machine.
+fn is_ephemeral(app: &Value) -> bool {
+    app.get("ephemeral")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Remove machine-local fields and ephemeral apps from a serialized config.
+pub fn strip_machine_local(config: &mut Value) {
+    for path in MACHINE_LOCAL_FIELDS {
+        let (field, parents) = path.split_last().expect("non-empty path");
+        if let Some(obj) = get_path_mut(config, parents).and_then(Value::as_object_mut) {
+            obj.remove(*field);
+        }
+    }
+    if let Some(apps) = config.get_mut("apps").and_then(Value::as_array_mut) {
+        apps.retain(|app| !is_ephemeral(app));
+        for app in apps.iter_mut().filter_map(Value::as_object_mut) {
+            for field in MACHINE_LOCAL_APP_FIELDS {
+                app.remove(*field);
+            }
+        }
+    }
+}
+
+/// Copy machine-local fields and ephemeral apps from `local` into `merged`.
+/// Apps that only exist in `merged` keep whatever they have (normally
+/// nothing, so serde defaults apply).
+pub fn restore_machine_local(merged: &mut Value, local: &Value) {
+    for path in MACHINE_LOCAL_FIELDS {
+        let (field, parents) = path.split_last().expect("non-empty path");
+        let Some(value) = get_path(local, path).cloned() else {
+            continue;
+        };
+        if let Some(obj) = get_path_mut(merged, parents).and_then(Value::as_object_mut) {
+            obj.insert(field.to_string(), value);
+        }
+    }
+    let local_apps = local.get("apps").and_then(Value::as_array);
+    if let (Some(local_apps), Some(apps)) = (
+        local_apps,
+        merged.get_mut("apps").and_then(Value::as_array_mut),
+    ) {
+        for app in apps.iter_mut() {
+            let Some(local_app) = local_apps.iter().find(|a| item_id(a) == item_id(app)) else {
+                continue;
+            };
+            if let Some(obj) = app.as_object_mut() {
+                for field in MACHINE_LOCAL_APP_FIELDS {
+                    if let Some(value) = local_app.get(*field) {
+                        obj.insert(field.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+        for local_app in local_apps.iter().filter(|a| is_ephemeral(a)) {
+            match apps.iter_mut().find(|a| item_id(a) == item_id(local_app)) {
+                Some(app) => *app = local_app.clone(),
+                None => apps.push(local_app.clone()),
+            }
+        }
+    }
+}
+
+/// Merge `local` and `remote` against their last common ancestor `base`.
+/// Without a base (first sync) every difference is a conflict, except items
+/// that only exist on one side, which are kept.
+pub fn merge_configs(base: Option<&Value>, local: &Value, remote: &Value) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let merged = merge_object(base, local, remote, &[], None, &mut conflicts);
+    MergeResult { merged, conflicts }
+}
+
+fn merge_object(
+    base: Option<&Value>,
+    local: &Value,
+    remote: &Value,
+    path: &[&str],
+    item: Option<(&str, &str)>,
+    conflicts: &mut Vec<SyncConflict>,
+) -> Value {
+    let empty = Map::new();
+    let local_obj = local.as_object().unwrap_or(&empty);
+    let remote_obj = remote.as_object().unwrap_or(&empty);
+    let mut keys: Vec<&String> = local_obj.keys().collect();
+    keys.extend(remote_obj.keys().filter(|k| !local_obj.contains_key(*k)));
+
+    let mut out = Map::new();
+    for key in keys {
+        let mut field_path = path.to_vec();
+        field_path.push(key);
+        let b = base.and_then(|b| b.get(key));
+        let l = local_obj.get(key);
+        let r = remote_obj.get(key);
+
+        if item.is_none() {
+            if let Some((section, _)) = KEYED_LISTS
+                .iter()
+                .find(|(_, p)| *p == field_path.as_slice())
+            {
+                let merged = merge_keyed_list(section, b, l, r, conflicts);
+                out.insert(key.clone(), merged);
+                continue;
+            }
+            // Sections and nested tables are merged field by field
+            let is_table = |v: Option<&Value>| matches!(v, None | Some(Value::Object(_)));
+            if is_table(b) && is_table(l) && is_table(r) {
+                let merged = merge_object(
+                    b,
+                    l.unwrap_or(&Value::Null),
+                    r.unwrap_or(&Value::Null),
+                    &field_path,
+                    None,
+                    conflicts,
+                );
+                out.insert(key.clone(), merged);
+                continue;
+            }
+        }
+
+        if let Some(value) = merge_value(b, l, r, &field_path, item, conflicts) {
+            out.insert(key.clone(), value);
+        }
+    }
+    Value::Object(out)
+}
+
+/// Merge a single field. Returns `None` when the field ends up absent.
+fn merge_value(
+    base: Option<&Value>,
+    local: Option<&Value>,
+    remote: Option<&Value>,
+    path: &[&str],
+    item: Option<(&str, &str)>,
+    conflicts: &mut Vec<SyncConflict>,
+) -> Option<Value> {
+    if local == remote || remote.is_none() {
+        return local.cloned();
+    }
+    if local.is_none() || base == local {
+        return remote.cloned();
+    }
+    if base == remote {
+        return local.cloned();
+    }
+    let (section, item_id, item_name, field) = match item {
+        Some((section, id)) => (
+            section.to_string(),
+            Some(id.to_string()),
+            None,
+            path.last().map(|f| f.to_string()),
+        ),
+        None => (path[0].to_string(), None, None, Some(path[1..].join("."))),
+    };
+    conflicts.push(SyncConflict {
+        id: conflict_id(&section, item_id.as_deref(), field.as_deref()),
+        section,
+        item_id,
+        item_name,
+        field,
+        local: local.cloned().unwrap_or(Value::Null),
+        remote: remote.cloned().unwrap_or(Value::Null),
+    });
+    local.cloned()
+}
+
+fn merge_keyed_list(
+    section: &str,
+    base: Option<&Value>,
+    local: Option<&Value>,
+    remote: Option<&Value>,
+    conflicts: &mut Vec<SyncConflict>,
+) -> Value {
+    let items = |v: Option<&Value>| -> Vec<Value> {
+        v.and_then(Value::as_array).cloned().unwrap_or_default()
+    };
+    let (base, local, remote) = (items(base), items(local), items(remote));
+    let find = |list: &[Value], id: &str| list.iter().find(|v| item_id(v) == Some(id)).cloned();
+
+    // Local order first, then items added remotely in their remote order
+    let mut ids: Vec<String> = Vec::new();
+    for v in local.iter().chain(remote.iter()) {
+        if let Some(id) = item_id(v) {
+            if !ids.iter().any(|i| i == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for id in ids {
+        let b = find(&base, &id);
+        let l = find(&local, &id);
+        let r = find(&remote, &id);
+        match (b, l, r) {
+            (b, Some(l), Some(r)) => {
+                if l == r {
+                    out.push(l);
+                    continue;
+                }
+                let before = conflicts.len();
+                let merged = merge_object(b.as_ref(), &l, &r, &[], Some((section, &id)), conflicts);
+                let name = item_name(&l);
+                for conflict in conflicts[before..].iter_mut() {
+                    conflict.item_name = name.clone();
+                }
+                out.push(merged);
+            }
+            // Added on one side only
+            (None, Some(l), None) => out.push(l),
+            (None, None, Some(r)) => out.push(r),
+            // Deleted remotely: follow unless it was edited locally
+            (Some(b), Some(l), None) => {
+                if l != b {
+                    conflicts.push(item_conflict(section, &id, Some(&l), None));
+                    out.push(l);
+                }
+            }
+            // Deleted locally: stays deleted unless it was edited remotely
+            (Some(b), None, Some(r)) => {
+                if r != b {
+                    conflicts.push(item_conflict(section, &id, None, Some(&r)));
+                }
+            }
+            (_, None, None) => {}
+        }
+    }
+    Value::Array(out)
+}
+
+fn item_conflict(
+    section: &str,
+    id: &str,
+    local: Option<&Value>,
+    remote: Option<&Value>,
+) -> SyncConflict {
+    SyncConflict {
+        id: conflict_id(section, Some(id), None),
+        section: section.to_string(),
+        item_id: Some(id.to_string()),
+        item_name: local.or(remote).and_then(item_name),
+        field: None,
+        local: local.cloned().unwrap_or(Value::Null),
+        remote: remote.cloned().unwrap_or(Value::Null),
+    }
+}
+
+fn conflict_id(section: &str, item_id: Option<&str>, field: Option<&str>) -> String {
+    [Some(section), item_id, field]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Write `value` to the location `conflict` refers to. A `null` value for a
+/// whole-item conflict removes the item.
+pub fn apply_resolution(
+    config: &mut Value,
+    conflict: &SyncConflict,
+    value: &Value,
+) -> Result<(), String> {
+    let Some(item_id) = conflict.item_id.as_deref() else {
+        let field = conflict.field.as_deref().ok_or("Conflict has no field")?;
+        let mut path: Vec<&str> = vec![conflict.section.as_str()];
+        path.extend(field.split('.'));
+        let (last, parents) = path.split_last().expect("non-empty path");
+        let obj = get_path_mut(config, parents)
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| format!("Section '{}' not found", conflict.section))?;
+        obj.insert(last.to_string(), value.clone());
+        return Ok(());
+    };
+
+    let (_, list_path) = KEYED_LISTS
+        .iter()
+        .find(|(section, _)| *section == conflict.section)
+        .ok_or_else(|| format!("Unknown section '{}'", conflict.section))?;
+    let list = get_path_mut(config, list_path)
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| format!("Section '{}' not found", conflict.section))?;
+    let pos = list.iter().position(|v| item_id_matches(v, item_id));
+
+    match (&conflict.field, pos) {
+        (Some(field), Some(idx)) => {
+            if let Some(obj) = list[idx].as_object_mut() {
+                obj.insert(field.clone(), value.clone());
+            }
+        }
+        (Some(_), None) => return Err(format!("'{}' no longer exists", item_id)),
+        (None, Some(idx)) if value.is_null() => {
+            list.remove(idx);
+        }
+        (None, Some(idx)) => list[idx] = value.clone(),
+        (None, None) if !value.is_null() => list.push(value.clone()),
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+fn item_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+fn item_id_matches(value: &Value, id: &str) -> bool {
+    item_id(value) == Some(id)
+}
+
+fn item_name(value: &Value) -> Option<String> {
+    value.get("name").and_then(Value::as_str).map(String::from)
+}
+
+fn get_path<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |v, key| v.get(*key))
+}
+
+fn get_path_mut<'a>(value: &'a mut Value, path: &[&str]) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, |v, key| v.get_mut(*key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(apps: Vec<Value>) -> Value {
+        json!({
+            "general": { "theme": "system", "tray_mode": true, "window_state": { "x": 0.0 } },
+            "workspaces": { "active": "default", "items": [{ "id": "default", "name": "Default", "app_ids": [] }] },
+            "apps": apps,
+        })
+    }
+
+    #[test]
+    fn test_non_overlapping_changes_merge() {
+        let base = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "b", "name": "B", "url": "https://b", "muted": false }),
+        ]);
+        let mut local = base.clone();
+        local["apps"][0]["url"] = json!("https://a.local");
+        local["general"]["tray_mode"] = json!(false);
+        let mut remote = base.clone();
+        remote["apps"][0]["muted"] = json!(true);
+        remote["apps"][1]["url"] = json!("https://b.remote");
+        remote["general"]["theme"] = json!("dark");
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert!(result.conflicts.is_empty(), "{:?}", result.conflicts);
+        let merged = result.merged;
+        assert_eq!(merged["apps"][0]["url"], "https://a.local");
+        assert_eq!(merged["apps"][0]["muted"], true);
+        assert_eq!(merged["apps"][1]["url"], "https://b.remote");
+        assert_eq!(merged["general"]["theme"], "dark");
+        assert_eq!(merged["general"]["tray_mode"], false);
+    }
+
+    #[test]
+    fn test_same_field_changed_on_both_sides_conflicts() {
+        let base = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+        ]);
+        let mut local = base.clone();
+        local["apps"][0]["url"] = json!("https://local");
+        let mut remote = base.clone();
+        remote["apps"][0]["url"] = json!("https://remote");
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert_eq!(result.merged["apps"][0]["url"], "https://local");
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.id, "apps.a.url");
+        assert_eq!(conflict.item_name.as_deref(), Some("A"));
+        assert_eq!(conflict.remote, "https://remote");
+    }
+
+    #[test]
+    fn test_added_and_deleted_apps() {
+        let base = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "b", "name": "B", "url": "https://b", "muted": false }),
+        ]);
+        // Local deleted b and added c; remote added d
+        let local = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "c", "name": "C", "url": "https://c", "muted": false }),
+        ]);
+        let remote = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "b", "name": "B", "url": "https://b", "muted": false }),
+            json!({ "id": "d", "name": "D", "url": "https://d", "muted": false }),
+        ]);
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert!(result.conflicts.is_empty());
+        let ids: Vec<_> = result.merged["apps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids, vec!["a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_delete_vs_edit_conflicts() {
+        let base = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+        ]);
+        let local = config(vec![]);
+        let mut remote = base.clone();
+        remote["apps"][0]["url"] = json!("https://edited");
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert_eq!(result.merged["apps"], json!([]));
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].field, None);
+        assert_eq!(result.conflicts[0].local, Value::Null);
+
+        let mut resolved = result.merged.clone();
+        apply_resolution(
+            &mut resolved,
+            &result.conflicts[0],
+            &result.conflicts[0].remote,
+        )
+        .unwrap();
+        assert_eq!(resolved["apps"][0]["url"], "https://edited");
+    }
+
+    #[test]
+    fn test_workspaces_merge_by_id() {
+        let base = config(vec![]);
+        let mut local = base.clone();
+        local["workspaces"]["items"][0]["name"] = json!("Home");
+        let mut remote = base.clone();
+        remote["workspaces"]["items"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "id": "work", "name": "Work", "app_ids": [] }));
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.merged["workspaces"]["items"][0]["name"], "Home");
+        assert_eq!(result.merged["workspaces"]["items"][1]["id"], "work");
+    }
+
+    #[test]
+    fn test_section_conflict_resolution() {
+        let base = config(vec![]);
+        let mut local = base.clone();
+        local["general"]["theme"] = json!("light");
+        let mut remote = base.clone();
+        remote["general"]["theme"] = json!("dark");
+
+        let result = merge_configs(Some(&base), &local, &remote);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].id, "general.theme");
+
+        let mut resolved = result.merged.clone();
+        apply_resolution(&mut resolved, &result.conflicts[0], &json!("dark")).unwrap();
+        assert_eq!(resolved["general"]["theme"], "dark");
+    }
+
+    #[test]
+    fn test_machine_local_fields_never_synced() {
+        let mut local = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+        ]);
+        local["apps"][0]["download_directory"] = json!("/home/me/Downloads");
+        local["apps"][0]["data_store_uuid"] = json!("local-uuid");
+        local["sync"] = json!({ "enabled": true });
+
+        let mut payload = local.clone();
+        strip_machine_local(&mut payload);
+        assert!(payload.get("sync").is_none());
+        assert!(payload["general"].get("window_state").is_none());
+        assert!(payload["workspaces"].get("active").is_none());
+        assert!(payload["apps"][0].get("download_directory").is_none());
+        assert!(payload["apps"][0].get("data_store_uuid").is_none());
+
+        let mut remote = payload.clone();
+        remote["apps"][0]["url"] = json!("https://remote");
+        let mut merged = merge_configs(Some(&payload), &payload, &remote).merged;
+        restore_machine_local(&mut merged, &local);
+        assert_eq!(merged["apps"][0]["url"], "https://remote");
+        assert_eq!(
+            merged["apps"][0]["download_directory"],
+            "/home/me/Downloads"
+        );
+        assert_eq!(merged["apps"][0]["data_store_uuid"], "local-uuid");
+        assert_eq!(
+            merged["general"]["window_state"],
+            local["general"]["window_state"]
+        );
+        assert_eq!(merged["sync"], local["sync"]);
+    }
+
+    #[test]
+    fn test_ephemeral_apps_never_synced() {
+        let mut local = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "b", "name": "B", "url": "https://b", "muted": false }),
+        ]);
+        local["apps"][1]["ephemeral"] = json!(true);
+
+        let mut payload = local.clone();
+        strip_machine_local(&mut payload);
+        assert_eq!(payload["apps"].as_array().unwrap().len(), 1);
+
+        // A remote copy from before the app became ephemeral is overridden
+        let remote = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+            json!({ "id": "b", "name": "B", "url": "https://b.remote", "muted": false }),
+        ]);
+        let mut merged = merge_configs(Some(&payload), &payload, &remote).merged;
+        restore_machine_local(&mut merged, &local);
+        assert_eq!(merged["apps"][1]["url"], "https://b");
+        assert_eq!(merged["apps"][1]["ephemeral"], true);
+
+        let mut merged = merge_configs(Some(&payload), &payload, &payload).merged;
+        restore_machine_local(&mut merged, &local);
+        assert_eq!(merged["apps"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_first_sync_without_base() {
+        let local = config(vec![
+            json!({ "id": "a", "name": "A", "url": "https://a", "muted": false }),
+        ]);
+        let mut remote = config(vec![
+            json!({ "id": "b", "name": "B", "url": "https://b", "muted": false }),
+        ]);
+        remote["general"]["theme"] = json!("dark");
+
+        let result = merge_configs(None, &local, &remote);
+        assert_eq!(result.merged["apps"].as_array().unwrap().len(), 2);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].id, "general.theme");
+    }
+}
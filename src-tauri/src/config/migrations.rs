@@ -0,0 +1,251 @@
+//! Versioned migrations for `config.toml`.
+//!
+//! Migrations operate on the raw `toml::Value` before it is deserialized into
+//! `OrblyConfig`, so a step can rename, move or reshape fields that the current
+//! structs no longer know about. Each step upgrades the config from exactly one
+//! version to the next; steps are applied in order until the config reaches
+//! `CURRENT_CONFIG_VERSION`.
+
+/// The config version written by this build of Orbly.
+pub const CURRENT_CONFIG_VERSION: u32 = 1;
+
+/// A single upgrade step from `from` to `from + 1`.
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut toml::Value) -> Result<(), String>,
+}
+
+/// Ordered registry of migration steps. Every version below
+/// `CURRENT_CONFIG_VERSION` must have exactly one entry.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "initial versioned config",
+    apply: migrate_v0_to_v1,
+}];
+
+#[derive(Debug, PartialEq)]
+pub enum MigrationError {
+    /// The config was written by a newer Orbly than this one.
+    NewerVersion(u32),
+    /// No step is registered for this version.
+    MissingStep(u32),
+    /// The pre-migration backup could not be written.
+    BackupFailed { from: u32, message: String },
+    /// A step failed to apply.
+    StepFailed { from: u32, message: String },
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::NewerVersion(v) => write!(
+                f,
+                "config version {} is newer than supported version {}",
+                v, CURRENT_CONFIG_VERSION
+            ),
+            MigrationError::MissingStep(v) => {
+                write!(f, "no migration registered for config version {}", v)
+            }
+            MigrationError::BackupFailed { from, message } => {
+                write!(
+                    f,
+                    "backup before migrating version {} failed: {}",
+                    from, message
+                )
+            }
+            MigrationError::StepFailed { from, message } => {
+                write!(f, "migration from version {} failed: {}", from, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Read `general.config_version` from a raw config. A missing version is
+/// treated as 1, matching the serde default on `GeneralConfig`.
+pub fn config_version(value: &toml::Value) -> u32 {
+    value
+        .get("general")
+        .and_then(|g| g.get("config_version"))
+        .and_then(|v| v.as_integer())
+        .map(|v| v.max(0) as u32)
+        .unwrap_or(1)
+}
+
+fn set_config_version(value: &mut toml::Value, version: u32) -> Result<(), String> {
+    let root = value.as_table_mut().ok_or("config root is not a table")?;
+    let general = root
+        .entry("general")
+        .or_insert_with(|| toml::Value::Table(toml::map::Map::new()))
+        .as_table_mut()
+        .ok_or("[general] is not a table")?;
+    general.insert(
+        "config_version".to_string(),
+        toml::Value::Integer(version as i64),
+    );
+    Ok(())
+}
+
+/// Returns the steps needed to bring a config at `version` up to date.
+pub fn pending_migrations(version: u32) -> Result<Vec<&'static Migration>, MigrationError> {
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(MigrationError::NewerVersion(version));
+    }
+    (version..CURRENT_CONFIG_VERSION)
+        .map(|v| {
+            MIGRATIONS
+                .iter()
+                .find(|m| m.from == v)
+                .ok_or(MigrationError::MissingStep(v))
+        })
+        .collect()
+}
+
+/// Apply all pending migrations to `value`. `before_step` is called with the
+/// version and the not-yet-migrated value ahead of each step so the caller can
+/// back it up; an error from it aborts the migration. Returns the versions that
+/// were migrated from.
+pub fn migrate<F>(value: &mut toml::Value, mut before_step: F) -> Result<Vec<u32>, MigrationError>
+where
+    F: FnMut(u32, &toml::Value) -> Result<(), String>,
+{
+    let steps = pending_migrations(config_version(value))?;
+    let mut applied = Vec::new();
+    for step in steps {
+        before_step(step.from, value).map_err(|message| MigrationError::BackupFailed {
+            from: step.from,
+            message,
+        })?;
+        (step.apply)(value).map_err(|message| MigrationError::StepFailed {
+            from: step.from,
+            message,
+        })?;
+        set_config_version(value, step.from + 1).map_err(|message| MigrationError::StepFailed {
+            from: step.from,
+            message,
+        })?;
+        log::info!(
+            "Migrated config v{} → v{}: {}",
+            step.from,
+            step.from + 1,
+            step.description
+        );
+        applied.push(step.from);
+    }
+    Ok(applied)
+}
+
+/// Convert a JSON config (e.g. from a sync backend) into a TOML value so it can
+/// go through the same migrations. TOML has no null, so null fields are dropped
+/// and fall back to their serde defaults.
+pub fn json_to_toml(json: serde_json::Value) -> Result<toml::Value, String> {
+    fn strip_nulls(value: serde_json::Value) -> Option<serde_json::Value> {
+        match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::Object(map) => Some(serde_json::Value::Object(
+                map.into_iter()
+                    .filter_map(|(k, v)| strip_nulls(v).map(|v| (k, v)))
+                    .collect(),
+            )),
+            serde_json::Value::Array(items) => Some(serde_json::Value::Array(
+                items.into_iter().filter_map(strip_nulls).collect(),
+            )),
+            other => Some(other),
+        }
+    }
+    let stripped = strip_nulls(json).unwrap_or(serde_json::Value::Object(Default::default()));
+    toml::Value::try_from(stripped).map_err(|e| e.to_string())
+}
+
+/// v0 → v1: the first versioned format. No structural changes; only the
+/// version number is introduced.
+fn migrate_v0_to_v1(_value: &mut toml::Value) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> toml::Value {
+        toml::from_str(s).expect("parse")
+    }
+
+    #[test]
+    fn test_registry_covers_every_version() {
+        for v in 0..CURRENT_CONFIG_VERSION {
+            assert_eq!(MIGRATIONS.iter().filter(|m| m.from == v).count(), 1);
+        }
+        assert!(MIGRATIONS.iter().all(|m| m.from < CURRENT_CONFIG_VERSION));
+    }
+
+    #[test]
+    fn test_missing_version_defaults_to_1() {
+        assert_eq!(config_version(&parse("[general]\ntheme = \"dark\"\n")), 1);
+        assert_eq!(config_version(&parse("")), 1);
+    }
+
+    #[test]
+    fn test_migrate_v0_to_v1_step() {
+        let mut value = parse("[general]\nconfig_version = 0\ntray_mode = false\n");
+        migrate_v0_to_v1(&mut value).expect("step");
+        assert_eq!(value["general"]["tray_mode"].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn test_migrate_runs_steps_and_bumps_version() {
+        let mut value = parse("[general]\nconfig_version = 0\n");
+        let mut backed_up = Vec::new();
+        let applied = migrate(&mut value, |v, _| {
+            backed_up.push(v);
+            Ok(())
+        })
+        .expect("migrate");
+        assert_eq!(applied, (0..CURRENT_CONFIG_VERSION).collect::<Vec<_>>());
+        assert_eq!(backed_up, applied);
+        assert_eq!(config_version(&value), CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn test_current_version_is_noop() {
+        let src = format!("[general]\nconfig_version = {}\n", CURRENT_CONFIG_VERSION);
+        let mut value = parse(&src);
+        let applied = migrate(&mut value, |_, _| panic!("no backup expected")).expect("migrate");
+        assert!(applied.is_empty());
+        assert_eq!(value, parse(&src));
+    }
+
+    #[test]
+    fn test_newer_version_rejected() {
+        let mut value = parse("[general]\nconfig_version = 99\n");
+        assert_eq!(
+            migrate(&mut value, |_, _| Ok(())),
+            Err(MigrationError::NewerVersion(99))
+        );
+        assert_eq!(config_version(&value), 99);
+    }
+
+    #[test]
+    fn test_backup_failure_aborts() {
+        let mut value = parse("[general]\nconfig_version = 0\n");
+        let result = migrate(&mut value, |_, _| Err("disk full".to_string()));
+        assert!(matches!(
+            result,
+            Err(MigrationError::BackupFailed { from: 0, .. })
+        ));
+        assert_eq!(config_version(&value), 0);
+    }
+
+    #[test]
+    fn test_json_to_toml_drops_nulls() {
+        let json = serde_json::json!({
+            "general": { "config_version": 1, "scrollbar_color": null },
+            "apps": [{ "id": "a", "skip_download_dialog": null }]
+        });
+        let value = json_to_toml(json).expect("convert");
+        assert!(value["general"].get("scrollbar_color").is_none());
+        assert_eq!(value["apps"][0]["id"].as_str(), Some("a"));
+    }
+}
@@ -1,4 +1,13 @@
+pub mod bundle;
 pub mod defaults;
 pub mod icloud;
 pub mod manager;
+pub mod merge;
+pub mod migrations;
 pub mod models;
+pub mod policy;
+pub mod secrets;
+pub mod snapshots;
+pub mod sync;
+pub mod validation;
+pub mod watcher;
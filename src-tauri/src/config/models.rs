@@ -48,6 +48,10 @@ pub struct AppConfig {
     pub custom_js: String,
     #[serde(default)]
     pub proxy: String,
+    /// Secret handle for the proxy username and password, which are kept out
+    /// of the proxy URL.
+    #[serde(default)]
+    pub proxy_credentials: String,
     #[serde(default = "default_dark_mode")]
     pub dark_mode: DarkModeType,
     #[serde(default = "default_100")]
@@ -84,6 +88,125 @@ pub struct AppConfig {
     pub suppress_high_usage_alert: bool,
     #[serde(default)]
     pub suppress_hibernate_confirm: bool,
+    #[serde(default = "default_crash_recovery")]
+    pub crash_recovery: CrashRecovery,
+    /// Never hibernated to keep within the memory budget
+    #[serde(default)]
+    pub pinned: bool,
+    /// Overrides the schedule of the app's workspace
+    #[serde(default)]
+    pub schedule: Option<HibernationSchedule>,
+    /// Keep text typed into the page's form fields across hibernation and
+    /// crashes. Stored unencrypted in `session_state.json`.
+    #[serde(default)]
+    pub restore_form_fields: bool,
+    /// Further sign-ins to the same service. The app's own data store and
+    /// notification style make up its default account.
+    #[serde(default)]
+    pub accounts: Vec<AppAccount>,
+    /// Name shown for the default account
+    #[serde(default)]
+    pub account_name: String,
+    /// Id of the account the app is signed in with; empty for the default
+    #[serde(default)]
+    pub active_account: String,
+    /// Shown in a window of its own instead of the main window
+    #[serde(default)]
+    pub detached: bool,
+    /// Where the app's own window was last placed
+    #[serde(default)]
+    pub popout_window: WindowState,
+    /// Runs on a throwaway data store, wiped whenever the webview goes away.
+    /// Never recovered after a crash nor synced.
+    #[serde(default)]
+    pub ephemeral: bool,
+    /// Whether the app starts active or hibernated when Orbly launches
+    #[serde(default = "default_launch_policy")]
+    pub launch: LaunchPolicy,
+    /// How long a `Delayed` app waits after launch before loading
+    #[serde(default = "default_15")]
+    pub launch_delay_secs: u32,
+    /// URLs of the service's sign-in pages, e.g. `accounts.google.com/*` or
+    /// `/login`. Landing on one marks the app signed out.
+    #[serde(default)]
+    pub login_url_patterns: Vec<String>,
+}
+
+impl AppConfig {
+    /// The name of the default account.
+    pub fn default_account_name(&self) -> &str {
+        if self.account_name.trim().is_empty() {
+            "Default"
+        } else {
+            &self.account_name
+        }
+    }
+
+    /// The extra account in use, or `None` for the default account.
+    pub fn current_account(&self) -> Option<&AppAccount> {
+        self.accounts.iter().find(|a| a.id == self.active_account)
+    }
+
+    /// The data store of the account in use.
+    pub fn active_data_store(&self) -> Uuid {
+        self.current_account()
+            .map(|a| a.data_store_uuid)
+            .unwrap_or(self.data_store_uuid)
+    }
+
+    /// Whether the app starts hibernated when Orbly launches.
+    pub fn starts_hibernated(&self) -> bool {
+        match self.launch {
+            LaunchPolicy::Restore => self.hibernated,
+            LaunchPolicy::Active | LaunchPolicy::Delayed => false,
+            LaunchPolicy::Hibernated => true,
+        }
+    }
+
+    /// The notification style of the account in use.
+    pub fn active_notification_style(&self) -> NotificationStyle {
+        self.current_account()
+            .map(|a| a.notification_style.clone())
+            .unwrap_or_else(|| self.notification_style.clone())
+    }
+
+    /// An app with default settings at `https://<id>.example.com` and a data
+    /// store of its own, for tests.
+    #[cfg(test)]
+    pub(crate) fn test_app(id: &str) -> Self {
+        let json = serde_json::json!({
+            "id": id,
+            "name": id,
+            "url": format!("https://{id}.example.com"),
+            "data_store_uuid": Uuid::new_v4(),
+        });
+        serde_json::from_value(json).expect("test app")
+    }
+}
+
+/// Another sign-in to an app's service, with a data store of its own.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AppAccount {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub data_store_uuid: Uuid,
+    #[serde(default = "default_notification_style")]
+    pub notification_style: NotificationStyle,
+}
+
+/// Times an app is woken and hibernated on its own. These are boundaries,
+/// not a window: at `wake_at` on each listed day the app is woken, at
+/// `hibernate_at` it is hibernated, and in between it is left as it is, so
+/// waking or hibernating it by hand lasts until the next boundary.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HibernationSchedule {
+    #[serde(default = "default_schedule_wake_at")]
+    pub wake_at: String,
+    #[serde(default = "default_schedule_hibernate_at")]
+    pub hibernate_at: String,
+    #[serde(default = "default_dnd_days")]
+    pub days: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -103,6 +226,31 @@ pub enum NotificationStyle {
     Off,
 }
 
+/// What to do when an app's webview crashes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashRecovery {
+    /// Reload at once, backing off if it keeps crashing
+    Reload,
+    /// Reload after a longer pause
+    Delayed,
+    /// Leave it crashed until the user reloads it
+    Off,
+}
+
+/// How an app starts when Orbly launches. Separate from `hibernated`, which
+/// follows the app's state while Orbly runs.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchPolicy {
+    /// As it was when Orbly last quit
+    Restore,
+    Active,
+    Hibernated,
+    /// Active after `launch_delay_secs`
+    Delayed,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ThemeMode {
@@ -147,12 +295,23 @@ pub struct GeneralConfig {
     pub local_scripts_only: bool,
     #[serde(default = "default_cpu_alert_threshold")]
     pub cpu_alert_threshold: f64,
+    /// Memory for the whole process tree, in MB, above which the least
+    /// recently used apps are hibernated. 0 means no budget.
+    #[serde(default)]
+    pub memory_budget_mb: u32,
     #[serde(default = "default_recipe_cache_ttl_hours")]
     pub recipe_cache_ttl_hours: u64,
     #[serde(default = "default_true")]
     pub sidebar_hover_expand: bool,
     #[serde(default)]
     pub recipe_manifest_url: Option<String>,
+    /// Apps loading at once while Orbly starts up
+    #[serde(default = "default_startup_concurrency")]
+    pub startup_concurrency: u32,
+    /// Load apps that are not pinned, badged or recently used only once
+    /// Orbly is idle after startup
+    #[serde(default)]
+    pub defer_idle_apps: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -221,6 +380,9 @@ pub struct Workspace {
     pub tiling_layout: String,
     #[serde(default)]
     pub tile_assignments: Vec<String>,
+    /// Applies to member apps that have no schedule of their own
+    #[serde(default)]
+    pub schedule: Option<HibernationSchedule>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -233,10 +395,32 @@ pub struct DownloadGlobalConfig {
     pub skip_download_dialog_default: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct SyncConfig {
     #[serde(default)]
     pub enabled: bool,
+    #[serde(default)]
+    pub backend: SyncBackendKind,
+    /// Directory used by the folder backend (e.g. a Syncthing or Nextcloud folder).
+    #[serde(default)]
+    pub folder_path: String,
+    /// WebDAV collection URL the sync file is stored in.
+    #[serde(default)]
+    pub webdav_url: String,
+    #[serde(default)]
+    pub webdav_username: String,
+    /// Secret handle once saved; a plaintext value is moved to the keyring.
+    #[serde(default)]
+    pub webdav_password: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackendKind {
+    #[default]
+    ICloud,
+    Folder,
+    WebDav,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
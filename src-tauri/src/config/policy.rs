@@ -0,0 +1,378 @@
+//! Administrator policy for managed deployments.
+//!
+//! A read-only `policy.toml` in a system location pins settings and limits
+//! which services can be added. Settings under `[settings]` use the same
+//! shape as `config.toml` and are applied over the user's config; every
+//! field they set is locked. `[apps]` holds URL patterns (`*` wildcards,
+//! matched against the full URL) for services that may or may not be used:
+//!
+//! ```toml
+//! [settings.general]
+//! developer_mode = false
+//! local_scripts_only = true
+//!
+//! [settings.adblock]
+//! enabled = true
+//!
+//! [apps]
+//! allow = ["https://*.slack.com/*", "https://mail.example.com/*"]
+//! deny = ["https://*.discord.com/*"]
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::models::OrblyConfig;
+use crate::utils::wildcard_match;
+
+/// Set to use a policy file other than the system one.
+const POLICY_PATH_ENV: &str = "ORBLY_POLICY_PATH";
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    settings: Option<toml::Table>,
+    #[serde(default)]
+    apps: AppRules,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AppRules {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// What the frontend needs to show which settings are managed.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct PolicyInfo {
+    pub path: Option<String>,
+    /// Locked fields as dotted paths, e.g. `general.developer_mode`.
+    pub locked: Vec<String>,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// The user's own values of the fields a policy locks, taken from the config
+/// before the policy was applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UserValues(Vec<(Vec<String>, Value)>);
+
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    path: Option<PathBuf>,
+    /// Locked field paths and the values they are held at.
+    locked: Vec<(Vec<String>, Value)>,
+    apps: AppRules,
+}
+
+impl Policy {
+    /// The policy file for this platform.
+    pub fn system_path() -> PathBuf {
+        if let Some(path) = std::env::var_os(POLICY_PATH_ENV) {
+            return PathBuf::from(path);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            PathBuf::from("/Library/Application Support/Orbly/policy.toml")
+        }
+        #[cfg(target_os = "windows")]
+        {
+            std::env::var_os("ProgramData")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+                .join("Orbly")
+                .join("policy.toml")
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            PathBuf::from("/etc/orbly/policy.toml")
+        }
+    }
+
+    /// Load the policy at `path`. A missing file means no policy.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents, Some(path.to_path_buf()))
+                .map_err(|e| format!("Invalid policy {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read policy {}: {}", path.display(), e)),
+        }
+    }
+
+    fn parse(contents: &str, path: Option<PathBuf>) -> Result<Self, String> {
+        let file: PolicyFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let mut leaves = Vec::new();
+        if let Some(settings) = file.settings {
+            let value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+            collect_leaves(&mut Vec::new(), &value, &mut leaves);
+        }
+
+        // Apply to the default config to reject unknown fields and wrong
+        // types, and to hold each value as the config itself serializes it
+        let mut base = serde_json::to_value(OrblyConfig::default()).map_err(|e| e.to_string())?;
+        for (path, value) in &leaves {
+            let slot = field_mut(&mut base, path)
+                .ok_or_else(|| format!("unknown setting '{}'", path.join(".")))?;
+            *slot = value.clone();
+        }
+        let config: OrblyConfig = serde_json::from_value(base).map_err(|e| e.to_string())?;
+        let normalized = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+        let locked = leaves
+            .into_iter()
+            .map(|(path, _)| {
+                let value = field(&normalized, &path).cloned().unwrap_or(Value::Null);
+                (path, value)
+            })
+            .collect();
+
+        Ok(Self {
+            path,
+            locked,
+            apps: file.apps,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locked.is_empty() && self.apps == AppRules::default()
+    }
+
+    pub fn info(&self) -> PolicyInfo {
+        PolicyInfo {
+            path: self.path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            locked: self.locked.iter().map(|(path, _)| path.join(".")).collect(),
+            allow: self.apps.allow.clone(),
+            deny: self.apps.deny.clone(),
+        }
+    }
+
+    /// Set every locked field of `config` to its policy value. Returns
+    /// whether anything changed.
+    pub fn apply(&self, config: &mut OrblyConfig) -> Result<bool, String> {
+        if self.locked.is_empty() {
+            return Ok(false);
+        }
+        let mut value = serde_json::to_value(&*config).map_err(|e| e.to_string())?;
+        let mut changed = false;
+        for (path, locked) in &self.locked {
+            if let Some(slot) = field_mut(&mut value, path) {
+                if slot != locked {
+                    *slot = locked.clone();
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            *config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        }
+        Ok(changed)
+    }
+
+    /// The values `config` holds in the fields the policy locks.
+    pub fn user_values(&self, config: &OrblyConfig) -> Result<UserValues, String> {
+        if self.locked.is_empty() {
+            return Ok(UserValues::default());
+        }
+        let value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+        Ok(UserValues(
+            self.locked
+                .iter()
+                .filter_map(|(path, _)| Some((path.clone(), field(&value, path)?.clone())))
+                .collect(),
+        ))
+    }
+
+    /// `config` with `user`'s values back in the locked fields: the config as
+    /// written to `config.toml` and synced, so the policy never overwrites
+    /// the user's own settings.
+    pub fn unapply(&self, config: &OrblyConfig, user: &UserValues) -> Result<OrblyConfig, String> {
+        if user.0.is_empty() {
+            return Ok(config.clone());
+        }
+        let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+        for (path, own) in &user.0 {
+            if let Some(slot) = field_mut(&mut value, path) {
+                *slot = own.clone();
+            }
+        }
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// Fail if `config` changes a locked field or adds an app (or points one
+    /// at a URL) the policy does not allow. Apps that were already there are
+    /// left alone; they are stopped from loading instead.
+    pub fn check(&self, old: &OrblyConfig, config: &OrblyConfig) -> Result<(), String> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        if !self.locked.is_empty() {
+            let value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+            for (path, locked) in &self.locked {
+                if field(&value, path) != Some(locked) {
+                    return Err(format!(
+                        "'{}' is locked by your administrator's policy ({})",
+                        path.join("."),
+                        self.source()
+                    ));
+                }
+            }
+        }
+        for app in &config.apps {
+            let unchanged = old.apps.iter().any(|a| a.id == app.id && a.url == app.url);
+            if !unchanged {
+                self.check_url(&app.url)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail if the policy does not allow apps at `url`.
+    pub fn check_url(&self, url: &str) -> Result<(), String> {
+        if self.allows_url(url) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} is not allowed by your administrator's policy ({})",
+                url,
+                self.source()
+            ))
+        }
+    }
+
+    pub fn allows_url(&self, url: &str) -> bool {
+        // Match the normalized form so `https://example.com` matches
+        // `https://example.com/*`
+        let url = url::Url::parse(url.trim())
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| url.trim().to_string());
+        if self.apps.deny.iter().any(|p| wildcard_match(p, &url)) {
+            return false;
+        }
+        self.apps.allow.is_empty() || self.apps.allow.iter().any(|p| wildcard_match(p, &url))
+    }
+
+    fn source(&self) -> String {
+        self.path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "policy.toml".to_string())
+    }
+}
+
+fn collect_leaves(prefix: &mut Vec<String>, value: &Value, out: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                prefix.push(key.clone());
+                collect_leaves(prefix, child, out);
+                prefix.pop();
+            }
+        }
+        _ => out.push((prefix.clone(), value.clone())),
+    }
+}
+
+fn field<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |v, key| v.as_object()?.get(key))
+}
+
+fn field_mut<'a>(value: &'a mut Value, path: &[String]) -> Option<&'a mut Value> {
+    path.iter()
+        .try_fold(value, |v, key| v.as_object_mut()?.get_mut(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+[settings.general]
+developer_mode = false
+local_scripts_only = true
+recipe_manifest_url = "https://recipes.example.com/manifest.json"
+
+[settings.adblock]
+enabled = true
+
+[apps]
+allow = ["https://*.slack.com/*", "https://mail.example.com/*"]
+deny = ["https://evil.slack.com/*"]
+"#;
+
+    #[test]
+    fn test_apply_and_check_locked_fields() {
+        let policy = Policy::parse(POLICY, None).expect("policy");
+        assert_eq!(
+            policy.info().locked,
+            vec![
+                "adblock.enabled",
+                "general.developer_mode",
+                "general.local_scripts_only",
+                "general.recipe_manifest_url"
+            ]
+        );
+
+        let mut config = OrblyConfig::default();
+        config.general.developer_mode = true;
+        config.adblock.enabled = false;
+        let own = config.clone();
+        let user = policy.user_values(&config).unwrap();
+        assert!(policy.apply(&mut config).unwrap());
+        assert!(!config.general.developer_mode);
+        assert!(config.adblock.enabled);
+        assert!(config.general.local_scripts_only);
+        assert!(!policy.apply(&mut config).unwrap());
+        // What is written to disk keeps the user's values
+        let on_disk = policy.unapply(&config, &user).unwrap();
+        assert_eq!(on_disk.general.developer_mode, own.general.developer_mode);
+        assert_eq!(on_disk.adblock.enabled, own.adblock.enabled);
+
+        let old = config.clone();
+        assert!(policy.check(&old, &config).is_ok());
+        config.general.developer_mode = true;
+        let err = policy.check(&old, &config).unwrap_err();
+        assert!(err.contains("general.developer_mode"), "{err}");
+    }
+
+    #[test]
+    fn test_app_allow_and_deny_lists() {
+        let policy = Policy::parse(POLICY, None).expect("policy");
+        assert!(policy.allows_url("https://acme.slack.com"));
+        assert!(policy.allows_url("https://mail.example.com/inbox"));
+        assert!(!policy.allows_url("https://evil.slack.com/"));
+        assert!(!policy.allows_url("https://web.whatsapp.com"));
+
+        let mut old: OrblyConfig = toml::from_str(
+            r#"
+[[apps]]
+id = "chat"
+name = "Chat"
+url = "https://web.whatsapp.com"
+"#,
+        )
+        .unwrap();
+        policy.apply(&mut old).unwrap();
+        // An app that was already there is not a new violation
+        let mut config = old.clone();
+        config.apps[0].name = "Renamed".to_string();
+        assert!(policy.check(&old, &config).is_ok());
+        config.apps[0].url = "https://discord.com/app".to_string();
+        assert!(policy.check(&old, &config).is_err());
+    }
+
+    #[test]
+    fn test_unknown_settings_are_rejected() {
+        let err = Policy::parse("[settings.general]\nno_such_field = true\n", None).unwrap_err();
+        assert!(err.contains("general.no_such_field"), "{err}");
+        assert!(Policy::parse("[settings.general]\ndeveloper_mode = \"yes\"\n", None).is_err());
+        assert!(Policy::parse("[apps]\nallowed = []\n", None).is_err());
+        assert!(Policy::parse("", None).unwrap().is_empty());
+    }
+}
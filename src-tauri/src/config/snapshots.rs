@@ -0,0 +1,337 @@
+//! Rotating history of `config.toml`.
+//!
+//! Every save records the resulting config together with the reason for it
+//! (usually the command that made the change), and risky operations such as
+//! imports and migrations record the config as it was beforehand. Snapshots
+//! live in `snapshots/` under the app data dir: one TOML file each, plus an
+//! `index.json` with their metadata.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Snapshots kept regardless of age.
+const MIN_SNAPSHOTS: usize = 10;
+/// Hard cap on the number of snapshots.
+const MAX_SNAPSHOTS: usize = 50;
+/// Snapshots beyond `MIN_SNAPSHOTS` older than this are pruned.
+const MAX_AGE_DAYS: i64 = 30;
+/// Saves with the same reason this close together update one snapshot
+/// rather than filling the history (e.g. dragging a zoom slider).
+const COALESCE_SECS: i64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub created_at: String,
+    pub reason: String,
+}
+
+/// One changed field between two configs.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    /// Field path in the style of validation issues, e.g. `apps[slack].url`.
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+pub struct SnapshotStore {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl SnapshotStore {
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self {
+            dir: app_data_dir.join("snapshots"),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Snapshots, newest first.
+    pub fn list(&self) -> Vec<SnapshotInfo> {
+        let _guard = self.lock.lock().expect("snapshot lock");
+        let mut index = self.read_index();
+        index.reverse();
+        index
+    }
+
+    /// Record `contents` (config TOML) with `reason`. Identical consecutive
+    /// contents are not recorded twice.
+    pub fn record(&self, reason: &str, contents: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().expect("snapshot lock");
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let mut index = self.read_index();
+        let now = Utc::now();
+
+        if let Some(last) = index.last() {
+            if fs::read_to_string(self.snapshot_path(&last.id))
+                .ok()
+                .as_deref()
+                == Some(contents)
+            {
+                return Ok(());
+            }
+        }
+        let coalesce = index.last().is_some_and(|last| {
+            last.reason == reason
+                && DateTime::parse_from_rfc3339(&last.created_at)
+                    .map(|t| (now - t.with_timezone(&Utc)).num_seconds() < COALESCE_SECS)
+                    .unwrap_or(false)
+        });
+        if coalesce {
+            index.pop();
+        }
+
+        let mut id = now.format("%Y%m%dT%H%M%S%3fZ").to_string();
+        let mut n = 1;
+        while index.iter().any(|s| s.id == id) {
+            n += 1;
+            id = format!("{}-{}", now.format("%Y%m%dT%H%M%S%3fZ"), n);
+        }
+        fs::write(self.snapshot_path(&id), contents).map_err(|e| e.to_string())?;
+        index.push(SnapshotInfo {
+            id,
+            created_at: now.to_rfc3339(),
+            reason: reason.to_string(),
+        });
+        Self::prune(&mut index, now);
+        self.write_index(&index)?;
+        self.remove_unindexed(&index);
+        Ok(())
+    }
+
+    /// Contents of a snapshot.
+    pub fn read(&self, id: &str) -> Result<String, String> {
+        let _guard = self.lock.lock().expect("snapshot lock");
+        if !self.read_index().iter().any(|s| s.id == id) {
+            return Err(format!("No config snapshot '{}'", id));
+        }
+        fs::read_to_string(self.snapshot_path(id)).map_err(|e| e.to_string())
+    }
+
+    fn prune(index: &mut Vec<SnapshotInfo>, now: DateTime<Utc>) {
+        let cutoff = now - chrono::Duration::days(MAX_AGE_DAYS);
+        let keep_from = index.len().saturating_sub(MIN_SNAPSHOTS);
+        let mut idx = 0;
+        index.retain(|s| {
+            let recent = idx >= keep_from
+                || DateTime::parse_from_rfc3339(&s.created_at)
+                    .map(|t| t.with_timezone(&Utc) >= cutoff)
+                    .unwrap_or(false);
+            idx += 1;
+            recent
+        });
+        if index.len() > MAX_SNAPSHOTS {
+            index.drain(..index.len() - MAX_SNAPSHOTS);
+        }
+    }
+
+    fn remove_unindexed(&self, index: &[SnapshotInfo]) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            if !index.iter().any(|s| s.id == stem) {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn snapshot_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.toml", id))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    fn read_index(&self) -> Vec<SnapshotInfo> {
+        let Ok(contents) = fs::read_to_string(self.index_path()) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!(
+                "Config snapshot index is corrupt, starting a new one: {}",
+                e
+            );
+            Vec::new()
+        })
+    }
+
+    fn write_index(&self, index: &[SnapshotInfo]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+        let tmp = self.index_path().with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, self.index_path()).map_err(|e| e.to_string())
+    }
+}
+
+/// Field-level differences from `before` to `after`. Lists of items with an
+/// `id` (apps, workspaces) are matched by id; other lists compare whole.
+pub fn diff(before: &Value, after: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    diff_into("", before, after, &mut changes);
+    changes
+}
+
+fn diff_into(path: &str, before: &Value, after: &Value, changes: &mut Vec<ConfigChange>) {
+    if before == after {
+        return;
+    }
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            for (key, bv) in b {
+                match a.get(key) {
+                    Some(av) => diff_into(&join(key), bv, av, changes),
+                    None => changes.push(change(join(key), Some(bv), None)),
+                }
+            }
+            for (key, av) in a.iter().filter(|(k, _)| !b.contains_key(*k)) {
+                changes.push(change(join(key), None, Some(av)));
+            }
+        }
+        (Value::Array(b), Value::Array(a)) if keyed(b) && keyed(a) => {
+            let id_of = |v: &Value| {
+                v.get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            for bv in b {
+                let id = id_of(bv);
+                let item = format!("{path}[{id}]");
+                match a.iter().find(|av| id_of(av) == id) {
+                    Some(av) => diff_into(&item, bv, av, changes),
+                    None => changes.push(change(item, Some(bv), None)),
+                }
+            }
+            for av in a {
+                let id = id_of(av);
+                if !b.iter().any(|bv| id_of(bv) == id) {
+                    changes.push(change(format!("{path}[{id}]"), None, Some(av)));
+                }
+            }
+        }
+        _ => changes.push(change(path.to_string(), Some(before), Some(after))),
+    }
+}
+
+fn keyed(items: &[Value]) -> bool {
+    items.iter().all(|v| {
+        v.get("id")
+            .and_then(Value::as_str)
+            .is_some_and(|id| !id.is_empty())
+    })
+}
+
+fn change(path: String, before: Option<&Value>, after: Option<&Value>) -> ConfigChange {
+    ConfigChange {
+        path,
+        before: before.cloned(),
+        after: after.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_store() -> (SnapshotStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("orbly-snapshots-{}", uuid::Uuid::new_v4()));
+        (SnapshotStore::new(&dir), dir)
+    }
+
+    #[test]
+    fn test_record_skips_duplicates_and_coalesces() {
+        let (store, dir) = temp_store();
+        store.record("update_app", "a = 1\n").expect("record");
+        store.record("update_app", "a = 1\n").expect("record");
+        assert_eq!(store.list().len(), 1);
+
+        // Same reason in quick succession replaces the latest snapshot
+        store.record("update_app", "a = 2\n").expect("record");
+        let list = store.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(store.read(&list[0].id).expect("read"), "a = 2\n");
+
+        store
+            .record("before import_config_json", "a = 2\nb = 1\n")
+            .expect("record");
+        let list = store.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].reason, "before import_config_json");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_retention_limits() {
+        let (store, dir) = temp_store();
+        for i in 0..(MAX_SNAPSHOTS + 5) {
+            store
+                .record(&format!("save {i}"), &format!("n = {i}\n"))
+                .expect("record");
+        }
+        let list = store.list();
+        assert_eq!(list.len(), MAX_SNAPSHOTS);
+        assert_eq!(list[0].reason, format!("save {}", MAX_SNAPSHOTS + 4));
+        let files = fs::read_dir(dir.join("snapshots")).unwrap().count();
+        assert_eq!(files, MAX_SNAPSHOTS + 1); // plus index.json
+
+        // Old snapshots beyond the minimum are dropped
+        let now = Utc::now();
+        let mut index = store.read_index();
+        for s in index.iter_mut().take(45) {
+            s.created_at = (now - chrono::Duration::days(MAX_AGE_DAYS + 1)).to_rfc3339();
+        }
+        SnapshotStore::prune(&mut index, now);
+        assert_eq!(index.len(), MIN_SNAPSHOTS);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_diff_matches_apps_by_id() {
+        let before = json!({
+            "general": { "theme": "system" },
+            "apps": [
+                { "id": "mail", "url": "https://mail.example.com", "zoom_level": 100 },
+                { "id": "chat", "url": "https://chat.example.com" },
+            ],
+        });
+        let after = json!({
+            "general": { "theme": "dark" },
+            "apps": [
+                { "id": "chat", "url": "https://chat.example.com" },
+                { "id": "mail", "url": "https://mail.example.com", "zoom_level": 120 },
+                { "id": "docs", "url": "https://docs.example.com" },
+            ],
+        });
+        let mut paths: Vec<_> = diff(&before, &after).into_iter().map(|c| c.path).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["apps[docs]", "apps[mail].zoom_level", "general.theme"]
+        );
+    }
+}
@@ -0,0 +1,721 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::merge::SyncConflict;
+use super::models::{SyncBackendKind, SyncConfig};
+
+/// Name of the synced file for folder and WebDAV backends.
+const SYNC_FILE_NAME: &str = "orbly-sync.json";
+/// Key used in the iCloud key-value store.
+const ICLOUD_KEY: &str = "orbly_config";
+/// How long to wait for further config writes before pushing.
+const PUSH_DEBOUNCE: Duration = Duration::from_secs(2);
+const WEBDAV_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A place the synced config is stored. Implementations move opaque payloads;
+/// the envelope format is handled by `SyncManager`.
+pub trait SyncBackend: Send {
+    /// Upload the payload, replacing any existing remote copy.
+    fn push(&self, payload: &str) -> Result<(), String>;
+    /// Download the remote payload, or `None` if nothing has been synced yet.
+    fn pull(&self) -> Result<Option<String>, String>;
+}
+
+/// iCloud key-value store (macOS only; a no-op elsewhere).
+pub struct ICloudBackend;
+
+impl SyncBackend for ICloudBackend {
+    fn push(&self, payload: &str) -> Result<(), String> {
+        if !cfg!(target_os = "macos") {
+            return Err("iCloud sync is only available on macOS".to_string());
+        }
+        crate::config::icloud::save_to_icloud(ICLOUD_KEY, payload);
+        Ok(())
+    }
+
+    fn pull(&self) -> Result<Option<String>, String> {
+        Ok(crate::config::icloud::load_from_icloud(ICLOUD_KEY))
+    }
+}
+
+/// A local directory kept in sync by an external tool (Syncthing, Nextcloud, Dropbox…).
+pub struct FolderBackend {
+    dir: PathBuf,
+}
+
+impl FolderBackend {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.dir.join(SYNC_FILE_NAME)
+    }
+}
+
+impl SyncBackend for FolderBackend {
+    fn push(&self, payload: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create sync folder {:?}: {}", self.dir, e))?;
+        // Write to a temp file and rename so sync tools never pick up a partial file
+        let tmp_path = self.dir.join(format!(".{}.tmp", SYNC_FILE_NAME));
+        std::fs::write(&tmp_path, payload)
+            .map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+        std::fs::rename(&tmp_path, self.file_path())
+            .map_err(|e| format!("Failed to replace {:?}: {}", self.file_path(), e))
+    }
+
+    fn pull(&self) -> Result<Option<String>, String> {
+        match std::fs::read_to_string(self.file_path()) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {:?}: {}", self.file_path(), e)),
+        }
+    }
+}
+
+/// A WebDAV collection (Nextcloud, ownCloud, Fastmail files, rclone serve webdav…).
+pub struct WebDavBackend {
+    collection_url: url::Url,
+    username: String,
+    password: String,
+    client: reqwest::blocking::Client,
+}
+
+impl WebDavBackend {
+    pub fn new(collection_url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let mut collection_url =
+            url::Url::parse(collection_url).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        match collection_url.scheme() {
+            "http" | "https" => {}
+            other => return Err(format!("Scheme '{}' is not allowed for WebDAV", other)),
+        }
+        // Treat the URL as a directory so joining the file name appends rather than replaces
+        if !collection_url.path().ends_with('/') {
+            let path = format!("{}/", collection_url.path());
+            collection_url.set_path(&path);
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(WEBDAV_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            collection_url,
+            username: username.to_string(),
+            password: password.to_string(),
+            client,
+        })
+    }
+
+    fn file_url(&self) -> url::Url {
+        self.collection_url
+            .join(SYNC_FILE_NAME)
+            .unwrap_or_else(|_| self.collection_url.clone())
+    }
+
+    fn request(&self, method: reqwest::Method, url: url::Url) -> reqwest::blocking::RequestBuilder {
+        let builder = self.client.request(method, url);
+        if self.username.is_empty() {
+            builder
+        } else {
+            builder.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    fn put(&self, payload: &str) -> Result<reqwest::StatusCode, String> {
+        self.request(reqwest::Method::PUT, self.file_url())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload.to_string())
+            .send()
+            .map(|r| r.status())
+            .map_err(|e| format!("WebDAV upload failed: {}", e))
+    }
+}
+
+impl SyncBackend for WebDavBackend {
+    fn push(&self, payload: &str) -> Result<(), String> {
+        let mut status = self.put(payload)?;
+        // 409 Conflict means the collection does not exist yet
+        if status == reqwest::StatusCode::CONFLICT {
+            let mkcol = reqwest::Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+            self.request(mkcol, self.collection_url.clone())
+                .send()
+                .map_err(|e| format!("WebDAV MKCOL failed: {}", e))?;
+            status = self.put(payload)?;
+        }
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("WebDAV upload returned HTTP {}", status.as_u16()))
+        }
+    }
+
+    fn pull(&self) -> Result<Option<String>, String> {
+        let response = self
+            .request(reqwest::Method::GET, self.file_url())
+            .send()
+            .map_err(|e| format!("WebDAV download failed: {}", e))?;
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            s if s.is_success() => response
+                .text()
+                .map(Some)
+                .map_err(|e| format!("WebDAV download failed: {}", e)),
+            s => Err(format!("WebDAV download returned HTTP {}", s.as_u16())),
+        }
+    }
+}
+
+/// Build the backend selected in the sync settings.
+pub fn backend_for(settings: &SyncConfig) -> Result<Box<dyn SyncBackend>, String> {
+    match settings.backend {
+        SyncBackendKind::ICloud => Ok(Box::new(ICloudBackend)),
+        SyncBackendKind::Folder => {
+            if settings.folder_path.trim().is_empty() {
+                return Err("No sync folder configured".to_string());
+            }
+            let dir = shellexpand::tilde(settings.folder_path.trim()).to_string();
+            Ok(Box::new(FolderBackend::new(PathBuf::from(dir))))
+        }
+        SyncBackendKind::WebDav => {
+            if settings.webdav_url.trim().is_empty() {
+                return Err("No WebDAV URL configured".to_string());
+            }
+            Ok(Box::new(WebDavBackend::new(
+                settings.webdav_url.trim(),
+                &settings.webdav_username,
+                &settings.webdav_password,
+            )?))
+        }
+    }
+}
+
+/// What is stored remotely: the config plus who wrote it and when.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncEnvelope {
+    pub format: u32,
+    pub device_id: String,
+    pub updated_at: String,
+    pub config: serde_json::Value,
+}
+
+impl SyncEnvelope {
+    /// Parse a remote payload. Payloads written before envelopes existed are a
+    /// bare config object and are accepted with empty metadata.
+    pub fn parse(payload: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(payload).map_err(|e| format!("Invalid sync payload: {}", e))?;
+        if value.get("format").is_some() && value.get("config").is_some() {
+            serde_json::from_value(value).map_err(|e| format!("Invalid sync envelope: {}", e))
+        } else {
+            Ok(Self {
+                format: 0,
+                device_id: String::new(),
+                updated_at: String::new(),
+                config: value,
+            })
+        }
+    }
+}
+
+/// Local bookkeeping about sync, persisted next to the config.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SyncState {
+    /// Identifies this machine in envelopes it writes.
+    pub device_id: String,
+    pub last_push_at: Option<String>,
+    pub last_pull_at: Option<String>,
+    pub last_error: Option<String>,
+    /// `updated_at` and `device_id` of the last envelope pulled.
+    pub remote_updated_at: Option<String>,
+    pub remote_device_id: Option<String>,
+    /// Conflicts from the last merge that still need a decision.
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Sync status reported to the frontend.
+#[derive(Serialize, Clone, Debug)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub backend: SyncBackendKind,
+    /// "disabled", "idle", "pending" or "error"
+    pub state: String,
+    pub last_synced_at: Option<String>,
+    pub last_push_at: Option<String>,
+    pub last_pull_at: Option<String>,
+    pub last_error: Option<String>,
+    pub remote_updated_at: Option<String>,
+    pub remote_device_id: Option<String>,
+    pub conflict_count: usize,
+}
+
+struct PushRequest {
+    settings: SyncConfig,
+    config_json: serde_json::Value,
+}
+
+/// Pushes config changes to the configured backend on a background thread,
+/// coalescing bursts of writes, and tracks sync metadata.
+pub struct SyncManager {
+    state_path: PathBuf,
+    /// Last config both this machine and the remote agreed on; the common
+    /// ancestor for three-way merges.
+    base_path: PathBuf,
+    state: Arc<Mutex<SyncState>>,
+    /// Push requests queued and not yet uploaded or coalesced away.
+    pending: Arc<AtomicUsize>,
+    sender: Mutex<Option<mpsc::Sender<PushRequest>>>,
+}
+
+impl SyncManager {
+    pub fn new(app_data_dir: &std::path::Path) -> Self {
+        let state_path = app_data_dir.join("sync_state.json");
+        let base_path = app_data_dir.join("sync_base.json");
+        let mut state: SyncState = std::fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        if state.device_id.is_empty() {
+            state.device_id = uuid::Uuid::new_v4().to_string();
+            Self::write_state(&state_path, &state);
+        }
+        Self {
+            state_path,
+            base_path,
+            state: Arc::new(Mutex::new(state)),
+            pending: Arc::new(AtomicUsize::new(0)),
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// Queue a push of `config_json`. Writes within the debounce window are
+    /// coalesced and only the latest is uploaded.
+    pub fn queue_push(&self, settings: &SyncConfig, config_json: serde_json::Value) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        let request = PushRequest {
+            settings: settings.clone(),
+            config_json,
+        };
+        let mut sender = self.sender.lock().expect("sync sender lock");
+        // Reuse the running worker; if it has exited, start a new one
+        let request = match sender.as_ref() {
+            Some(tx) => match tx.send(request) {
+                Ok(()) => return,
+                Err(mpsc::SendError(request)) => request,
+            },
+            None => request,
+        };
+        let (tx, rx) = mpsc::channel();
+        let _ = tx.send(request);
+        *sender = Some(tx);
+        self.spawn_worker(rx);
+    }
+
+    fn spawn_worker(&self, rx: mpsc::Receiver<PushRequest>) {
+        let state = self.state.clone();
+        let pending = self.pending.clone();
+        let state_path = self.state_path.clone();
+        let base_path = self.base_path.clone();
+        std::thread::spawn(move || {
+            while let Ok(mut request) = rx.recv() {
+                let mut taken = 1;
+                while let Ok(newer) = rx.recv_timeout(PUSH_DEBOUNCE) {
+                    request = newer;
+                    taken += 1;
+                }
+                Self::push_and_record(
+                    &state,
+                    &state_path,
+                    &base_path,
+                    &request.settings,
+                    request.config_json,
+                )
+                .ok();
+                // Requests queued during the push are still waiting
+                pending.fetch_sub(taken, Ordering::SeqCst);
+            }
+        });
+    }
+
+    fn push_and_record(
+        state: &Mutex<SyncState>,
+        state_path: &PathBuf,
+        base_path: &PathBuf,
+        settings: &SyncConfig,
+        config_json: serde_json::Value,
+    ) -> Result<(), String> {
+        let (device_id, last_seen) = {
+            let guard = state.lock().expect("sync state lock");
+            (guard.device_id.clone(), guard.remote_updated_at.clone())
+        };
+        let result = Self::push_now(settings, &device_id, last_seen.as_deref(), &config_json);
+        let mut guard = state.lock().expect("sync state lock");
+        if let Ok(ref updated_at) = result {
+            guard.remote_updated_at = Some(updated_at.clone());
+            guard.remote_device_id = Some(device_id);
+            Self::write_base(base_path, &config_json);
+        }
+        Self::record_result(
+            &mut guard,
+            "push",
+            result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+        );
+        Self::write_state(state_path, &guard);
+        result.map(|_| ())
+    }
+
+    /// Upload `config_json`, returning the envelope's `updated_at`. Refuses to
+    /// overwrite a remote copy written by another device that has not been
+    /// merged yet, so offline edits made elsewhere are never lost.
+    fn push_now(
+        settings: &SyncConfig,
+        device_id: &str,
+        last_seen_remote: Option<&str>,
+        config_json: &serde_json::Value,
+    ) -> Result<String, String> {
+        let backend = backend_for(settings)?;
+        if let Some(existing) = backend.pull()? {
+            let remote = SyncEnvelope::parse(&existing)?;
+            if !remote.device_id.is_empty()
+                && remote.device_id != device_id
+                && Some(remote.updated_at.as_str()) != last_seen_remote
+            {
+                return Err(
+                    "The synced config was changed on another device; sync now to merge"
+                        .to_string(),
+                );
+            }
+        }
+        let envelope = SyncEnvelope {
+            format: 1,
+            device_id: device_id.to_string(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            config: config_json.clone(),
+        };
+        let payload = serde_json::to_string_pretty(&envelope).map_err(|e| e.to_string())?;
+        backend.push(&payload)?;
+        Ok(envelope.updated_at)
+    }
+
+    /// Upload immediately, bypassing the debounce (used by "Sync now").
+    pub fn push_blocking(
+        &self,
+        settings: &SyncConfig,
+        config_json: serde_json::Value,
+    ) -> Result<(), String> {
+        Self::push_and_record(
+            &self.state,
+            &self.state_path,
+            &self.base_path,
+            settings,
+            config_json,
+        )
+    }
+
+    /// Download the remote envelope, if any, and record the outcome.
+    pub fn pull(&self, settings: &SyncConfig) -> Result<Option<SyncEnvelope>, String> {
+        let result = backend_for(settings)
+            .and_then(|backend| backend.pull())
+            .and_then(|payload| payload.map(|p| SyncEnvelope::parse(&p)).transpose());
+        let mut guard = self.state.lock().expect("sync state lock");
+        if let Ok(Some(ref envelope)) = result {
+            guard.remote_updated_at = Some(envelope.updated_at.clone()).filter(|s| !s.is_empty());
+            guard.remote_device_id = Some(envelope.device_id.clone()).filter(|s| !s.is_empty());
+        }
+        Self::record_result(
+            &mut guard,
+            "pull",
+            result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+        );
+        Self::write_state(&self.state_path, &guard);
+        result
+    }
+
+    /// Check that the backend is reachable without changing local state.
+    pub fn test_connection(settings: &SyncConfig) -> Result<bool, String> {
+        backend_for(settings)?
+            .pull()
+            .map(|payload| payload.is_some())
+    }
+
+    /// The last config known to match the remote copy.
+    pub fn base(&self) -> Option<serde_json::Value> {
+        std::fs::read_to_string(&self.base_path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// Record `config_json` as matching the remote copy without pushing it.
+    pub fn set_base(&self, config_json: &serde_json::Value) {
+        Self::write_base(&self.base_path, config_json);
+    }
+
+    pub fn conflicts(&self) -> Vec<SyncConflict> {
+        self.state
+            .lock()
+            .expect("sync state lock")
+            .conflicts
+            .clone()
+    }
+
+    pub fn set_conflicts(&self, conflicts: Vec<SyncConflict>) {
+        let mut guard = self.state.lock().expect("sync state lock");
+        guard.conflicts = conflicts;
+        Self::write_state(&self.state_path, &guard);
+    }
+
+    pub fn remove_conflict(&self, conflict_id: &str) {
+        let mut guard = self.state.lock().expect("sync state lock");
+        guard.conflicts.retain(|c| c.id != conflict_id);
+        Self::write_state(&self.state_path, &guard);
+    }
+
+    pub fn device_id(&self) -> String {
+        self.state
+            .lock()
+            .expect("sync state lock")
+            .device_id
+            .clone()
+    }
+
+    pub fn status(&self, settings: &SyncConfig) -> SyncStatus {
+        let state = self.state.lock().expect("sync state lock").clone();
+        let pending = self.pending.load(Ordering::SeqCst) > 0;
+        let state_str = if !settings.enabled {
+            "disabled"
+        } else if pending {
+            "pending"
+        } else if state.last_error.is_some() {
+            "error"
+        } else {
+            "idle"
+        };
+        let last_synced_at = match (&state.last_push_at, &state.last_pull_at) {
+            (Some(push), Some(pull)) => Some(push.clone().max(pull.clone())),
+            (push, pull) => push.clone().or_else(|| pull.clone()),
+        };
+        SyncStatus {
+            enabled: settings.enabled,
+            backend: settings.backend.clone(),
+            state: state_str.to_string(),
+            last_synced_at,
+            last_push_at: state.last_push_at,
+            last_pull_at: state.last_pull_at,
+            last_error: state.last_error,
+            remote_updated_at: state.remote_updated_at,
+            remote_device_id: state.remote_device_id,
+            conflict_count: state.conflicts.len(),
+        }
+    }
+
+    fn record_result(state: &mut SyncState, op: &str, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                let now = Some(chrono::Utc::now().to_rfc3339());
+                if op == "push" {
+                    state.last_push_at = now;
+                } else {
+                    state.last_pull_at = now;
+                }
+                state.last_error = None;
+            }
+            Err(e) => {
+                log::warn!("Config sync {} failed: {}", op, e);
+                state.last_error = Some(e);
+            }
+        }
+    }
+
+    fn write_base(path: &PathBuf, config_json: &serde_json::Value) {
+        match serde_json::to_string(config_json) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    log::warn!("Failed to persist sync base: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to serialize sync base: {}", e),
+        }
+    }
+
+    fn write_state(path: &PathBuf, state: &SyncState) {
+        match serde_json::to_string_pretty(state) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    log::warn!("Failed to persist sync state: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to serialize sync state: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("orbly-sync-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn folder_settings(dir: &std::path::Path) -> SyncConfig {
+        SyncConfig {
+            enabled: true,
+            backend: SyncBackendKind::Folder,
+            folder_path: dir.to_string_lossy().to_string(),
+            ..SyncConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_folder_backend_roundtrip() {
+        let dir = temp_dir();
+        let backend = FolderBackend::new(dir.join("nested"));
+        assert_eq!(backend.pull().expect("pull"), None);
+        backend.push("{\"a\":1}").expect("push");
+        assert_eq!(backend.pull().expect("pull").as_deref(), Some("{\"a\":1}"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_envelope_accepts_legacy_payload() {
+        let envelope = SyncEnvelope::parse("{\"general\":{\"config_version\":1}}").expect("parse");
+        assert_eq!(envelope.format, 0);
+        assert_eq!(envelope.config["general"]["config_version"], 1);
+    }
+
+    #[test]
+    fn test_push_and_pull_record_metadata() {
+        let dir = temp_dir();
+        let manager = SyncManager::new(&dir);
+        let settings = folder_settings(&dir.join("synced"));
+
+        manager
+            .push_blocking(&settings, serde_json::json!({ "apps": [] }))
+            .expect("push");
+        let envelope = manager.pull(&settings).expect("pull").expect("envelope");
+        assert_eq!(envelope.device_id, manager.device_id());
+        assert_eq!(envelope.config["apps"], serde_json::json!([]));
+
+        let status = manager.status(&settings);
+        assert_eq!(status.state, "idle");
+        assert!(status.last_push_at.is_some());
+        assert!(status.last_pull_at.is_some());
+        assert_eq!(status.remote_device_id, Some(manager.device_id()));
+
+        // Device id survives a restart
+        let reopened = SyncManager::new(&dir);
+        assert_eq!(reopened.device_id(), manager.device_id());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_queued_pushes_stay_pending_until_uploaded() {
+        let dir = temp_dir();
+        let manager = SyncManager::new(&dir);
+        let settings = folder_settings(&dir.join("synced"));
+
+        manager.queue_push(&settings, serde_json::json!({ "n": 1 }));
+        manager.queue_push(&settings, serde_json::json!({ "n": 2 }));
+        assert_eq!(manager.status(&settings).state, "pending");
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while manager.status(&settings).state == "pending" {
+            assert!(std::time::Instant::now() < deadline, "push never finished");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let envelope = manager.pull(&settings).expect("pull").expect("envelope");
+        assert_eq!(envelope.config["n"], 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_misconfigured_backend_reports_error() {
+        let dir = temp_dir();
+        let manager = SyncManager::new(&dir);
+        let settings = SyncConfig {
+            enabled: true,
+            backend: SyncBackendKind::WebDav,
+            ..SyncConfig::default()
+        };
+        assert!(manager.pull(&settings).is_err());
+        assert_eq!(manager.status(&settings).state, "error");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    /// Minimal single-file WebDAV stand-in: answers PUT with 201 and GET with
+    /// the last body stored (or 404).
+    fn spawn_webdav_stub(requests: usize) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let handle = std::thread::spawn(move || {
+            let mut stored: Option<String> = None;
+            let mut seen = Vec::new();
+            for _ in 0..requests {
+                let (mut stream, _) = listener.accept().expect("accept");
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let (head, body) = loop {
+                    let n = stream.read(&mut chunk).expect("read");
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(idx) = text.find("\r\n\r\n") {
+                        let head = text[..idx].to_string();
+                        let len = head
+                            .lines()
+                            .find_map(|l| {
+                                l.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if buf.len() >= idx + 4 + len {
+                            break (head, text[idx + 4..idx + 4 + len].to_string());
+                        }
+                    }
+                };
+                let request_line = head.lines().next().unwrap_or_default().to_string();
+                seen.push(request_line.clone());
+                let response = if request_line.starts_with("PUT ") {
+                    stored = Some(body);
+                    "HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else if let Some(ref body) = stored {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).expect("write");
+            }
+            seen
+        });
+        (format!("http://{}/dav/orbly", addr), handle)
+    }
+
+    #[test]
+    fn test_webdav_backend_against_stub() {
+        let (url, server) = spawn_webdav_stub(3);
+        let backend = WebDavBackend::new(&url, "me", "secret").expect("backend");
+        assert_eq!(backend.pull().expect("pull"), None);
+        backend.push("{\"format\":1}").expect("push");
+        assert_eq!(
+            backend.pull().expect("pull").as_deref(),
+            Some("{\"format\":1}")
+        );
+
+        let seen = server.join().expect("server");
+        assert!(seen
+            .iter()
+            .all(|l| l.contains("/dav/orbly/orbly-sync.json")));
+        assert!(seen[1].starts_with("PUT "));
+    }
+}
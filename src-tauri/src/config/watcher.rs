@@ -0,0 +1,148 @@
+//! Picks up edits made to `config.toml` outside Orbly (by hand or by dotfiles
+//! tooling) and applies them to the running app.
+//!
+//! The file is polled rather than watched with filesystem notifications so
+//! edits that replace it (editors writing a temp file, symlinked dotfiles)
+//! are seen the same way as in-place writes.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::manager::ConfigManager;
+use super::models::OrblyConfig;
+use crate::adblock::engine::AdblockState;
+use crate::adblock::filter_lists::FilterListManager;
+use crate::app_manager::lifecycle;
+use crate::app_manager::session_state::SessionState;
+use crate::app_manager::startup;
+use crate::app_manager::state::{AppManager, AppRuntimeState};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn start_config_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let config_manager = app_handle.state::<ConfigManager>();
+            match config_manager.reload_from_disk() {
+                Ok(Some(reload)) => {
+                    log::info!("config.toml changed on disk, applying");
+                    apply_reload(&app_handle, &reload.old, &reload.new);
+                    let _ = app_handle.emit("config-reloaded", ());
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("Ignoring invalid edit to config.toml: {}", e);
+                    let _ = app_handle.emit("config-reload-failed", e);
+                }
+            }
+        }
+    });
+}
+
+fn changed<T: Serialize>(old: &T, new: &T) -> bool {
+    serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+}
+
+/// Push a config that was replaced wholesale (by an external edit or a
+/// snapshot restore) out to the parts of the app that cache it.
+pub fn apply_reload(app_handle: &AppHandle, old: &OrblyConfig, new: &OrblyConfig) {
+    let app_manager = app_handle.state::<AppManager>();
+
+    for app in &new.apps {
+        let Some(old_app) = old.apps.iter().find(|a| a.id == app.id) else {
+            app_manager.init_from_config(std::slice::from_ref(app));
+            continue;
+        };
+        if old_app.enabled && !app.enabled {
+            let _ = lifecycle::destroy_app_webview(app_handle, &app.id);
+            crate::app_manager::set_app_state(
+                app_handle,
+                &app.id,
+                AppRuntimeState::Disabled,
+                "disabled in config file",
+            );
+            if let Some(session_state) = app_handle.try_state::<SessionState>() {
+                session_state.remove(&app.id);
+            }
+            let _ = app_handle.emit("app-disabled", &app.id);
+        } else if !old_app.enabled && app.enabled {
+            // Starts out hibernated; `reenabled_queue` loads it unless its
+            // launch policy says otherwise
+            app_manager.init_from_config(std::slice::from_ref(app));
+            let _ = app_handle.emit("app-enabled", &app.id);
+        } else if lifecycle::needs_webview_recreate(old_app, app) {
+            lifecycle::recreate_app_webview(app_handle, app);
+        } else if old_app.zoom_level != app.zoom_level {
+            if let Some(webview) = app_handle.get_webview(&app.id) {
+                let _ = webview.set_zoom(app.zoom_level as f64 / 100.0);
+            }
+        }
+    }
+
+    startup::start_queue(app_handle.clone(), startup::reenabled_queue(old, new));
+
+    for removed in old
+        .apps
+        .iter()
+        .filter(|a| !new.apps.iter().any(|n| n.id == a.id))
+    {
+        let _ = lifecycle::destroy_app_webview(app_handle, &removed.id);
+        app_manager.remove(&removed.id);
+    }
+
+    if changed(&old.adblock, &new.adblock) {
+        reload_adblock(app_handle, old, new);
+    }
+
+    if changed(&old.shortcuts, &new.shortcuts) {
+        let _ = app_handle.emit("shortcuts-updated", ());
+    }
+
+    if old.general.recipe_manifest_url != new.general.recipe_manifest_url {
+        if let Some(recipe_manager) = app_handle.try_state::<crate::recipes::RecipeManager>() {
+            recipe_manager.set_manifest_url(new.general.recipe_manifest_url.clone());
+        }
+    }
+
+    crate::tray::rebuild_tray_menu(app_handle);
+    let _ = app_handle.emit("config-updated", ());
+}
+
+fn reload_adblock(app_handle: &AppHandle, old: &OrblyConfig, new: &OrblyConfig) {
+    let adblock = new.adblock.clone();
+    if !adblock.enabled {
+        app_handle.state::<AdblockState>().load_rules("", &[]);
+        let _ = app_handle.emit("content-rules-updated", ());
+        return;
+    }
+    if adblock.filter_lists == old.adblock.filter_lists && old.adblock.enabled {
+        if let Some(rules_text) = app_handle.state::<AdblockState>().get_filter_rules_text() {
+            app_handle
+                .state::<AdblockState>()
+                .load_rules(&rules_text, &adblock.custom_rules);
+            let _ = app_handle.emit("content-rules-updated", ());
+            return;
+        }
+    }
+    // Lists changed or were never loaded: fetch them (from cache when fresh)
+    let Ok(app_data_dir) = app_handle.path().app_data_dir() else {
+        return;
+    };
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let manager = FilterListManager::new(app_data_dir);
+        match manager.get_filter_rules(&adblock.filter_lists, false).await {
+            Ok(rules_text) => {
+                handle
+                    .state::<AdblockState>()
+                    .load_rules(&rules_text, &adblock.custom_rules);
+                let _ = handle.emit("content-rules-updated", ());
+            }
+            Err(e) => log::error!("Failed to load adblock filter lists: {}", e),
+        }
+    });
+}
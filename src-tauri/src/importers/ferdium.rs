@@ -0,0 +1,239 @@
+//! Franz, Ferdi and Ferdium.
+//!
+//! Ferdium (and Ferdi before it) keeps services in `server.sqlite` when used
+//! without an account server; Franz accounts only keep them on Franz's
+//! servers, so a Franz data directory imports only if it has the same
+//! database. Service URLs come from the recipe's `package.json` unless the
+//! service has a custom URL, and per-recipe `user.css` / `user.js` files
+//! become the app's custom CSS and JS.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags};
+use serde_json::Value;
+
+use super::{bool_field, str_field, ImportedService};
+
+const DATABASE: &str = "server.sqlite";
+/// `server` values meaning the built-in local server is in use.
+const LOCAL_SERVERS: &[&str] = &[
+    "You are using Ferdium without a server",
+    "You are using Ferdi without a server",
+    "http://127.0.0.1:45569",
+    "http://localhost:45569",
+];
+
+/// Read services from a data directory, or from a `server.sqlite` chosen
+/// directly.
+pub fn read_services(
+    path: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ImportedService>, String> {
+    let (dir, db) = if path.is_file() {
+        let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        (dir, path.to_path_buf())
+    } else {
+        (path.to_path_buf(), path.join(DATABASE))
+    };
+    if !db.is_file() {
+        return Err(format!(
+            "No local service database at {}. Franz and Ferdium accounts keep \
+             services on their server; switch to the local server or export \
+             them there first",
+            db.display()
+        ));
+    }
+    if let Some(server) = read_server(&dir) {
+        if !LOCAL_SERVERS.contains(&server.as_str()) {
+            warnings.push(format!(
+                "This install syncs with {server}; only services in the local \
+                 database are imported"
+            ));
+        }
+    }
+
+    let conn = Connection::open_with_flags(&db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open {}: {e}", db.display()))?;
+    let mut stmt = conn
+        .prepare("SELECT name, recipeId, settings FROM services ORDER BY id")
+        .map_err(|e| format!("Unrecognised service database: {e}"))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut services = Vec::new();
+    for row in rows {
+        let (name, recipe, settings) = row.map_err(|e| e.to_string())?;
+        let settings: Value = settings
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or(Value::Null);
+        let order = settings.get("order").and_then(Value::as_i64).unwrap_or(0);
+        match service_from_row(&dir, name, recipe, &settings) {
+            Ok(service) => services.push((order, service)),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    services.sort_by_key(|(order, _)| *order);
+    Ok(services.into_iter().map(|(_, s)| s).collect())
+}
+
+fn service_from_row(
+    dir: &Path,
+    name: String,
+    recipe: String,
+    settings: &Value,
+) -> Result<ImportedService, String> {
+    let recipe_dir = recipe_dir(dir, &recipe);
+    let mut notes = Vec::new();
+    let custom_url = str_field(settings, "customUrl");
+    let url = if custom_url.trim().is_empty() {
+        recipe_url(&recipe_dir, &str_field(settings, "team"))
+            .ok_or_else(|| format!("Skipped '{name}': no URL found for recipe '{recipe}'"))?
+    } else {
+        custom_url.trim().to_string()
+    };
+
+    let custom_css = read_user_file(&recipe_dir, "user.css");
+    let custom_js = read_user_file(&recipe_dir, "user.js");
+    if !custom_js.is_empty() {
+        notes.push("Custom JS comes from the recipe's user.js".to_string());
+    }
+    if !bool_field(settings, "isBadgeEnabled", true) {
+        notes.push("Unread badges were hidden in the source app".to_string());
+    }
+
+    Ok(ImportedService {
+        name,
+        url,
+        custom_css,
+        custom_js,
+        notifications: bool_field(settings, "isNotificationEnabled", true),
+        muted: bool_field(settings, "isMuted", false),
+        enabled: bool_field(settings, "isEnabled", true),
+        dark_mode: bool_field(settings, "isDarkModeEnabled", false),
+        notes,
+        recipe,
+    })
+}
+
+fn read_server(dir: &Path) -> Option<String> {
+    let contents = fs::read_to_string(dir.join("config").join("settings.json")).ok()?;
+    let settings: Value = serde_json::from_str(&contents).ok()?;
+    settings
+        .get("server")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn recipe_dir(dir: &Path, recipe: &str) -> PathBuf {
+    // Recipe ids are plain names; refuse anything that would leave `recipes/`
+    let safe = !recipe.is_empty() && !recipe.contains(['/', '\\']) && recipe != "..";
+    dir.join("recipes")
+        .join(if safe { recipe } else { "_invalid" })
+}
+
+/// The recipe's service URL, with the team filled in for team-based services.
+fn recipe_url(recipe_dir: &Path, team: &str) -> Option<String> {
+    let contents = fs::read_to_string(recipe_dir.join("package.json")).ok()?;
+    let package: Value = serde_json::from_str(&contents).ok()?;
+    let url = package
+        .get("config")
+        .and_then(|c| c.get("serviceURL"))
+        .and_then(Value::as_str)?;
+    if url.is_empty() || (url.contains("{teamId}") && team.is_empty()) {
+        return None;
+    }
+    Some(url.replace("{teamId}", team))
+}
+
+fn read_user_file(recipe_dir: &Path, name: &str) -> String {
+    fs::read_to_string(recipe_dir.join(name))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER_SQL: &str = include_str!("fixtures/ferdium/server.sql");
+    const SETTINGS: &str = include_str!("fixtures/ferdium/settings.json");
+    const SLACK_PACKAGE: &str = include_str!("fixtures/ferdium/slack-package.json");
+    const WHATSAPP_PACKAGE: &str = include_str!("fixtures/ferdium/whatsapp-package.json");
+
+    fn fixture_dir(settings: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("orbly-ferdium-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("config")).unwrap();
+        fs::write(dir.join("config/settings.json"), settings).unwrap();
+        for (recipe, package) in [("slack", SLACK_PACKAGE), ("whatsapp", WHATSAPP_PACKAGE)] {
+            fs::create_dir_all(dir.join("recipes").join(recipe)).unwrap();
+            fs::write(
+                dir.join("recipes").join(recipe).join("package.json"),
+                package,
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.join("recipes/slack/user.css"),
+            ".p-client { font-size: 15px; }\n",
+        )
+        .unwrap();
+        let conn = Connection::open(dir.join(DATABASE)).unwrap();
+        conn.execute_batch(SERVER_SQL).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_reads_services_from_local_database() {
+        let dir = fixture_dir(SETTINGS);
+        let mut warnings = Vec::new();
+        let services = read_services(&dir, &mut warnings).expect("services");
+
+        // The unknown recipe without a custom URL is skipped with a warning
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Old Chat"), "{warnings:?}");
+
+        let names: Vec<_> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["WhatsApp", "Acme Slack", "Wiki"]);
+
+        let slack = &services[1];
+        assert_eq!(slack.recipe, "slack");
+        assert_eq!(slack.url, "https://acme.slack.com");
+        assert_eq!(slack.custom_css, ".p-client { font-size: 15px; }");
+        assert!(!slack.notifications);
+        assert!(slack.muted);
+        assert!(slack.dark_mode);
+
+        let whatsapp = &services[0];
+        assert_eq!(whatsapp.url, "https://web.whatsapp.com");
+        assert!(whatsapp.notifications);
+        assert!(!whatsapp.muted);
+
+        let wiki = &services[2];
+        assert_eq!(wiki.url, "https://wiki.example.com");
+        assert!(!wiki.enabled);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_warns_when_using_account_server() {
+        let dir = fixture_dir(r#"{ "server": "https://api.ferdium.org" }"#);
+        let mut warnings = Vec::new();
+        read_services(&dir, &mut warnings).expect("services");
+        assert!(warnings.iter().any(|w| w.contains("api.ferdium.org")));
+
+        // A Franz directory without a local database cannot be imported
+        let empty = dir.join("franz");
+        fs::create_dir_all(&empty).unwrap();
+        assert!(read_services(&empty, &mut warnings).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
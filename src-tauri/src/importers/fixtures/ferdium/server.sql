@@ -0,0 +1,24 @@
+-- Schema and rows in the shape of Ferdium's internal-server database
+-- (server.sqlite), as written by Ferdium 6.x.
+CREATE TABLE services (
+  id integer not null primary key autoincrement,
+  serviceId varchar(255),
+  name varchar(80) not null,
+  recipeId varchar(254) not null,
+  settings json,
+  created_at datetime,
+  updated_at datetime
+);
+INSERT INTO services (serviceId, name, recipeId, settings, created_at, updated_at) VALUES
+  ('6b1a5a5e-0f3e-4c1b-9a59-2c1d8f0f6c11', 'Acme Slack', 'slack',
+   '{"team":"acme","customUrl":"","isEnabled":true,"isNotificationEnabled":false,"isBadgeEnabled":true,"isMuted":true,"isDarkModeEnabled":true,"order":2,"iconUrl":null}',
+   '2024-03-01 09:12:44', '2024-05-20 17:03:10'),
+  ('0a3e0c52-7d5b-4b68-8d0e-4a6f3b1f2d22', 'WhatsApp', 'whatsapp',
+   '{"isEnabled":true,"isNotificationEnabled":true,"isBadgeEnabled":true,"isMuted":false,"order":1}',
+   '2024-03-01 09:13:02', '2024-03-01 09:13:02'),
+  ('f2c9d1a7-3b6e-4e4f-8a1c-9d7e5b3a4c33', 'Wiki', 'custom-website',
+   '{"customUrl":"https://wiki.example.com","isEnabled":false,"isNotificationEnabled":true,"isBadgeEnabled":false,"isMuted":false,"order":3}',
+   '2024-04-11 14:40:19', '2024-04-11 14:40:19'),
+  ('c4d8e2b6-1a9f-4d3c-b7e5-6f2a8c1d9e44', 'Old Chat', 'hangoutschat',
+   '{"isEnabled":true,"order":4}',
+   '2024-04-12 08:00:00', '2024-04-12 08:00:00');
@@ -0,0 +1,326 @@
+//! Importers for the service lists of other multi-service apps.
+//!
+//! Each importer reads a source app's local data and turns its services into
+//! an [`ImportPlan`]: ready-made `AppConfig`s plus notes on anything that
+//! could not be carried over. Nothing is written until the user has reviewed
+//! the plan and [`apply`] is called with the apps they kept.
+
+pub mod ferdium;
+pub mod rambox;
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::config::defaults::default_workspace;
+use crate::config::models::{AppConfig, OrblyConfig};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Franz,
+    Ferdium,
+    Rambox,
+}
+
+impl ImportSource {
+    pub const ALL: [ImportSource; 3] = [Self::Franz, Self::Ferdium, Self::Rambox];
+
+    /// Standard data directories, most likely first. Ferdium's predecessor
+    /// Ferdi uses the same layout, so its directory is tried as well.
+    pub fn default_dirs(self) -> Vec<PathBuf> {
+        let Some(config_dir) = dirs::config_dir() else {
+            return Vec::new();
+        };
+        let names: &[&str] = match self {
+            Self::Franz => &["Franz"],
+            Self::Ferdium => &["Ferdium", "Ferdi"],
+            Self::Rambox => &["Rambox", "rambox"],
+        };
+        names.iter().map(|name| config_dir.join(name)).collect()
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DetectedSource {
+    pub source: ImportSource,
+    pub path: String,
+}
+
+/// One service from the source app, before it becomes an `AppConfig`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportedService {
+    /// The source app's recipe or service type, e.g. `slack` or `msteams`.
+    pub recipe: String,
+    pub name: String,
+    pub url: String,
+    pub custom_css: String,
+    pub custom_js: String,
+    pub notifications: bool,
+    pub muted: bool,
+    pub enabled: bool,
+    pub dark_mode: bool,
+    /// Things the user should know about this service, e.g. settings that
+    /// have no Orbly equivalent.
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportItem {
+    pub app: AppConfig,
+    /// Name of an existing app with the same URL, if any.
+    pub existing: Option<String>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportPlan {
+    pub source: ImportSource,
+    pub path: String,
+    pub items: Vec<ImportItem>,
+    /// Problems with the source as a whole rather than one service.
+    pub warnings: Vec<String>,
+}
+
+/// Source data directories that exist on this machine.
+pub fn detect() -> Vec<DetectedSource> {
+    let mut found = Vec::new();
+    for source in ImportSource::ALL {
+        if let Some(dir) = source.default_dirs().into_iter().find(|d| d.is_dir()) {
+            found.push(DetectedSource {
+                source,
+                path: dir.to_string_lossy().to_string(),
+            });
+        }
+    }
+    found
+}
+
+/// Read `source` from `path` (or its standard directory) and build a plan
+/// for importing its services into `config`.
+pub fn plan(
+    config: &OrblyConfig,
+    source: ImportSource,
+    path: Option<&Path>,
+) -> Result<ImportPlan, String> {
+    let path = match path {
+        Some(p) => p.to_path_buf(),
+        None => source
+            .default_dirs()
+            .into_iter()
+            .find(|d| d.exists())
+            .ok_or_else(|| format!("No {:?} data found; choose its folder instead", source))?,
+    };
+    let mut warnings = Vec::new();
+    let services = match source {
+        ImportSource::Franz | ImportSource::Ferdium => {
+            ferdium::read_services(&path, &mut warnings)?
+        }
+        ImportSource::Rambox => rambox::read_services(&path, &mut warnings)?,
+    };
+    if services.is_empty() {
+        warnings.push(format!("No services found in {}", path.display()));
+    }
+
+    let items = services
+        .into_iter()
+        .map(|service| {
+            let mut notes = service.notes.clone();
+            let app = app_from_service(&service, &mut notes);
+            let existing = config
+                .apps
+                .iter()
+                .find(|a| same_url(&a.url, &app.url))
+                .map(|a| a.name.clone());
+            ImportItem {
+                app,
+                existing,
+                notes,
+            }
+        })
+        .collect();
+
+    Ok(ImportPlan {
+        source,
+        path: path.to_string_lossy().to_string(),
+        items,
+        warnings,
+    })
+}
+
+/// Add the apps the user kept from a plan. Ids and data stores that are
+/// already taken are replaced, and the apps are placed after existing ones.
+/// Returns the ids of the added apps.
+pub fn apply(config: &mut OrblyConfig, apps: Vec<AppConfig>) -> Vec<String> {
+    let first_position = config
+        .apps
+        .iter()
+        .map(|a| a.position + 1)
+        .max()
+        .unwrap_or(0);
+    let mut ids = Vec::with_capacity(apps.len());
+    for (position, mut app) in (first_position..).zip(apps) {
+        if app.id.is_empty() || config.apps.iter().any(|a| a.id == app.id) {
+            app.id = Uuid::new_v4().to_string();
+        }
+        if config
+            .apps
+            .iter()
+            .any(|a| a.data_store_uuid == app.data_store_uuid)
+        {
+            app.data_store_uuid = Uuid::new_v4();
+        }
+        app.position = position;
+        ids.push(app.id.clone());
+        config.apps.push(app);
+    }
+    ids
+}
+
+fn app_from_service(service: &ImportedService, notes: &mut Vec<String>) -> AppConfig {
+    let service_type = service_type_for(&service.recipe);
+    if service_type == "custom" && !service.recipe.is_empty() && service.recipe != "custom" {
+        notes.push(format!(
+            "No Orbly template for '{}', imported as a custom app",
+            service.recipe
+        ));
+    }
+    let name = if service.name.trim().is_empty() {
+        service.recipe.clone()
+    } else {
+        service.name.trim().to_string()
+    };
+    let value = json!({
+        "id": Uuid::new_v4().to_string(),
+        "name": name,
+        "service_type": service_type,
+        "url": service.url,
+        "data_store_uuid": Uuid::new_v4().to_string(),
+        "enabled": service.enabled,
+        "audio_muted": service.muted,
+        "custom_css": service.custom_css,
+        "custom_js": service.custom_js,
+        "dark_mode": if service.dark_mode { "dynamic" } else { "off" },
+        "notification_style": if service.notifications { "full" } else { "off" },
+        "workspace": default_workspace(),
+    });
+    serde_json::from_value(value).expect("imported app config")
+}
+
+/// Orbly service templates and the recipe ids other apps use for them.
+const SERVICE_TYPES: &[(&str, &[&str])] = &[
+    ("whatsapp", &["whatsapp"]),
+    ("slack", &["slack"]),
+    ("telegram", &["telegram"]),
+    ("discord", &["discord"]),
+    ("mattermost", &["mattermost"]),
+    ("teams", &["msteams", "teams", "microsoft-teams"]),
+    ("messenger", &["messenger", "facebook-messenger"]),
+    ("gmail", &["gmail", "gmailinbox"]),
+    (
+        "outlook",
+        &["outlook", "outlook365", "office365-owa", "outlook-web"],
+    ),
+    ("notion", &["notion"]),
+    ("linear", &["linear"]),
+    (
+        "google-calendar",
+        &["googlecalendar", "google-calendar", "gcalendar"],
+    ),
+    ("google-drive", &["gdrive", "googledrive", "google-drive"]),
+    ("todoist", &["todoist"]),
+    ("trello", &["trello"]),
+    ("github", &["github"]),
+    ("gitlab", &["gitlab"]),
+    (
+        "protonmail",
+        &["protonmail", "proton-mail", "protonmail-v4"],
+    ),
+    ("figma", &["figma"]),
+    ("twitter", &["twitter", "tweetdeck", "x"]),
+    ("reddit", &["reddit"]),
+    ("youtube", &["youtube", "youtubemusic"]),
+    ("spotify", &["spotify"]),
+];
+
+/// Map a source recipe id onto an Orbly service type.
+pub fn service_type_for(recipe: &str) -> String {
+    let recipe = recipe.trim().to_lowercase();
+    SERVICE_TYPES
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&recipe.as_str()))
+        .map(|(ty, _)| ty.to_string())
+        .unwrap_or_else(|| "custom".to_string())
+}
+
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/')
+        .eq_ignore_ascii_case(b.trim_end_matches('/'))
+}
+
+fn bool_field(value: &Value, key: &str, default: bool) -> bool {
+    match value.get(key) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_i64().map(|n| n != 0).unwrap_or(default),
+        Some(Value::String(s)) => matches!(s.as_str(), "true" | "1"),
+        _ => default,
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_type_aliases() {
+        assert_eq!(service_type_for("msteams"), "teams");
+        assert_eq!(service_type_for("Slack"), "slack");
+        assert_eq!(service_type_for("office365-owa"), "outlook");
+        assert_eq!(service_type_for("hangoutschat"), "custom");
+    }
+
+    #[test]
+    fn test_apply_replaces_taken_ids() {
+        let mut config: OrblyConfig = toml::from_str(
+            r#"
+[[apps]]
+id = "slack"
+name = "Slack"
+url = "https://app.slack.com"
+position = 4
+"#,
+        )
+        .expect("config");
+        let mut service = ImportedService {
+            recipe: "slack".to_string(),
+            name: "Work Slack".to_string(),
+            url: "https://work.slack.com".to_string(),
+            notifications: true,
+            enabled: true,
+            ..Default::default()
+        };
+        let mut notes = Vec::new();
+        let mut app = app_from_service(&service, &mut notes);
+        app.id = "slack".to_string();
+        service.name = "Other".to_string();
+        let other = app_from_service(&service, &mut notes);
+
+        let ids = apply(&mut config, vec![app, other]);
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], "slack");
+        assert_eq!(config.apps.len(), 3);
+        assert_eq!(config.apps[1].position, 5);
+        assert_eq!(config.apps[2].position, 6);
+        assert!(notes.is_empty());
+    }
+}
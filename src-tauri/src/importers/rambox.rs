@@ -0,0 +1,233 @@
+//! Rambox Community Edition.
+//!
+//! Rambox keeps services as JSON records in its Chromium local storage
+//! (`Local Storage/leveldb` in the data directory), one record per service.
+//! The records are found by scanning the LevelDB files for JSON objects that
+//! look like services; blocks LevelDB has compressed are skipped, so a
+//! backup made with Rambox's "Backup configuration" (a JSON array of the
+//! same records) can be chosen instead and is read directly.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::{bool_field, str_field, ImportedService};
+
+/// Read services from a data directory or a backup file.
+pub fn read_services(
+    path: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ImportedService>, String> {
+    let records = if path.is_file() {
+        read_backup(path)?
+    } else {
+        let leveldb = path.join("Local Storage").join("leveldb");
+        if !leveldb.is_dir() {
+            return Err(format!(
+                "No Rambox local storage at {}; choose a Rambox backup file instead",
+                leveldb.display()
+            ));
+        }
+        scan_leveldb(&leveldb)?
+    };
+
+    let mut services = Vec::new();
+    for record in records {
+        match service_from_record(&record) {
+            Ok(service) => services.push(service),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    Ok(services)
+}
+
+fn read_backup(path: &Path) -> Result<Vec<Value>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value =
+        serde_json::from_str(&contents).map_err(|e| format!("Not a Rambox backup: {e}"))?;
+    let records = match value {
+        Value::Array(items) => items,
+        Value::Object(mut obj) => match obj.remove("services") {
+            Some(Value::Array(items)) => items,
+            _ => return Err("Not a Rambox backup: no services".to_string()),
+        },
+        _ => return Err("Not a Rambox backup".to_string()),
+    };
+    let mut records: Vec<_> = records.into_iter().filter(is_service_record).collect();
+    records.sort_by_key(|r| r.get("position").and_then(Value::as_i64).unwrap_or(0));
+    Ok(records)
+}
+
+/// Find service records in LevelDB table and log files. Later writes of a
+/// record replace earlier ones, so files are read oldest first and the last
+/// version of each record id wins.
+fn scan_leveldb(dir: &Path) -> Result<Vec<Value>, String> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("ldb") | Some("log")
+            )
+        })
+        .collect();
+    // Table files hold compacted (older) data; the log holds recent writes
+    files.sort_by_key(|p| {
+        (
+            p.extension().and_then(|e| e.to_str()) == Some("log"),
+            p.file_name().map(|n| n.to_os_string()),
+        )
+    });
+
+    let mut by_id = BTreeMap::new();
+    let mut unnumbered = Vec::new();
+    for file in files {
+        let Ok(bytes) = fs::read(&file) else {
+            continue;
+        };
+        for record in json_objects(&bytes) {
+            if !is_service_record(&record) {
+                continue;
+            }
+            match record.get("id").and_then(Value::as_i64) {
+                Some(id) => {
+                    by_id.insert(id, record);
+                }
+                None => unnumbered.push(record),
+            }
+        }
+    }
+    let mut records: Vec<_> = by_id.into_values().chain(unnumbered).collect();
+    records.sort_by_key(|r| r.get("position").and_then(Value::as_i64).unwrap_or(0));
+    Ok(records)
+}
+
+/// Every JSON object embedded in `bytes`, skipping anything that does not
+/// parse.
+fn json_objects(bytes: &[u8]) -> Vec<Value> {
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'{' && bytes[i + 1] == b'"' {
+            let mut stream = serde_json::Deserializer::from_slice(&bytes[i..]).into_iter::<Value>();
+            if let Some(Ok(value @ Value::Object(_))) = stream.next() {
+                found.push(value);
+                i += stream.byte_offset();
+                continue;
+            }
+        }
+        i += 1;
+    }
+    found
+}
+
+fn is_service_record(record: &Value) -> bool {
+    record.get("type").is_some_and(Value::is_string)
+        && record.get("url").is_some_and(Value::is_string)
+        && record.get("name").is_some_and(Value::is_string)
+}
+
+fn service_from_record(record: &Value) -> Result<ImportedService, String> {
+    let name = str_field(record, "name");
+    let url = str_field(record, "url").trim().to_string();
+    // Team services store `___` in place of the team until it is filled in
+    if url.is_empty() || url.contains("___") {
+        return Err(format!("Skipped '{name}': it has no complete URL"));
+    }
+    let mut notes = Vec::new();
+    let custom_js = str_field(record, "js_unread").trim().to_string();
+    if !custom_js.is_empty() {
+        notes.push("Custom JS comes from Rambox's unread-count code".to_string());
+    }
+    if bool_field(record, "trust", false) {
+        notes.push("Rambox trusted invalid certificates for this service".to_string());
+    }
+    Ok(ImportedService {
+        recipe: str_field(record, "type"),
+        name,
+        url,
+        custom_css: str_field(record, "custom_css").trim().to_string(),
+        custom_js,
+        notifications: bool_field(record, "notifications", true),
+        muted: bool_field(record, "muted", false),
+        enabled: bool_field(record, "enabled", true),
+        dark_mode: false,
+        notes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const BACKUP: &str = include_str!("fixtures/rambox/rambox-backup.json");
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("orbly-rambox-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_reads_backup_file() {
+        let dir = temp_dir();
+        let path = dir.join("rambox-backup.json");
+        fs::write(&path, BACKUP).unwrap();
+        let mut warnings = Vec::new();
+        let services = read_services(&path, &mut warnings).expect("services");
+
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        let names: Vec<_> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Telegram", "Team Chat", "Intranet"]);
+        let chat = &services[1];
+        assert_eq!(chat.recipe, "slack");
+        assert_eq!(chat.url, "https://team.slack.com/");
+        assert!(chat.muted);
+        assert!(!chat.notifications);
+        let intranet = &services[2];
+        assert_eq!(intranet.recipe, "custom");
+        assert!(intranet.custom_js.contains("unread"));
+        assert!(!intranet.enabled);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_scans_local_storage_records() {
+        let dir = temp_dir();
+        let leveldb = dir.join("Local Storage").join("leveldb");
+        fs::create_dir_all(&leveldb).unwrap();
+
+        // Records as Chromium writes them: key, then a Latin-1 marker byte
+        // and the JSON value, between LevelDB framing bytes
+        let record = |id: u32, name: &str, muted: bool| {
+            let mut bytes = vec![0x5c, 0x1a, 0x00, 0x01, 0x01];
+            bytes.extend_from_slice(format!("_file://\0\x01services-{id}").as_bytes());
+            bytes.extend_from_slice(&[0x8c, 0x01, 0x01]);
+            bytes.extend_from_slice(
+                format!(
+                    r#"{{"position":{id},"type":"discord","logo":"discord.png","name":"{name}","url":"https://discord.com/app","notifications":true,"muted":{muted},"enabled":true,"js_unread":"","id":{id}}}"#
+                )
+                .as_bytes(),
+            );
+            bytes
+        };
+        let mut table = record(1, "Discord", false);
+        table.extend(record(2, "Second", false));
+        fs::write(leveldb.join("000005.ldb"), table).unwrap();
+        // The newer write in the log replaces record 1
+        fs::write(leveldb.join("000007.log"), record(1, "Gaming", true)).unwrap();
+
+        let mut warnings = Vec::new();
+        let services = read_services(&dir, &mut warnings).expect("services");
+        let names: Vec<_> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Gaming", "Second"]);
+        assert!(services[0].muted);
+        assert!(warnings.is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
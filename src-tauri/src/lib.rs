@@ -4,6 +4,7 @@ mod commands;
 mod config;
 mod darkmode;
 mod downloads;
+mod importers;
 mod notifications;
 mod recipes;
 mod resource_monitor;
@@ -110,6 +111,10 @@ pub fn run() {
             app.manage(DownloadManager::new());
             app.manage(ResourceMonitor::new());
             app.manage(WindowStateSaveTimer::new());
+            app.manage(app_manager::popout::PopoutWindows::new());
+            app.manage(app_manager::auth_popup::AuthPopups::new());
+            app.manage(app_manager::data_store::EphemeralStores::new());
+            app.manage(app_manager::unsaved_work::QuitGuard::new());
             app.manage(CertificateExceptions::new());
             app.manage(session_state);
             app.manage(recipe_manager);
@@ -136,6 +141,7 @@ pub fn run() {
                     Ok(rules_text) => {
                         let state = adblock_handle.state::<AdblockState>();
                         state.load_rules(&rules_text, &adblock_config.custom_rules);
+                        let _ = adblock_handle.emit("content-rules-updated", ());
                         log::info!("Adblock engine loaded with filter rules");
                     }
                     Err(e) => {
@@ -162,9 +168,10 @@ pub fn run() {
                         Ok(rules_text) => {
                             let state = periodic_handle.state::<AdblockState>();
                             state.load_rules(&rules_text, &config.adblock.custom_rules);
-                            let mut cfg = config_manager.get_config();
-                            cfg.adblock.last_updated = chrono::Utc::now().to_rfc3339();
-                            let _ = config_manager.save_config(cfg);
+                            let now = chrono::Utc::now().to_rfc3339();
+                            let _ = config_manager.update_quietly("filter list update", |cfg| {
+                                cfg.adblock.last_updated = now;
+                            });
                             let _ = periodic_handle.emit("filter-lists-updated", ());
                             let _ = periodic_handle.emit("content-rules-updated", ());
                             log::info!("Periodic filter list update completed");
@@ -221,14 +228,15 @@ pub fn run() {
                 loop {
                     interval.tick().await;
                     let config_manager = dnd_handle.state::<ConfigManager>();
-                    let mut config = config_manager.get_config();
+                    let config = config_manager.get_config();
                     if !config.general.dnd_schedule_enabled {
                         continue;
                     }
                     let should_be_dnd = crate::notifications::handler::is_in_dnd_schedule(&config);
                     if should_be_dnd != config.general.dnd_enabled {
-                        config.general.dnd_enabled = should_be_dnd;
-                        let _ = config_manager.save_config(config);
+                        let _ = config_manager.update_quietly("dnd schedule", |config| {
+                            config.general.dnd_enabled = should_be_dnd;
+                        });
                         let _ = dnd_handle.emit("dnd-toggled", ());
                     }
                 }
@@ -236,7 +244,25 @@ pub fn run() {
 
             app_manager::start_auto_hibernate_task(app.handle().clone());
             app_manager::start_crash_detection_task(app.handle().clone());
+            app_manager::schedule::start_schedule_task(app.handle().clone());
+
+            // Stores left behind by apps removed without deleting their data
+            let store_handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                app_manager::data_store::clean_orphaned_stores(&store_handle);
+            });
             resource_monitor::poller::start_resource_polling(app.handle().clone());
+            config::watcher::start_config_watcher(app.handle().clone());
+
+            // Merge in the synced config without holding up the window
+            let sync_handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Some(reload) = sync_handle.state::<ConfigManager>().pull_synced() {
+                    log::info!("Applying synced config");
+                    config::watcher::apply_reload(&sync_handle, &reload.old, &reload.new);
+                    let _ = sync_handle.emit("config-reloaded", ());
+                }
+            });
 
             // Restore window state from config
             let ws = app.state::<ConfigManager>().get_config().general.window_state;
@@ -271,6 +297,15 @@ pub fn run() {
                 }
             });
 
+            // Linux: recompile and reattach WebKitGTK content filters when the rules change
+            #[cfg(target_os = "linux")]
+            {
+                let filters_handle = app.handle().clone();
+                app.listen("content-rules-updated", move |_| {
+                    crate::adblock::content_rules::refresh_filters(&filters_handle);
+                });
+            }
+
             // Notification click routing is not exposed by tauri-plugin-notification v2.3.3.
             // Placeholder: once plugin exposes click callbacks, emit `switch-to-app`, and
             // focus/show main window on notification activation.
@@ -290,6 +325,9 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::config_commands::get_config,
+            commands::config_commands::get_config_read_only_reason,
+            commands::config_commands::get_policy,
+            commands::config_commands::validate_config,
             commands::config_commands::get_apps,
             commands::config_commands::get_app,
             commands::config_commands::add_app,
@@ -302,9 +340,27 @@ pub fn run() {
             commands::config_commands::test_link_route,
             commands::config_commands::export_config_json,
             commands::config_commands::import_config_json,
+            commands::config_commands::list_config_snapshots,
+            commands::config_commands::diff_config_snapshot,
+            commands::config_commands::restore_config_snapshot,
             commands::config_commands::update_shortcuts_config,
+            commands::bundle_commands::export_app_bundle,
+            commands::bundle_commands::export_workspace_bundle,
+            commands::bundle_commands::preview_bundle_import,
+            commands::bundle_commands::import_bundle,
+            commands::import_commands::detect_service_imports,
+            commands::import_commands::plan_service_import,
+            commands::import_commands::apply_service_import,
             commands::config_commands::update_workspaces_config,
             commands::app_lifecycle_commands::get_app_states,
+            commands::app_lifecycle_commands::get_app_state_history,
+            commands::app_lifecycle_commands::switch_app_account,
+            commands::app_lifecycle_commands::pop_out_app,
+            commands::app_lifecycle_commands::dock_app,
+            commands::data_store_commands::get_data_store_sizes,
+            commands::data_store_commands::clear_app_data,
+            commands::data_store_commands::reset_app_data,
+            commands::data_store_commands::clean_orphaned_data_stores,
             commands::app_lifecycle_commands::activate_app,
             commands::app_lifecycle_commands::ensure_webview_exists,
             commands::app_lifecycle_commands::hibernate_app,
@@ -333,6 +389,12 @@ pub fn run() {
             commands::download_commands::retry_download,
             commands::download_commands::open_download_file,
             commands::download_commands::open_download_folder,
+            commands::sync_commands::update_sync_config,
+            commands::sync_commands::get_sync_status,
+            commands::sync_commands::sync_now,
+            commands::sync_commands::get_sync_conflicts,
+            commands::sync_commands::resolve_sync_conflict,
+            commands::sync_commands::test_sync_connection,
             commands::workspace_commands::get_workspaces,
             commands::workspace_commands::get_active_workspace,
             commands::workspace_commands::switch_workspace,
@@ -370,14 +432,26 @@ pub fn run() {
             commands::app_lifecycle_commands::apply_layout,
             commands::app_lifecycle_commands::check_unsaved_work,
             commands::app_lifecycle_commands::set_has_unsaved_work,
+            commands::app_lifecycle_commands::save_page_snapshot,
             commands::app_lifecycle_commands::heartbeat,
             commands::link_routing_commands::route_link,
+            commands::auth_popup_commands::auth_popup_message,
+            commands::auth_popup_commands::post_to_auth_popup,
+            commands::auth_popup_commands::close_auth_popup,
             commands::favicon_commands::fetch_favicon,
             commands::native_integration_commands::open_share_sheet_placeholder,
             commands::native_integration_commands::run_shortcut_intent_placeholder,
             commands::native_integration_commands::apply_focus_filter_placeholder,
         ])
         .on_window_event(|window, event| {
+            if let Some(app_id) = app_manager::popout::app_id_of(window.label()) {
+                app_manager::popout::on_window_event(window, app_id, event);
+                return;
+            }
+            if let Some(popup_id) = app_manager::auth_popup::popup_id_of(window.label()) {
+                app_manager::auth_popup::on_window_event(window, popup_id, event);
+                return;
+            }
             match event {
                 tauri::WindowEvent::CloseRequested { api, .. } => {
                     let config_manager =
@@ -389,6 +463,9 @@ pub fn run() {
                     let app_manager = window.app_handle().state::<AppManager>();
                     let apps = app_manager.apps.lock().expect("apps lock");
                     for (app_id, runtime) in apps.iter() {
+                        if config.apps.iter().any(|a| a.id == *app_id && a.ephemeral) {
+                            continue;
+                        }
                         match &runtime.state {
                             AppRuntimeState::Active { current_url } => {
                                 session_state.set_active(app_id, current_url);
@@ -404,8 +481,12 @@ pub fn run() {
                     if config.general.tray_mode {
                         api.prevent_close();
                         let _ = window.hide();
+                    } else if app_manager::unsaved_work::hold_quit(window.app_handle()) {
+                        api.prevent_close();
                     } else {
                         session_state.clear();
+                        crate::app_manager::popout::close_all(window.app_handle());
+                        crate::app_manager::auth_popup::close_all(window.app_handle());
                     }
                 }
                 tauri::WindowEvent::Moved(pos) => {
@@ -414,31 +495,43 @@ pub fn run() {
                         let sf = window.scale_factor().unwrap_or(1.0);
                         let logical = pos.to_logical::<f64>(sf);
                         let cm = window.app_handle().state::<ConfigManager>();
-                        let mut config = cm.get_config();
-                        config.general.window_state.x = Some(logical.x);
-                        config.general.window_state.y = Some(logical.y);
-                        let _ = cm.save_config(config);
+                        let _ = cm.update_quietly("window state", |config| {
+                            config.general.window_state.x = Some(logical.x);
+                            config.general.window_state.y = Some(logical.y);
+                        });
                     }
                 }
                 tauri::WindowEvent::Resized(size) => {
                     let timer = window.app_handle().state::<WindowStateSaveTimer>();
                     if timer.should_save(500) {
                         let cm = window.app_handle().state::<ConfigManager>();
-                        let mut config = cm.get_config();
                         let maximized = window.is_maximized().unwrap_or(false);
-                        config.general.window_state.maximized = maximized;
-                        if !maximized {
-                            let sf = window.scale_factor().unwrap_or(1.0);
-                            let logical = size.to_logical::<f64>(sf);
-                            config.general.window_state.width = Some(logical.width);
-                            config.general.window_state.height = Some(logical.height);
-                        }
-                        let _ = cm.save_config(config);
+                        let sf = window.scale_factor().unwrap_or(1.0);
+                        let logical = size.to_logical::<f64>(sf);
+                        let _ = cm.update_quietly("window state", |config| {
+                            config.general.window_state.maximized = maximized;
+                            if !maximized {
+                                config.general.window_state.width = Some(logical.width);
+                                config.general.window_state.height = Some(logical.height);
+                            }
+                        });
                     }
                 }
                 _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running Orbly");
+        .build(tauri::generate_context!())
+        .expect("error while building Orbly")
+        .run(|app_handle, event| match event {
+            // Quitting from the tray or the app menu
+            tauri::RunEvent::ExitRequested { api, .. } => {
+                if app_manager::unsaved_work::hold_quit(app_handle) {
+                    api.prevent_exit();
+                }
+            }
+            tauri::RunEvent::Exit => {
+                app_manager::data_store::discard_all_ephemeral_stores(app_handle);
+            }
+            _ => {}
+        });
 }
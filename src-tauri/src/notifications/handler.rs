@@ -1,7 +1,8 @@
 use chrono::{Datelike, Timelike};
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::app_manager::session_state::SessionState;
 use crate::app_manager::state::AppManager;
 use crate::config::manager::ConfigManager;
 use crate::config::models::{NotificationStyle, OrblyConfig};
@@ -40,16 +41,21 @@ pub fn on_web_notification(
         None => return Ok(()),
     };
 
-    match app_config.notification_style {
+    // Notifications come from the signed-in account
+    let name = match app_config.current_account() {
+        Some(account) => format!("{} ({})", app_config.name, account.name),
+        None => app_config.name.clone(),
+    };
+    match app_config.active_notification_style() {
         NotificationStyle::Off => {}
         NotificationStyle::Private => {
-            send_native_notification(&app_handle, &app_config.name, "New notification", &notification.app_id)?;
+            send_native_notification(&app_handle, &name, "New notification", &notification.app_id)?;
         }
         NotificationStyle::Full => {
             let title = if notification.title.is_empty() {
-                app_config.name.clone()
+                name
             } else {
-                format!("{}: {}", app_config.name, notification.title)
+                format!("{}: {}", name, notification.title)
             };
             send_native_notification(&app_handle, &title, &notification.body, &notification.app_id)?;
         }
@@ -62,6 +68,7 @@ pub fn on_web_notification(
 pub fn on_badge_update(
     update: BadgeUpdate,
     app_manager: State<'_, AppManager>,
+    config_manager: State<'_, ConfigManager>,
     app_handle: AppHandle,
 ) -> Result<(), String> {
     let count = match update.count {
@@ -71,22 +78,38 @@ pub fn on_badge_update(
         None => None,
     };
 
-    app_manager.set_badge_count(&update.app_id, count);
+    // The page belongs to whichever account is signed in
+    let config = config_manager.get_config();
+    let app_config = config.apps.iter().find(|a| a.id == update.app_id);
+    let account_id = app_config.map(|a| a.active_account.as_str()).unwrap_or("");
+    let before = app_manager.get_state(&update.app_id).map(|r| r.account_badges);
+    app_manager.set_badge_count(&update.app_id, account_id, count);
+    let runtime = app_manager.get_state(&update.app_id);
+    if let Some(session_state) = app_handle.try_state::<SessionState>() {
+        session_state.set_badge(&update.app_id, runtime.as_ref().and_then(|r| r.badge_count));
+    }
 
     let _ = app_handle.emit(
         "badge-updated",
         serde_json::json!({
             "appId": update.app_id,
-            "count": count,
+            "count": runtime.as_ref().and_then(|r| r.badge_count),
+            "accounts": runtime.as_ref().map(|r| r.account_badges.clone()),
         }),
     );
 
     update_aggregated_badge(&app_manager, &app_handle);
+    // The tray lists each account with its badge
+    if app_config.is_some_and(|a| !a.accounts.is_empty())
+        && before != runtime.map(|r| r.account_badges)
+    {
+        crate::tray::rebuild_tray_menu(&app_handle);
+    }
 
     Ok(())
 }
 
-fn send_native_notification(app_handle: &AppHandle, title: &str, body: &str, app_id: &str) -> Result<(), String> {
+pub fn send_native_notification(app_handle: &AppHandle, title: &str, body: &str, app_id: &str) -> Result<(), String> {
     app_handle
         .notification()
         .builder()
@@ -107,15 +130,7 @@ pub fn is_in_dnd_schedule(config: &OrblyConfig) -> bool {
     let now = chrono::Local::now();
 
     // Check day of week using locale-independent weekday matching
-    let day_str = match now.weekday() {
-        chrono::Weekday::Mon => "mon",
-        chrono::Weekday::Tue => "tue",
-        chrono::Weekday::Wed => "wed",
-        chrono::Weekday::Thu => "thu",
-        chrono::Weekday::Fri => "fri",
-        chrono::Weekday::Sat => "sat",
-        chrono::Weekday::Sun => "sun",
-    };
+    let day_str = day_key(now.weekday());
     let day_enabled = config
         .general
         .dnd_schedule_days
@@ -145,15 +160,7 @@ pub fn is_in_dnd_schedule(config: &OrblyConfig) -> bool {
             return true;
         }
         if current_minutes < end {
-            let prev_day = match now.weekday() {
-                chrono::Weekday::Mon => "sun",
-                chrono::Weekday::Tue => "mon",
-                chrono::Weekday::Wed => "tue",
-                chrono::Weekday::Thu => "wed",
-                chrono::Weekday::Fri => "thu",
-                chrono::Weekday::Sat => "fri",
-                chrono::Weekday::Sun => "sat",
-            };
+            let prev_day = day_key(now.weekday().pred());
             return config.general.dnd_schedule_days.iter().any(|d| d == prev_day);
         }
         // Overnight schedule (e.g., 18:00 - 09:00): DND is active OUTSIDE the inverse window
@@ -161,6 +168,19 @@ pub fn is_in_dnd_schedule(config: &OrblyConfig) -> bool {
     }
 }
 
+/// The key a weekday has in schedule day lists, e.g. `mon`.
+pub fn day_key(weekday: chrono::Weekday) -> &'static str {
+    match weekday {
+        chrono::Weekday::Mon => "mon",
+        chrono::Weekday::Tue => "tue",
+        chrono::Weekday::Wed => "wed",
+        chrono::Weekday::Thu => "thu",
+        chrono::Weekday::Fri => "fri",
+        chrono::Weekday::Sat => "sat",
+        chrono::Weekday::Sun => "sun",
+    }
+}
+
 pub fn parse_time(time_str: &str) -> Option<u32> {
     let parts: Vec<&str> = time_str.split(':').collect();
     if parts.len() != 2 {
@@ -168,22 +188,19 @@ pub fn parse_time(time_str: &str) -> Option<u32> {
     }
     let hours: u32 = parts[0].parse().ok()?;
     let minutes: u32 = parts[1].parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
     Some(hours * 60 + minutes)
 }
 
+/// Sum the badges of every account of every app for the tray and dock.
 fn update_aggregated_badge(app_manager: &AppManager, app_handle: &AppHandle) {
     let (total, has_unread_dot) = {
         let apps = app_manager.apps.lock().expect("apps lock");
-        let total: u32 = apps
-            .values()
-            .filter_map(|a| a.badge_count)
-            .filter(|c| *c > 0)
-            .map(|c| c as u32)
-            .sum();
-        let has_unread_dot = total == 0
-            && apps
-                .values()
-                .any(|a| matches!(a.badge_count, Some(c) if c < 0));
+        let counts = || apps.values().flat_map(|a| a.account_badges.values().copied());
+        let total: u32 = counts().filter(|c| *c > 0).map(|c| c as u32).sum();
+        let has_unread_dot = total == 0 && counts().any(|c| c < 0);
         (total, has_unread_dot)
     };
 
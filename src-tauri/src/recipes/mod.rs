@@ -20,6 +20,11 @@ pub struct ServiceRecipe {
     pub injection_css: Option<String>,
     pub injection_js: Option<String>,
     pub recommended_user_agent: Option<String>,
+    /// Sign-in pages of the service, as in `AppConfig::login_url_patterns`.
+    /// Not covered by `sha256`: they only decide when an app counts as
+    /// signed out.
+    #[serde(default)]
+    pub login_url_patterns: Vec<String>,
     pub sha256: String,
 }
 
@@ -182,6 +187,7 @@ impl RecipeManager {
                     injection_css: css,
                     injection_js: js,
                     recommended_user_agent: None,
+                    login_url_patterns: Vec::new(),
                     sha256: String::new(),
                 });
             }
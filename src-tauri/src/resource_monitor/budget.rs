@@ -0,0 +1,152 @@
+//! Memory budget for the whole process tree.
+//!
+//! When Orbly uses more memory than `general.memory_budget_mb`, the least
+//! recently used apps that can safely go are hibernated until the estimate is
+//! back under budget. Webviews share helper processes, so apps are not
+//! measured one by one; each active app counts for an equal share.
+
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::app_manager::hibernate_in_background;
+use crate::app_manager::state::{AppManager, AppRuntime, AppRuntimeState};
+use crate::config::manager::ConfigManager;
+use crate::config::models::AppConfig;
+
+/// Time for closed webviews to give their memory back before acting again.
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Why an app must not be hibernated to save memory, or `None` if it may.
+pub fn skip_reason(runtime: &AppRuntime, app_config: Option<&AppConfig>) -> Option<&'static str> {
+    if !matches!(runtime.state, AppRuntimeState::Active { .. }) {
+        Some("not active")
+    } else if runtime.is_visible {
+        Some("visible")
+    } else if runtime.is_playing_media {
+        Some("playing media")
+    } else if runtime.has_unsaved_work {
+        Some("unsaved work")
+    } else if app_config.is_some_and(|a| a.pinned) {
+        Some("pinned")
+    } else {
+        None
+    }
+}
+
+/// The least recently used `candidates`, as many as it takes to bring
+/// `used_mb` under `budget_mb` at `per_app_mb` each.
+pub fn pick(
+    mut candidates: Vec<(String, Option<Instant>)>,
+    used_mb: f64,
+    budget_mb: f64,
+    per_app_mb: f64,
+) -> Vec<String> {
+    if used_mb <= budget_mb || per_app_mb <= 0.0 {
+        return Vec::new();
+    }
+    let needed = ((used_mb - budget_mb) / per_app_mb).ceil().max(1.0) as usize;
+    // Apps never interacted with sort first
+    candidates.sort_by_key(|(_, last_interaction)| *last_interaction);
+    candidates
+        .into_iter()
+        .take(needed)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Hibernate apps if `used_mb`, measured with `active_count` apps active,
+/// is over the budget.
+pub fn enforce(app_handle: &AppHandle, used_mb: f64, active_count: usize) {
+    let config = app_handle.state::<ConfigManager>().get_config();
+    let budget_mb = config.general.memory_budget_mb;
+    if budget_mb == 0 || active_count == 0 || used_mb <= budget_mb as f64 {
+        return;
+    }
+    let monitor = app_handle.state::<super::ResourceMonitor>();
+    if monitor
+        .budget_acted
+        .lock()
+        .expect("budget lock")
+        .is_some_and(|t| t.elapsed() < COOLDOWN)
+    {
+        return;
+    }
+
+    let apps = app_handle
+        .state::<AppManager>()
+        .apps
+        .lock()
+        .expect("apps lock")
+        .clone();
+    let mut candidates = Vec::new();
+    for (app_id, runtime) in &apps {
+        let app_config = config.apps.iter().find(|a| a.id == *app_id);
+        match skip_reason(runtime, app_config) {
+            None => candidates.push((app_id.clone(), runtime.last_interaction)),
+            Some("not active") => {}
+            Some(reason) => log::debug!("Memory budget: keeping '{}' ({})", app_id, reason),
+        }
+    }
+    let chosen = pick(
+        candidates,
+        used_mb,
+        budget_mb as f64,
+        used_mb / active_count as f64,
+    );
+    if chosen.is_empty() {
+        log::debug!(
+            "Memory use {:.0} MB is over the {} MB budget but no app can be hibernated",
+            used_mb,
+            budget_mb
+        );
+        return;
+    }
+    *monitor.budget_acted.lock().expect("budget lock") = Some(Instant::now());
+
+    for app_id in chosen {
+        let name = config
+            .apps
+            .iter()
+            .find(|a| a.id == app_id)
+            .map(|a| a.name.clone())
+            .unwrap_or_else(|| app_id.clone());
+        if hibernate_in_background(app_handle, &app_id, "memory budget") {
+            let reason = format!(
+                "Orbly was using {:.0} MB of its {} MB memory budget and {} was the least recently used app",
+                used_mb, budget_mb, name
+            );
+            log::info!("Hibernated '{}': {}", app_id, reason);
+            let _ = app_handle.emit(
+                "app-budget-hibernated",
+                serde_json::json!({ "appId": app_id, "reason": reason }),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_takes_least_recently_used_until_under_budget() {
+        let now = Instant::now();
+        let earlier = now - Duration::from_secs(600);
+        let candidates = vec![
+            ("recent".to_string(), Some(now)),
+            ("old".to_string(), Some(earlier)),
+            ("never".to_string(), None),
+        ];
+        // 2500 MB used against 2000: two apps at 300 MB each
+        assert_eq!(
+            pick(candidates.clone(), 2500.0, 2000.0, 300.0),
+            vec!["never", "old"]
+        );
+        assert_eq!(
+            pick(candidates.clone(), 2100.0, 2000.0, 300.0),
+            vec!["never"]
+        );
+        assert!(pick(candidates, 1900.0, 2000.0, 300.0).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod budget;
 pub mod poller;
 
 use std::collections::HashMap;
@@ -19,6 +20,8 @@ pub struct ResourceMonitor {
     usage: Mutex<HashMap<String, AppResourceUsage>>,
     alerted: Mutex<HashMap<String, std::time::Instant>>,
     pub(crate) sys: Mutex<System>,
+    /// When the memory budget last hibernated apps
+    budget_acted: Mutex<Option<std::time::Instant>>,
 }
 
 #[allow(dead_code)]
@@ -28,6 +31,7 @@ impl ResourceMonitor {
             usage: Mutex::new(HashMap::new()),
             alerted: Mutex::new(HashMap::new()),
             sys: Mutex::new(System::new()),
+            budget_acted: Mutex::new(None),
         }
     }
 
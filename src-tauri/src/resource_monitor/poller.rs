@@ -105,6 +105,7 @@ pub fn start_resource_polling(app_handle: AppHandle) {
 
             let _ = app_handle.emit("resource-usage-updated", &usages);
             check_high_usage_alerts(&app_handle, &usages);
+            super::budget::enforce(&app_handle, total_memory, active_count);
         }
     });
 }
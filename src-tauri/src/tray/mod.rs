@@ -1,6 +1,6 @@
 use tauri::{
     AppHandle, Emitter, Manager,
-    menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder},
+    menu::{CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, SubmenuBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
 };
 
@@ -64,13 +64,31 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                     let cm = app_handle.state::<ConfigManager>();
                     let mut config = cm.get_config();
                     config.general.dnd_enabled = !config.general.dnd_enabled;
-                    let _ = cm.save_config(config);
+                    let _ = cm.save_config("tray toggle_dnd", config);
                     rebuild_tray_menu(app_handle);
                     let _ = app_handle.emit("dnd-toggled", ());
                 }
                 "quit" => {
                     app_handle.exit(0);
                 }
+                id if id.starts_with("account_") => {
+                    // `account_<app id>:<account id>`; account ids have no colons
+                    let Some((app_id, account_id)) =
+                        id.strip_prefix("account_").and_then(|rest| rest.rsplit_once(':'))
+                    else {
+                        return;
+                    };
+                    if let Err(e) =
+                        crate::app_manager::accounts::switch_account(app_handle, app_id, account_id)
+                    {
+                        log::warn!("Failed to switch account of {}: {}", app_id, e);
+                    }
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                    let _ = app_handle.emit("switch-to-app", app_id.to_string());
+                }
                 id if id.starts_with("app_") => {
                     let app_id = id.strip_prefix("app_").unwrap();
                     if let Some(window) = app_handle.get_webview_window("main") {
@@ -98,6 +116,9 @@ pub fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         })
         .build(app)?;
 
+    // Apps with several accounts get a submenu
+    rebuild_tray_menu(app.handle());
+
     Ok(())
 }
 
@@ -112,8 +133,41 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle) {
     let Ok(show_hide) = MenuItemBuilder::with_id("show_hide", "Show/Hide Orbly").build(app_handle) else { return };
     let mut menu_builder = MenuBuilder::new(app_handle).item(&show_hide).separator();
 
+    let app_manager = app_handle.state::<crate::app_manager::state::AppManager>();
     for app_config in &config.apps {
-        if app_config.enabled {
+        if app_config.enabled && !app_config.accounts.is_empty() {
+            // One entry per account with its badge; picking one switches to it
+            let badges = app_manager
+                .get_state(&app_config.id)
+                .map(|r| r.account_badges)
+                .unwrap_or_default();
+            let accounts = std::iter::once(("", app_config.default_account_name())).chain(
+                app_config
+                    .accounts
+                    .iter()
+                    .map(|a| (a.id.as_str(), a.name.as_str())),
+            );
+            let mut submenu = SubmenuBuilder::new(app_handle, &app_config.name);
+            for (account_id, name) in accounts {
+                let label = match badges.get(account_id) {
+                    Some(count) if *count > 0 => format!("{} ({})", name, count),
+                    Some(count) if *count < 0 => format!("{} •", name),
+                    _ => name.to_string(),
+                };
+                if let Ok(item) = CheckMenuItemBuilder::with_id(
+                    &format!("account_{}:{}", app_config.id, account_id),
+                    &label,
+                )
+                .checked(app_config.active_account == account_id)
+                .build(app_handle)
+                {
+                    submenu = submenu.item(&item);
+                }
+            }
+            if let Ok(submenu) = submenu.build() {
+                menu_builder = menu_builder.item(&submenu);
+            }
+        } else if app_config.enabled {
             if let Ok(item) = MenuItemBuilder::with_id(
                 &format!("app_{}", app_config.id),
                 &app_config.name,
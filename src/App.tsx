@@ -72,7 +72,7 @@ function createShortcutHandlers(args: {
     },
     reloadCurrentApp: () => {
       const id = activeAppId();
-      if (id) reloadApp(id);
+      if (id) reloadApp(id).catch((err) => showToast(`${err}; reload cancelled`, "warning"));
     },
     appsManager: () => setAppsManagerVisible((v) => !v),
     downloads: () => {
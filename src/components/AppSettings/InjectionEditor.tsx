@@ -1,5 +1,6 @@
 import { Component, createSignal } from "solid-js";
 import { updateApp, reloadApp } from "../../lib/ipc";
+import { showToast } from "../Toast/ToastContainer";
 import type { AppConfig } from "../../types/config";
 
 interface InjectionEditorProps {
@@ -23,7 +24,9 @@ const InjectionEditor: Component<InjectionEditorProps> = (props) => {
         custom_js: js(),
       };
       await updateApp(updated);
-      await reloadApp(props.app.id);
+      await reloadApp(props.app.id).catch((err) =>
+        showToast(`${err}; reload it to apply the changes`, "warning"),
+      );
       props.onSave(updated);
     } catch (err) {
       console.error("Failed to save injection settings:", err);
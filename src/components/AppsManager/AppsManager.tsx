@@ -1,7 +1,7 @@
 import { Component, For, Show, createSignal, createEffect, onCleanup } from "solid-js";
 import { listen } from "@tauri-apps/api/event";
 import { getResourceUsage, reloadApp, hibernateApp, disableApp, enableApp, killApp, getConfig, checkUnsavedWork } from "../../lib/ipc";
-import { appConfigs } from "../../stores/uiStore";
+import { appConfigs, appStates } from "../../stores/uiStore";
 import { showToast } from "../Toast/ToastContainer";
 
 interface AppsManagerProps {
@@ -118,6 +118,10 @@ const AppsManager: Component<AppsManagerProps> = (props) => {
     try {
       switch (action) {
         case "reload":
+          if (await checkUnsavedWork(appId)) {
+            showToast("Unsaved work detected; reload cancelled", "warning");
+            break;
+          }
           await reloadApp(appId);
           break;
         case "hibernate":
@@ -131,7 +135,7 @@ const AppsManager: Component<AppsManagerProps> = (props) => {
                 break;
               }
             }
-            await hibernateApp(appId);
+            await hibernateApp(appId, appConfig?.suppress_hibernate_confirm ?? false);
           }
           break;
         case "disable":
@@ -228,6 +232,11 @@ const AppsManager: Component<AppsManagerProps> = (props) => {
                         <td class="px-3 py-2">
                           <span class="mr-1">{statusIcon(usage.status)}</span>
                           {usage.status}
+                          <Show when={(appStates.find((s) => s.id === usage.app_id)?.crash_count ?? 0) > 0}>
+                            <span class="ml-1 text-xs text-gray-400">
+                              ({appStates.find((s) => s.id === usage.app_id)?.crash_count} crashes)
+                            </span>
+                          </Show>
                         </td>
                         <td class="text-right px-3 py-2">
                           {usage.cpu_percent !== null ? `${usage.cpu_percent.toFixed(1)}%` : "—"}
@@ -33,6 +33,7 @@ const Onboarding: Component<OnboardingProps> = (props) => {
         custom_css: "",
         custom_js: "",
         proxy: "",
+        proxy_credentials: "",
         dark_mode: "off",
         dark_mode_brightness: 100,
         dark_mode_contrast: 90,
@@ -51,6 +52,19 @@ const Onboarding: Component<OnboardingProps> = (props) => {
         zoom_level: 100,
         suppress_high_usage_alert: false,
         suppress_hibernate_confirm: false,
+        crash_recovery: "reload",
+        pinned: false,
+        schedule: null,
+        restore_form_fields: false,
+        accounts: [],
+        account_name: "",
+        active_account: "",
+        detached: false,
+        popout_window: { maximized: false },
+        ephemeral: false,
+        launch: "restore",
+        launch_delay_secs: 15,
+        login_url_patterns: [],
       };
       try {
         await addApp(app);
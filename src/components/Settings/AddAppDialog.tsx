@@ -5,6 +5,7 @@ import { addApp, fetchFavicon } from "../../lib/ipc";
 import { refreshAppConfigs } from "../../lib/stateSync";
 import { appConfigs } from "../../stores/uiStore";
 import type { AppConfig } from "../../types/config";
+import { showToast } from "../Toast/ToastContainer";
 
 interface AddAppDialogProps {
   onClose: () => void;
@@ -91,6 +92,7 @@ const AddAppDialog: Component<AddAppDialogProps> = (props) => {
         custom_css: "",
         custom_js: "",
         proxy: "",
+        proxy_credentials: "",
         dark_mode: "off",
         dark_mode_brightness: 100,
         dark_mode_contrast: 90,
@@ -109,12 +111,26 @@ const AddAppDialog: Component<AddAppDialogProps> = (props) => {
         zoom_level: 100,
         suppress_high_usage_alert: false,
         suppress_hibernate_confirm: false,
+        crash_recovery: "reload",
+        pinned: false,
+        schedule: null,
+        restore_form_fields: false,
+        accounts: [],
+        account_name: "",
+        active_account: "",
+        detached: false,
+        popout_window: { maximized: false },
+        ephemeral: false,
+        launch: "restore",
+        launch_delay_secs: 15,
+        login_url_patterns: [],
       };
       await addApp(newApp);
       await refreshAppConfigs();
       props.onClose();
     } catch (err) {
       console.error("Failed to add app:", err);
+      showToast(`Failed to add app: ${err}`, "error");
     } finally {
       setAdding(false);
     }
@@ -0,0 +1,189 @@
+import { Component, For, Show, createSignal, onMount } from "solid-js";
+import { Portal } from "solid-js/web";
+import { open } from "@tauri-apps/plugin-dialog";
+import { SERVICE_TEMPLATES } from "../../lib/serviceTemplates";
+import { applyServiceImport, detectServiceImports, planServiceImport } from "../../lib/ipc";
+import { refreshAppConfigs } from "../../lib/stateSync";
+import { showToast } from "../Toast/ToastContainer";
+import type { DetectedSource, ImportPlan, ImportSource } from "../../types/config";
+
+interface ImportServicesDialogProps {
+  onClose: () => void;
+}
+
+const SOURCES: { id: ImportSource; label: string; pickFile: string }[] = [
+  { id: "ferdium", label: "Ferdium / Ferdi", pickFile: "server.sqlite" },
+  { id: "franz", label: "Franz", pickFile: "server.sqlite" },
+  { id: "rambox", label: "Rambox", pickFile: "backup file" },
+];
+
+const ImportServicesDialog: Component<ImportServicesDialogProps> = (props) => {
+  const [detected, setDetected] = createSignal<DetectedSource[]>([]);
+  const [source, setSource] = createSignal<ImportSource>("ferdium");
+  const [plan, setPlan] = createSignal<ImportPlan | null>(null);
+  const [selected, setSelected] = createSignal<Set<number>>(new Set());
+  const [loading, setLoading] = createSignal(false);
+
+  onMount(async () => {
+    try {
+      const found = await detectServiceImports();
+      setDetected(found);
+      if (found.length > 0) setSource(found[0].source);
+    } catch (err) {
+      console.error("Failed to detect imports:", err);
+    }
+  });
+
+  const detectedPath = (id: ImportSource) => detected().find((d) => d.source === id)?.path;
+
+  const loadPlan = async (path?: string) => {
+    setLoading(true);
+    try {
+      const result = await planServiceImport(source(), path);
+      setPlan(result);
+      // Services that duplicate an existing app start unticked
+      setSelected(new Set(result.items.flatMap((item, i) => (item.existing ? [] : [i]))));
+    } catch (err) {
+      setPlan(null);
+      showToast(`${err}`, "error", 6000);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const choosePath = async (directory: boolean) => {
+    try {
+      const chosen = await open({ directory });
+      if (chosen) await loadPlan(chosen as string);
+    } catch {}
+  };
+
+  const toggle = (idx: number) => {
+    const next = new Set(selected());
+    if (next.has(idx)) next.delete(idx);
+    else next.add(idx);
+    setSelected(next);
+  };
+
+  const handleImport = async () => {
+    const current = plan();
+    if (!current) return;
+    const apps = current.items
+      .filter((_, i) => selected().has(i))
+      .map((item) => ({
+        ...item.app,
+        icon: SERVICE_TEMPLATES.find((t) => t.id === item.app.service_type)?.icon ?? "🌐",
+      }));
+    setLoading(true);
+    try {
+      const ids = await applyServiceImport(apps);
+      await refreshAppConfigs();
+      showToast(`Imported ${ids.length} app${ids.length === 1 ? "" : "s"}`, "info", 3000);
+      props.onClose();
+    } catch (err) {
+      showToast(`Import failed: ${err}`, "error", 6000);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <Portal>
+      <div
+        class="fixed inset-0 bg-black/30 backdrop-blur-sm z-[60] flex items-center justify-center"
+        onClick={(e) => { if (e.target === e.currentTarget) props.onClose(); }}
+      >
+        <div class="w-[550px] max-h-[600px] bg-white dark:bg-[#2D2D2D] rounded-xl shadow-2xl flex flex-col overflow-hidden">
+          <div class="px-6 pt-5 pb-3">
+            <div class="flex items-center justify-between mb-3">
+              <h2 class="text-base font-semibold text-gray-800 dark:text-gray-200">Import from Another App</h2>
+              <button onClick={props.onClose} class="text-gray-400 hover:text-gray-600 dark:hover:text-gray-200 cursor-pointer" aria-label="Close dialog">✕</button>
+            </div>
+            <div class="flex gap-2 flex-wrap mb-3">
+              <For each={SOURCES}>
+                {(s) => (
+                  <button
+                    class={`px-3 py-1 text-xs rounded-full cursor-pointer transition-colors ${source() === s.id ? "bg-blue-500 text-white" : "bg-gray-100 dark:bg-gray-800 text-gray-600 dark:text-gray-400"}`}
+                    onClick={() => { setSource(s.id); setPlan(null); }}
+                  >
+                    {s.label}{detectedPath(s.id) ? " ✓" : ""}
+                  </button>
+                )}
+              </For>
+            </div>
+            <p class="text-xs text-gray-500 dark:text-gray-400 mb-3 truncate">
+              {detectedPath(source()) ? `Found at ${detectedPath(source())}` : "Not found in the standard location"}
+            </p>
+            <div class="flex gap-2">
+              <button
+                class="px-3 py-1.5 text-xs bg-blue-500 text-white rounded-md hover:bg-blue-600 cursor-pointer disabled:opacity-50"
+                disabled={loading() || !detectedPath(source())}
+                onClick={() => loadPlan()}
+              >Read services</button>
+              <button
+                class="px-3 py-1.5 text-xs bg-gray-100 dark:bg-gray-700 text-gray-700 dark:text-gray-300 rounded-md hover:bg-gray-200 dark:hover:bg-gray-600 cursor-pointer"
+                disabled={loading()}
+                onClick={() => choosePath(true)}
+              >Choose folder...</button>
+              <button
+                class="px-3 py-1.5 text-xs bg-gray-100 dark:bg-gray-700 text-gray-700 dark:text-gray-300 rounded-md hover:bg-gray-200 dark:hover:bg-gray-600 cursor-pointer"
+                disabled={loading()}
+                onClick={() => choosePath(false)}
+              >Choose {SOURCES.find((s) => s.id === source())?.pickFile}...</button>
+            </div>
+          </div>
+
+          <Show when={plan()}>
+            {(current) => (
+              <div class="flex-1 overflow-y-auto px-6 pb-5">
+                <For each={current().warnings}>
+                  {(w) => <p class="text-xs text-amber-500 mb-1">{w}</p>}
+                </For>
+                <div class="space-y-1 mt-2">
+                  <For each={current().items}>
+                    {(item, i) => (
+                      <label class="flex items-start gap-3 py-2 px-2 rounded-md hover:bg-gray-50 dark:hover:bg-gray-800 cursor-pointer">
+                        <input type="checkbox" class="mt-1" checked={selected().has(i())} onChange={() => toggle(i())} />
+                        <div class="min-w-0">
+                          <p class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                            {item.app.name}
+                            <span class="ml-2 text-xs font-normal text-gray-400">{item.app.service_type}</span>
+                          </p>
+                          <p class="text-xs text-gray-400 truncate">{item.app.url}</p>
+                          <p class="text-xs text-gray-400">
+                            {item.app.notification_style === "off" ? "Notifications off" : "Notifications on"}
+                            {item.app.audio_muted ? " · Muted" : ""}
+                            {item.app.enabled ? "" : " · Disabled"}
+                            {item.app.custom_css ? " · Custom CSS" : ""}
+                            {item.app.custom_js ? " · Custom JS" : ""}
+                          </p>
+                          <Show when={item.existing}>
+                            <p class="text-xs text-amber-500">Same URL as existing app "{item.existing}"</p>
+                          </Show>
+                          <For each={item.notes}>
+                            {(note) => <p class="text-xs text-gray-500 dark:text-gray-400">{note}</p>}
+                          </For>
+                        </div>
+                      </label>
+                    )}
+                  </For>
+                </div>
+                <div class="flex justify-end mt-4">
+                  <button
+                    class="px-4 py-2 bg-blue-500 text-white text-sm font-medium rounded-lg hover:bg-blue-600 cursor-pointer disabled:opacity-50"
+                    onClick={handleImport}
+                    disabled={loading() || selected().size === 0}
+                  >
+                    {loading() ? "Importing..." : `Import ${selected().size} App${selected().size === 1 ? "" : "s"}`}
+                  </button>
+                </div>
+              </div>
+            )}
+          </Show>
+        </div>
+      </div>
+    </Portal>
+  );
+};
+
+export default ImportServicesDialog;
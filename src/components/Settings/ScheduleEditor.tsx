@@ -0,0 +1,86 @@
+import { Component, For, Show } from "solid-js";
+import { SettingRow, ToggleSwitch } from "./SettingsControls";
+import type { HibernationSchedule } from "../../types/config";
+
+const DAYS = [
+  { value: "mon", label: "Mon" },
+  { value: "tue", label: "Tue" },
+  { value: "wed", label: "Wed" },
+  { value: "thu", label: "Thu" },
+  { value: "fri", label: "Fri" },
+  { value: "sat", label: "Sat" },
+  { value: "sun", label: "Sun" },
+];
+
+const DEFAULT_SCHEDULE: HibernationSchedule = {
+  wake_at: "08:45",
+  hibernate_at: "18:00",
+  days: ["mon", "tue", "wed", "thu", "fri"],
+};
+
+// Wake/hibernate times for an app or workspace. `null` means no schedule.
+const ScheduleEditor: Component<{
+  schedule: HibernationSchedule | null;
+  onChange: (schedule: HibernationSchedule | null) => void;
+  description: string;
+}> = (props) => {
+  const update = (patch: Partial<HibernationSchedule>) => {
+    if (props.schedule) props.onChange({ ...props.schedule, ...patch });
+  };
+
+  const toggleDay = (day: string) => {
+    const days = props.schedule?.days ?? [];
+    update({ days: days.includes(day) ? days.filter((d) => d !== day) : [...days, day] });
+  };
+
+  return (
+    <>
+      <SettingRow label="Schedule" description={props.description}>
+        <ToggleSwitch
+          checked={props.schedule !== null}
+          onChange={(v) => props.onChange(v ? { ...DEFAULT_SCHEDULE } : null)}
+        />
+      </SettingRow>
+      <Show when={props.schedule}>
+        {(schedule) => (
+          <div class="py-3 border-b border-gray-100 dark:border-gray-800 pl-4 space-y-3">
+            <div class="flex items-center gap-4">
+              <label class="text-xs text-gray-500 w-16">Wake</label>
+              <input
+                type="time"
+                value={schedule().wake_at}
+                onInput={(e) => update({ wake_at: e.currentTarget.value })}
+                class="bg-gray-100 dark:bg-gray-700 border border-gray-200 dark:border-gray-600 rounded-md px-2 py-1 text-sm text-gray-800 dark:text-gray-200"
+              />
+              <label class="text-xs text-gray-500 w-16">Hibernate</label>
+              <input
+                type="time"
+                value={schedule().hibernate_at}
+                onInput={(e) => update({ hibernate_at: e.currentTarget.value })}
+                class="bg-gray-100 dark:bg-gray-700 border border-gray-200 dark:border-gray-600 rounded-md px-2 py-1 text-sm text-gray-800 dark:text-gray-200"
+              />
+            </div>
+            <div class="flex gap-1">
+              <For each={DAYS}>
+                {(day) => (
+                  <button
+                    class={`px-2 py-1 text-xs rounded cursor-pointer ${
+                      schedule().days.includes(day.value)
+                        ? "bg-blue-500 text-white"
+                        : "bg-gray-100 dark:bg-gray-700 text-gray-600 dark:text-gray-400"
+                    }`}
+                    onClick={() => toggleDay(day.value)}
+                  >
+                    {day.label}
+                  </button>
+                )}
+              </For>
+            </div>
+          </div>
+        )}
+      </Show>
+    </>
+  );
+};
+
+export default ScheduleEditor;
@@ -1,4 +1,5 @@
 import { Component, Show, JSX } from "solid-js";
+import { isLocked } from "../../stores/uiStore";
 
 // Section heading with optional description
 export const SettingSection: Component<{ title: string; description?: string }> = (props) => (
@@ -10,18 +11,25 @@ export const SettingSection: Component<{ title: string; description?: string }>
   </div>
 );
 
-// A labeled row with a control on the right
-export const SettingRow: Component<{ label: string; description?: string; children: JSX.Element }> = (props) => (
-  <div class="flex items-start justify-between py-3 border-b border-gray-100 dark:border-gray-800">
-    <div class="pr-4">
-      <p class="text-sm font-medium text-gray-800 dark:text-gray-200">{props.label}</p>
-      <Show when={props.description}>
-        <p class="text-xs text-gray-400 dark:text-gray-500 mt-0.5">{props.description}</p>
-      </Show>
+// A labeled row with a control on the right. `policyPath` names the config
+// field the row edits; the control is disabled when the policy locks it.
+export const SettingRow: Component<{ label: string; description?: string; policyPath?: string; children: JSX.Element }> = (props) => {
+  const locked = () => !!props.policyPath && isLocked(props.policyPath);
+  return (
+    <div class="flex items-start justify-between py-3 border-b border-gray-100 dark:border-gray-800">
+      <div class="pr-4">
+        <p class="text-sm font-medium text-gray-800 dark:text-gray-200">{props.label}</p>
+        <Show when={props.description}>
+          <p class="text-xs text-gray-400 dark:text-gray-500 mt-0.5">{props.description}</p>
+        </Show>
+        <Show when={locked()}>
+          <p class="text-xs text-amber-500 mt-0.5">🔒 Set by your administrator</p>
+        </Show>
+      </div>
+      <fieldset class={`flex-shrink-0 ${locked() ? "opacity-50 pointer-events-none" : ""}`} disabled={locked()}>{props.children}</fieldset>
     </div>
-    <div class="flex-shrink-0">{props.children}</div>
-  </div>
-);
+  );
+};
 
 // Toggle switch
 export const ToggleSwitch: Component<{ checked: boolean; onChange: (v: boolean) => void; disabled?: boolean }> = (props) => (
@@ -5,6 +5,7 @@ import { SettingSection, SettingRow, ToggleSwitch, TextInput, Button } from "../
 import { getConfig, updateFilterLists, updateApp, addCustomAdblockRule, updateAdblockConfig } from "../../../lib/ipc";
 import { refreshAppConfigs } from "../../../lib/stateSync";
 import RecipeStatusPanel from "../RecipeStatusPanel";
+import { showToast } from "../../Toast/ToastContainer";
 import { appConfigs } from "../../../stores/uiStore";
 import type { AdblockConfig } from "../../../types/config";
 
@@ -47,6 +48,7 @@ const AdBlockingTab: Component = () => {
       await updateAdblockConfig({ ...adblock });
     } catch (err) {
       console.error("Failed to save adblock config:", err);
+      showToast(`Failed to save settings: ${err}`, "error");
     }
   };
 
@@ -110,7 +112,7 @@ const AdBlockingTab: Component = () => {
     <div>
       <SettingSection title="Ad Blocking" description="Block ads and trackers across your apps" />
 
-      <SettingRow label="Enable ad blocking" description="Global toggle for all apps">
+      <SettingRow label="Enable ad blocking" policyPath="adblock.enabled" description="Global toggle for all apps">
         <ToggleSwitch checked={adblock.enabled} onChange={(v) => saveAdblock({ enabled: v })} />
       </SettingRow>
 
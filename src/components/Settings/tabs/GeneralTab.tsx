@@ -4,6 +4,7 @@ import { SettingSection, SettingRow, ToggleSwitch, SelectDropdown, TextInput } f
 import { getConfig, updateGeneralConfig, getCertificateExceptions, removeCertificateException, setLaunchAtLogin } from "../../../lib/ipc";
 import { setTheme } from "../../../stores/uiStore";
 import RecipeStatusPanel from "../RecipeStatusPanel";
+import { showToast } from "../../Toast/ToastContainer";
 import type { GeneralConfig } from "../../../types/config";
 
 const GeneralTab: Component = () => {
@@ -25,9 +26,12 @@ const GeneralTab: Component = () => {
     selection_color: null,
     local_scripts_only: false,
     cpu_alert_threshold: 30,
+    memory_budget_mb: 0,
     sidebar_hover_expand: true,
     recipe_cache_ttl_hours: 24,
     recipe_manifest_url: null,
+    startup_concurrency: 2,
+    defer_idle_apps: false,
     window_state: { maximized: false },
   });
 
@@ -56,6 +60,7 @@ const GeneralTab: Component = () => {
       await updateGeneralConfig({ ...config, window_state: latest.general.window_state });
     } catch (err) {
       console.error("Failed to save general config:", err);
+      showToast(`Failed to save settings: ${err}`, "error");
     }
   };
 
@@ -72,7 +77,7 @@ const GeneralTab: Component = () => {
     <div>
       <SettingSection title="General" description="Appearance and behavior settings" />
 
-      <SettingRow label="Theme" description="Choose the app appearance">
+      <SettingRow label="Theme" policyPath="general.theme" description="Choose the app appearance">
         <SelectDropdown
           value={config.theme}
           options={[
@@ -84,7 +89,7 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Launch at login" description="Start Orbly when you log in">
+      <SettingRow label="Launch at login" policyPath="general.launch_at_login" description="Start Orbly when you log in">
         <ToggleSwitch
           checked={config.launch_at_login}
           onChange={async (v) => {
@@ -94,49 +99,49 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Close to tray" description="Minimize to system tray instead of quitting">
+      <SettingRow label="Close to tray" policyPath="general.tray_mode" description="Minimize to system tray instead of quitting">
         <ToggleSwitch
           checked={config.tray_mode}
           onChange={(v) => save({ tray_mode: v })}
         />
       </SettingRow>
 
-      <SettingRow label="Show badge in tray" description="Display unread count on the tray/dock icon">
+      <SettingRow label="Show badge in tray" policyPath="general.show_badge_in_tray" description="Display unread count on the tray/dock icon">
         <ToggleSwitch
           checked={config.show_badge_in_tray}
           onChange={(v) => save({ show_badge_in_tray: v })}
         />
       </SettingRow>
 
-      <SettingRow label="Check for updates" description="Automatically check for new versions">
+      <SettingRow label="Check for updates" policyPath="general.check_for_updates" description="Automatically check for new versions">
         <ToggleSwitch
           checked={config.check_for_updates}
           onChange={(v) => save({ check_for_updates: v })}
         />
       </SettingRow>
 
-      <SettingRow label="Developer mode" description="Show developer tools like Inject Console in the app menu">
+      <SettingRow label="Developer mode" policyPath="general.developer_mode" description="Show developer tools like Inject Console in the app menu">
         <ToggleSwitch
           checked={config.developer_mode}
           onChange={(v) => save({ developer_mode: v })}
         />
       </SettingRow>
 
-      <SettingRow label="Sidebar hover expand" description="Expand sidebar when hovering over it">
+      <SettingRow label="Sidebar hover expand" policyPath="general.sidebar_hover_expand" description="Expand sidebar when hovering over it">
         <ToggleSwitch
           checked={config.sidebar_hover_expand}
           onChange={(v) => save({ sidebar_hover_expand: v })}
         />
       </SettingRow>
 
-      <SettingRow label="Local scripts only" description="Disable remote recipe fetching (badge scripts, CSS/JS)">
+      <SettingRow label="Local scripts only" policyPath="general.local_scripts_only" description="Disable remote recipe fetching (badge scripts, CSS/JS)">
         <ToggleSwitch
           checked={config.local_scripts_only}
           onChange={(v) => save({ local_scripts_only: v })}
         />
       </SettingRow>
 
-      <SettingRow label="CPU alert threshold" description="Alert when an app exceeds this CPU % for 30+ seconds">
+      <SettingRow label="CPU alert threshold" policyPath="general.cpu_alert_threshold" description="Alert when an app exceeds this CPU % for 30+ seconds">
         <input
           type="number"
           min="10"
@@ -147,7 +152,36 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Scrollbar color" description="CSS color for scrollbars (empty for default)">
+      <SettingRow label="Memory budget" policyPath="general.memory_budget_mb" description="Hibernate the least recently used apps when Orbly uses more than this many MB (0 = no limit)">
+        <input
+          type="number"
+          min="0"
+          step="256"
+          value={config.memory_budget_mb}
+          onInput={(e) => save({ memory_budget_mb: Math.max(0, parseInt(e.currentTarget.value) || 0) })}
+          class="w-24 bg-gray-100 dark:bg-gray-700 border border-gray-200 dark:border-gray-600 rounded-md px-2 py-1 text-sm text-gray-800 dark:text-gray-200 outline-none focus:ring-2 focus:ring-blue-500"
+        />
+      </SettingRow>
+
+      <SettingRow label="Apps loading at startup" policyPath="general.startup_concurrency" description="How many apps load at once behind the first one when Orbly starts">
+        <input
+          type="number"
+          min="1"
+          max="8"
+          value={config.startup_concurrency}
+          onInput={(e) => save({ startup_concurrency: Math.max(1, parseInt(e.currentTarget.value) || 2) })}
+          class="w-20 bg-gray-100 dark:bg-gray-700 border border-gray-200 dark:border-gray-600 rounded-md px-2 py-1 text-sm text-gray-800 dark:text-gray-200 outline-none focus:ring-2 focus:ring-blue-500"
+        />
+      </SettingRow>
+
+      <SettingRow label="Load rarely used apps when idle" policyPath="general.defer_idle_apps" description="At startup, hold back apps that are not pinned, have no unread badge and were not used this week until Orbly is idle">
+        <ToggleSwitch
+          checked={config.defer_idle_apps}
+          onChange={(v) => save({ defer_idle_apps: v })}
+        />
+      </SettingRow>
+
+      <SettingRow label="Scrollbar color" policyPath="general.scrollbar_color" description="CSS color for scrollbars (empty for default)">
         <TextInput
           value={config.scrollbar_color ?? ''}
           onChange={(v) => save({ scrollbar_color: v || null })}
@@ -156,7 +190,7 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Selection color" description="CSS color for text selection (empty for default)">
+      <SettingRow label="Selection color" policyPath="general.selection_color" description="CSS color for text selection (empty for default)">
         <TextInput
           value={config.selection_color ?? ''}
           onChange={(v) => save({ selection_color: v || null })}
@@ -165,7 +199,7 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Recipe cache TTL (hours)" description="How long to cache remote recipes before refreshing">
+      <SettingRow label="Recipe cache TTL (hours)" policyPath="general.recipe_cache_ttl_hours" description="How long to cache remote recipes before refreshing">
         <input
           type="number"
           min="1"
@@ -176,7 +210,7 @@ const GeneralTab: Component = () => {
         />
       </SettingRow>
 
-      <SettingRow label="Recipe manifest URL" description="Custom URL for the recipe manifest (leave empty for default)">
+      <SettingRow label="Recipe manifest URL" policyPath="general.recipe_manifest_url" description="Custom URL for the recipe manifest (leave empty for default)">
         <TextInput
           value={config.recipe_manifest_url ?? ''}
           onChange={(v) => save({ recipe_manifest_url: v || null })}
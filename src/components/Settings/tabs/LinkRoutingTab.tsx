@@ -64,7 +64,10 @@ const LinkRoutingTab: Component = () => {
   };
 
   const targetOptions = () => {
-    const opts = [{ value: "external", label: "External Browser" }];
+    const opts = [
+      { value: "external", label: "External Browser" },
+      { value: "popup", label: "Sign-in Popup" },
+    ];
     for (const app of appConfigs) {
       opts.push({ value: app.id, label: app.name });
     }
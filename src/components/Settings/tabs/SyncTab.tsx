@@ -1,20 +1,117 @@
-import { Component, createSignal, onMount } from "solid-js";
-import { SettingSection, SettingRow, ToggleSwitch, Button } from "../SettingsControls";
-import { getConfig, exportConfigJson, importConfigJson } from "../../../lib/ipc";
+import { Component, For, Show, createSignal, onMount } from "solid-js";
+import { createStore } from "solid-js/store";
+import { SettingSection, SettingRow, ToggleSwitch, Button, SelectDropdown, TextInput } from "../SettingsControls";
+import { getConfig, exportConfigJson, importConfigJson, validateConfig, updateSyncConfig, getSyncStatus, syncNow, testSyncConnection, getSyncConflicts, resolveSyncConflict, listConfigSnapshots, diffConfigSnapshot, restoreConfigSnapshot } from "../../../lib/ipc";
 import { initializeState } from "../../../lib/stateSync";
+import { showToast } from "../../Toast/ToastContainer";
+import type { ConfigChange, SnapshotInfo, SyncConfig, SyncConflict, SyncStatus } from "../../../types/config";
 
 const SyncTab: Component = () => {
-  const [syncEnabled, setSyncEnabled] = createSignal(false);
+  const [sync, setSync] = createStore<SyncConfig>({
+    enabled: false,
+    backend: "icloud",
+    folder_path: "",
+    webdav_url: "",
+    webdav_username: "",
+    webdav_password: "",
+  });
+  const [status, setStatus] = createSignal<SyncStatus | null>(null);
+  const [testResult, setTestResult] = createSignal<string | null>(null);
+  const [conflicts, setConflicts] = createSignal<SyncConflict[]>([]);
+  const [snapshots, setSnapshots] = createSignal<SnapshotInfo[]>([]);
+  const [openSnapshot, setOpenSnapshot] = createSignal<{ id: string; changes: ConfigChange[] } | null>(null);
+  let initialized = false;
+
+  const refreshStatus = async () => {
+    try {
+      setStatus(await getSyncStatus());
+      setConflicts(await getSyncConflicts());
+    } catch (err) {
+      console.error("Failed to load sync status:", err);
+    }
+  };
 
   onMount(async () => {
     try {
       const config = await getConfig();
-      setSyncEnabled(config.sync.enabled);
+      setSync(config.sync);
+      initialized = true;
+      await refreshStatus();
+      setSnapshots(await listConfigSnapshots());
     } catch (err) {
       console.error("Failed to load sync config:", err);
     }
   });
 
+  const save = async (updates: Partial<SyncConfig>) => {
+    setSync(updates);
+    setTestResult(null);
+    if (!initialized) return;
+    try {
+      await updateSyncConfig({ ...sync });
+      await refreshStatus();
+    } catch (err) {
+      console.error("Failed to save sync config:", err);
+    }
+  };
+
+  const handleSyncNow = async () => {
+    try {
+      await syncNow();
+      await initializeState();
+    } catch (err) {
+      console.error("Sync failed:", err);
+    }
+    await refreshStatus();
+  };
+
+  const handleResolve = async (conflict: SyncConflict, resolution: "local" | "remote") => {
+    try {
+      await resolveSyncConflict(conflict.id, resolution);
+      if (resolution === "remote") await initializeState();
+    } catch (err) {
+      console.error("Failed to resolve sync conflict:", err);
+    }
+    await refreshStatus();
+  };
+
+  const describeConflict = (conflict: SyncConflict) => {
+    const owner = conflict.item_name ?? conflict.item_id ?? conflict.section;
+    return conflict.field ? `${owner}: ${conflict.field}` : owner;
+  };
+
+  const describeValue = (value: unknown) => {
+    if (value === null) return "deleted";
+    if (typeof value === "object") return (value as { name?: string }).name ?? JSON.stringify(value);
+    return String(value);
+  };
+
+  const toggleSnapshot = async (snapshot: SnapshotInfo) => {
+    if (openSnapshot()?.id === snapshot.id) {
+      setOpenSnapshot(null);
+      return;
+    }
+    try {
+      setOpenSnapshot({ id: snapshot.id, changes: await diffConfigSnapshot(snapshot.id) });
+    } catch (err) {
+      console.error("Failed to diff snapshot:", err);
+    }
+  };
+
+  const handleRestore = async (snapshot: SnapshotInfo) => {
+    try {
+      await restoreConfigSnapshot(snapshot.id);
+      await initializeState();
+      const config = await getConfig();
+      setSync(config.sync);
+      setOpenSnapshot(null);
+      showToast(`Restored config from ${new Date(snapshot.created_at).toLocaleString()}`, "info");
+    } catch (err) {
+      showToast(`Failed to restore config: ${err}`, "error");
+    }
+    setSnapshots(await listConfigSnapshots());
+  };
+
   const handleExport = async () => {
     try {
       const json = await exportConfigJson();
@@ -39,10 +136,20 @@ const SyncTab: Component = () => {
       if (!file) return;
       try {
         const text = await file.text();
+        const report = await validateConfig(JSON.parse(text));
+        if (report.errors.length > 0) {
+          const first = report.errors[0];
+          showToast(`Import refused: ${first.path}: ${first.message}${report.errors.length > 1 ? ` (+${report.errors.length - 1} more)` : ""}`, "error", 8000);
+          return;
+        }
         await importConfigJson(text);
         await initializeState();
+        if (report.warnings.length > 0) {
+          showToast(`Imported with ${report.warnings.length} warning(s): ${report.warnings[0].path}: ${report.warnings[0].message}`, "warning", 6000);
+        }
       } catch (err) {
         console.error("Failed to import config:", err);
+        showToast(`Failed to import config: ${err}`, "error");
       }
     };
     input.click();
@@ -52,14 +159,90 @@ const SyncTab: Component = () => {
     <div>
       <SettingSection title="Sync" description="Sync your configuration across devices" />
 
-      <SettingRow label="iCloud Sync" description="Sync settings via iCloud (macOS only, coming soon)">
-        <ToggleSwitch checked={syncEnabled()} onChange={setSyncEnabled} disabled />
+      <SettingRow label="Sync configuration" description="Keep apps and settings in sync between machines">
+        <ToggleSwitch checked={sync.enabled} onChange={(v) => save({ enabled: v })} />
       </SettingRow>
 
-      <div class="py-3 border-b border-gray-100 dark:border-gray-800">
-        <p class="text-xs text-gray-400">Last synced: Not available</p>
+      <SettingRow label="Sync backend" description="Where the synced config is stored">
+        <SelectDropdown
+          value={sync.backend}
+          options={[
+            { value: "folder", label: "Folder (Syncthing, Nextcloud…)" },
+            { value: "webdav", label: "WebDAV" },
+            { value: "icloud", label: "iCloud (macOS only)" },
+          ]}
+          onChange={(v) => save({ backend: v as SyncConfig["backend"] })}
+        />
+      </SettingRow>
+
+      <Show when={sync.backend === "folder"}>
+        <SettingRow label="Sync folder" description="A folder kept in sync by another tool">
+          <TextInput value={sync.folder_path} onChange={(v) => save({ folder_path: v })} placeholder="~/Sync/Orbly" class="w-52" />
+        </SettingRow>
+      </Show>
+
+      <Show when={sync.backend === "webdav"}>
+        <SettingRow label="WebDAV URL" description="Collection the sync file is stored in">
+          <TextInput value={sync.webdav_url} onChange={(v) => save({ webdav_url: v })} placeholder="https://cloud.example.com/remote.php/dav/files/me/Orbly" class="w-52" />
+        </SettingRow>
+        <SettingRow label="Username">
+          <TextInput value={sync.webdav_username} onChange={(v) => save({ webdav_username: v })} class="w-52" />
+        </SettingRow>
+        <SettingRow label="Password">
+          <input
+            type="password"
+            value={sync.webdav_password.startsWith("secret:") ? "" : sync.webdav_password}
+            placeholder={sync.webdav_password.startsWith("secret:") ? "Saved in keychain" : ""}
+            onInput={(e) => save({ webdav_password: e.currentTarget.value })}
+            class="w-52 bg-gray-100 dark:bg-gray-700 border border-gray-200 dark:border-gray-600 rounded-md px-2 py-1 text-sm text-gray-800 dark:text-gray-200 outline-none focus:ring-2 focus:ring-blue-500"
+          />
+        </SettingRow>
+      </Show>
+
+      <div class="py-3 border-b border-gray-100 dark:border-gray-800 flex items-center justify-between">
+        <div>
+          <p class="text-xs text-gray-400">Last synced: {formatTime(status()?.last_synced_at)}</p>
+          <Show when={status()?.remote_updated_at}>
+            <p class="text-xs text-gray-400">Remote copy updated: {formatTime(status()?.remote_updated_at)}</p>
+          </Show>
+          <Show when={status()?.last_error}>
+            <p class="text-xs text-red-500">{status()?.last_error}</p>
+          </Show>
+          <Show when={testResult()}>
+            <p class="text-xs text-gray-500">{testResult()}</p>
+          </Show>
+        </div>
+        <div class="flex gap-2">
+          <Button onClick={handleTest}>Test connection</Button>
+          <Button onClick={handleSyncNow} disabled={!sync.enabled}>Sync now</Button>
+        </div>
       </div>
 
+      <Show when={conflicts().length > 0}>
+        <div class="mt-6">
+          <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-1">Sync Conflicts</h4>
+          <p class="text-xs text-gray-400 mb-2">
+            These settings were changed differently on this device and another one. The local value is used until you choose.
+          </p>
+          <For each={conflicts()}>
+            {(conflict) => (
+              <div class="flex items-center justify-between py-2 border-b border-gray-100 dark:border-gray-800">
+                <div class="pr-4 min-w-0">
+                  <p class="text-sm text-gray-800 dark:text-gray-200 truncate">{describeConflict(conflict)}</p>
+                  <p class="text-xs text-gray-400 truncate">
+                    This device: {describeValue(conflict.local)} · Other device: {describeValue(conflict.remote)}
+                  </p>
+                </div>
+                <div class="flex gap-2 flex-shrink-0">
+                  <Button onClick={() => handleResolve(conflict, "local")}>Keep this device</Button>
+                  <Button onClick={() => handleResolve(conflict, "remote")}>Use other device</Button>
+                </div>
+              </div>
+            )}
+          </For>
+        </div>
+      </Show>
+
       <div class="mt-6">
         <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-3">Config Backup</h4>
         <div class="flex gap-2">
@@ -70,6 +253,44 @@ const SyncTab: Component = () => {
           Export your complete configuration for backup or transfer to another machine.
         </p>
       </div>
+
+      <div class="mt-6">
+        <h4 class="text-sm font-medium text-gray-800 dark:text-gray-200 mb-1">History</h4>
+        <p class="text-xs text-gray-400 mb-2">
+          A snapshot is kept after each change and before imports and upgrades. Show changes compares a snapshot with the current config.
+        </p>
+        <For each={snapshots()}>
+          {(snapshot) => (
+            <div class="py-2 border-b border-gray-100 dark:border-gray-800">
+              <div class="flex items-center justify-between">
+                <div class="pr-4 min-w-0">
+                  <p class="text-sm text-gray-800 dark:text-gray-200 truncate">{snapshot.reason}</p>
+                  <p class="text-xs text-gray-400">{new Date(snapshot.created_at).toLocaleString()}</p>
+                </div>
+                <div class="flex gap-2 flex-shrink-0">
+                  <Button onClick={() => toggleSnapshot(snapshot)}>
+                    {openSnapshot()?.id === snapshot.id ? "Hide changes" : "Show changes"}
+                  </Button>
+                  <Button onClick={() => handleRestore(snapshot)}>Restore</Button>
+                </div>
+              </div>
+              <Show when={openSnapshot()?.id === snapshot.id}>
+                <Show when={openSnapshot()!.changes.length > 0} fallback={<p class="text-xs text-gray-400 mt-1">Same as the current config</p>}>
+                  <ul class="mt-1 space-y-0.5">
+                    <For each={openSnapshot()!.changes}>
+                      {(change) => (
+                        <li class="text-xs text-gray-500 truncate">
+                          <span class="font-mono">{change.path}</span>: {change.before == null ? "none" : describeValue(change.before)} → {change.after == null ? "none" : describeValue(change.after)}
+                        </li>
+                      )}
+                    </For>
+                  </ul>
+                </Show>
+              </Show>
+            </div>
+          )}
+        </For>
+      </div>
     </div>
   );
 };
@@ -1,8 +1,23 @@
 import { Component, For, Show, createSignal, onMount } from "solid-js";
 import { SettingSection, SettingRow, ToggleSwitch, TextInput, Button } from "../SettingsControls";
-import { getWorkspaces, createWorkspace, updateWorkspace, deleteWorkspace, getConfig, updateWorkspacesConfig } from "../../../lib/ipc";
+import { getWorkspaces, createWorkspace, updateWorkspace, deleteWorkspace, getConfig, updateWorkspacesConfig, exportWorkspaceBundle, previewBundleImport, importBundle } from "../../../lib/ipc";
 import { appConfigs, workspaces, setWorkspaces } from "../../../stores/uiStore";
-import type { Workspace } from "../../../types/config";
+import { initializeState } from "../../../lib/stateSync";
+import { saveBundleFile, pickBundleFile } from "../../../lib/bundles";
+import { showToast } from "../../Toast/ToastContainer";
+import ScheduleEditor from "../ScheduleEditor";
+import type { BundlePreview, HibernationSchedule, Workspace } from "../../../types/config";
+
+const conflictText = (c: BundlePreview["conflicts"][number]) => {
+  switch (c.kind) {
+    case "app":
+      return `${c.item} is already set up as "${c.existing}"`;
+    case "workspace":
+      return `A workspace named "${c.existing}" exists; the import will be renamed`;
+    case "link_rule":
+      return `A rule for ${c.item} already exists and will be kept`;
+  }
+};
 
 const WorkspacesTab: Component = () => {
   const [editingId, setEditingId] = createSignal<string | null>(null);
@@ -10,6 +25,8 @@ const WorkspacesTab: Component = () => {
   const [newName, setNewName] = createSignal("");
   const [creating, setCreating] = createSignal(false);
   const [autoHibernate, setAutoHibernate] = createSignal(false);
+  const [pendingBundle, setPendingBundle] = createSignal<{ json: string; preview: BundlePreview } | null>(null);
+  const [reuseExisting, setReuseExisting] = createSignal(true);
 
   onMount(async () => {
     try {
@@ -66,6 +83,43 @@ const WorkspacesTab: Component = () => {
     }
   };
 
+  const handleExport = async (ws: Workspace) => {
+    try {
+      const json = await exportWorkspaceBundle(ws.id);
+      saveBundleFile(json, ws.name);
+    } catch (err) {
+      console.error("Failed to export workspace:", err);
+      showToast(`Failed to export workspace: ${err}`, "error");
+    }
+  };
+
+  const handlePickBundle = async () => {
+    const json = await pickBundleFile();
+    if (!json) return;
+    try {
+      const preview = await previewBundleImport(json);
+      setReuseExisting(true);
+      setPendingBundle({ json, preview });
+    } catch (err) {
+      showToast(`${err}`, "error");
+    }
+  };
+
+  const handleImportBundle = async () => {
+    const pending = pendingBundle();
+    if (!pending) return;
+    try {
+      const result = await importBundle(pending.json, reuseExisting());
+      setPendingBundle(null);
+      await initializeState();
+      const added = result.app_ids.length;
+      showToast(`Imported "${pending.preview.name}" (${added} new app${added === 1 ? "" : "s"})`, "info");
+    } catch (err) {
+      console.error("Failed to import bundle:", err);
+      showToast(`Failed to import bundle: ${err}`, "error");
+    }
+  };
+
   const toggleAppInWorkspace = async (ws: Workspace, appId: string) => {
     const newAppIds = ws.app_ids.includes(appId)
       ? ws.app_ids.filter(id => id !== appId)
@@ -79,6 +133,16 @@ const WorkspacesTab: Component = () => {
     }
   };
 
+  const setSchedule = async (ws: Workspace, schedule: HibernationSchedule | null) => {
+    const updated = { ...ws, schedule };
+    try {
+      await updateWorkspace(updated);
+      setWorkspaces(workspaces.map(w => w.id === ws.id ? updated : w));
+    } catch (err) {
+      console.error("Failed to update workspace schedule:", err);
+    }
+  };
+
   return (
     <div>
       <SettingSection title="Workspaces" description="Organize your apps into separate workspaces" />
@@ -114,6 +178,7 @@ const WorkspacesTab: Component = () => {
                   }>
                     <Button onClick={() => finishEditing(ws)}>Done</Button>
                   </Show>
+                  <Button onClick={() => handleExport(ws)}>Export</Button>
                   <Show when={ws.id !== "default"}>
                     <Button variant="danger" onClick={() => handleDelete(ws.id)}>Delete</Button>
                   </Show>
@@ -135,6 +200,11 @@ const WorkspacesTab: Component = () => {
                       </label>
                     )}
                   </For>
+                  <ScheduleEditor
+                    schedule={ws.schedule}
+                    onChange={(v) => setSchedule(ws, v)}
+                    description="Wake and hibernate this workspace's apps at set times"
+                  />
                 </div>
               </Show>
               <Show when={editingId() !== ws.id}>
@@ -149,7 +219,10 @@ const WorkspacesTab: Component = () => {
 
       <div class="mt-4">
         <Show when={creating()} fallback={
-          <Button onClick={() => setCreating(true)}>Create Workspace</Button>
+          <div class="flex gap-2">
+            <Button onClick={() => setCreating(true)}>Create Workspace</Button>
+            <Button onClick={handlePickBundle}>Import Bundle</Button>
+          </div>
         }>
           <div class="flex gap-2">
             <TextInput value={newName()} onChange={setNewName} placeholder="Workspace name" class="flex-1" />
@@ -158,6 +231,41 @@ const WorkspacesTab: Component = () => {
           </div>
         </Show>
       </div>
+
+      <Show when={pendingBundle()}>
+        {(pending) => (
+          <div class="mt-4 border border-blue-200 dark:border-blue-800 rounded-lg p-4">
+            <h4 class="text-sm font-semibold text-gray-800 dark:text-gray-200">Import "{pending().preview.name}"</h4>
+            <p class="text-xs text-gray-500 mt-1">
+              {pending().preview.apps.join(", ")}
+              <Show when={pending().preview.workspace}> · new workspace "{pending().preview.workspace}"</Show>
+              <Show when={pending().preview.link_rules > 0}> · {pending().preview.link_rules} link rule(s)</Show>
+            </p>
+            <Show when={pending().preview.conflicts.length > 0}>
+              <ul class="mt-2 space-y-1">
+                <For each={pending().preview.conflicts}>
+                  {(conflict) => <li class="text-xs text-amber-600 dark:text-amber-400">{conflictText(conflict)}</li>}
+                </For>
+              </ul>
+            </Show>
+            <Show when={pending().preview.conflicts.some((c) => c.kind === "app")}>
+              <label class="flex items-center gap-2 mt-2 text-xs text-gray-700 dark:text-gray-300">
+                <input
+                  type="checkbox"
+                  checked={reuseExisting()}
+                  onChange={(e) => setReuseExisting(e.currentTarget.checked)}
+                  class="rounded border-gray-300 dark:border-gray-600"
+                />
+                Use apps I already have instead of adding duplicates
+              </label>
+            </Show>
+            <div class="flex justify-end gap-2 mt-3">
+              <Button onClick={() => setPendingBundle(null)}>Cancel</Button>
+              <Button variant="primary" onClick={handleImportBundle}>Import</Button>
+            </div>
+          </div>
+        )}
+      </Show>
     </div>
   );
 };
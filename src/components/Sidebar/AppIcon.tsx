@@ -6,6 +6,7 @@ interface AppIconProps {
   name: string;
   state: "active" | "loading" | "hibernated" | "disabled";
   badgeCount: number | null;
+  signedOut?: boolean;
   icon?: string;
   audioMuted: boolean;
   onClick: (id: string) => void;
@@ -54,8 +55,8 @@ const AppIcon: Component<AppIconProps> = (props) => {
           btn?.focus();
         }
       }}
-      title={props.name}
-      aria-label={`${props.name}, ${props.state}${props.badgeCount && props.badgeCount > 0 ? `, ${props.badgeCount} unread` : props.badgeCount && props.badgeCount < 0 ? ', unread' : ''}`}
+      title={props.signedOut ? `${props.name} (signed out)` : props.name}
+      aria-label={`${props.name}, ${props.state}${props.signedOut ? ', signed out' : ''}${props.badgeCount && props.badgeCount > 0 ? `, ${props.badgeCount} unread` : props.badgeCount && props.badgeCount < 0 ? ', unread' : ''}`}
       style={{ "min-height": "44px" }}
     >
       <div class="relative w-10 h-10 flex items-center justify-center flex-shrink-0">
@@ -69,8 +70,16 @@ const AppIcon: Component<AppIconProps> = (props) => {
           )}
         </div>
 
+        {/* A signed-out app's unread count means nothing until it signs back in */}
+        <Show when={props.signedOut && props.state !== "disabled"}>
+          <span class="absolute -top-0.5 -right-0.5 bg-[#FF9500] dark:bg-[#FF9F0A] text-white text-[10px] font-bold rounded-full min-w-[16px] h-4 flex items-center justify-center px-1">
+            !
+          </span>
+        </Show>
+
         <Show
           when={
+            !props.signedOut &&
             props.badgeCount !== null &&
             props.badgeCount !== undefined &&
             props.badgeCount !== 0 &&
@@ -35,6 +35,7 @@ interface SortableAppIconProps {
     name: string;
     state: "active" | "hibernated" | "disabled";
     badgeCount: number | null;
+    signedOut: boolean;
     icon?: string;
     audioMuted: boolean;
   };
@@ -60,6 +61,7 @@ const SortableAppIcon: Component<SortableAppIconProps> = (props) => {
         icon={props.app.icon}
         state={props.app.state}
         badgeCount={props.app.badgeCount}
+        signedOut={props.app.signedOut}
         audioMuted={props.app.audioMuted}
         onClick={props.onClick}
         onContextMenu={props.onContextMenu}
@@ -90,6 +92,7 @@ const AppIconList: Component = () => {
             | "hibernated"
             | "disabled",
           badgeCount: state?.badge_count ?? null,
+          signedOut: state?.signed_out ?? false,
           audioMuted: config.audio_muted,
           icon: config.icon,
           sidebarSection: config.sidebar_section,
@@ -241,6 +244,7 @@ const AppIconList: Component = () => {
                   icon={app().icon}
                   state={app().state}
                   badgeCount={app().badgeCount}
+                  signedOut={app().signedOut}
                   audioMuted={app().audioMuted}
                   onClick={() => {}}
                   onContextMenu={() => {}}
@@ -1,7 +1,7 @@
 import { Component, For, Show, onMount, onCleanup, createSignal } from "solid-js";
 import { Portal } from "solid-js/web";
 import { open } from "@tauri-apps/plugin-shell";
-import { hibernateApp, disableApp, enableApp, reloadApp, removeApp, setAudioMuted, updateApp, checkUnsavedWork, updateWorkspace } from "../../lib/ipc";
+import { hibernateApp, disableApp, enableApp, reloadApp, removeApp, setAudioMuted, updateApp, checkUnsavedWork, updateWorkspace, switchAppAccount, popOutApp, dockApp, activateApp } from "../../lib/ipc";
 import { appConfigs, appStates, setSettingsVisible, setEditingAppIdFromContextMenu, workspaces } from "../../stores/uiStore";
 import { refreshAppConfigs, refreshAppStates } from "../../lib/stateSync";
 import ConfirmDialog from "../Dialogs/ConfirmDialog";
@@ -19,22 +19,43 @@ const menuItems = [
   { label: "---", action: "separator" },
   { label: "Mute/Unmute Audio", action: "toggle-mute" },
   { label: "Open in External Browser", action: "open-external" },
+  { label: "Pop Out", action: "pop-out" },
   { label: "---", action: "separator" },
   { label: "Edit", action: "edit" },
   { label: "Remove", action: "remove" },
   { label: "---", action: "separator" },
   { label: "Move to Section", action: "move-section" },
   { label: "Add to Workspace", action: "move-workspace" },
+  { label: "Switch Account", action: "switch-account" },
 ] as const;
 
 const ContextMenu: Component<ContextMenuProps> = (props) => {
   const [showRemoveConfirm, setShowRemoveConfirm] = createSignal(false);
   const [showHibernateConfirm, setShowHibernateConfirm] = createSignal(false);
+  const [showReloadConfirm, setShowReloadConfirm] = createSignal(false);
   const [deleteData, setDeleteData] = createSignal(false);
   const [showSectionMenu, setShowSectionMenu] = createSignal(false);
   const [showWorkspaceMenu, setShowWorkspaceMenu] = createSignal(false);
+  const [showAccountMenu, setShowAccountMenu] = createSignal(false);
   const [suppressForApp, setSuppressForApp] = createSignal(false);
 
+  const appConfig = () => appConfigs.find((a) => a.id === props.appId);
+
+  const accounts = () => {
+    const config = appConfig();
+    if (!config || config.accounts.length === 0) return [];
+    return [
+      { id: "", name: config.account_name || "Default" },
+      ...config.accounts.map((a) => ({ id: a.id, name: a.name })),
+    ];
+  };
+
+  const accountBadge = (accountId: string) => {
+    const count = appStates.find((s) => s.id === props.appId)?.account_badges[accountId];
+    if (!count) return "";
+    return count > 0 ? ` (${count})` : " •";
+  };
+
   const sections = () => {
     const set = new Set(appConfigs.map((a) => a.sidebar_section).filter(Boolean));
     return [...set];
@@ -44,6 +65,10 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
     try {
       switch (action) {
         case "reload":
+          if (await checkUnsavedWork(props.appId)) {
+            setShowReloadConfirm(true);
+            return;
+          }
           await reloadApp(props.appId);
           break;
         case "hibernate": {
@@ -55,7 +80,7 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
               return;
             }
           }
-          await hibernateApp(props.appId);
+          await hibernateApp(props.appId, config?.suppress_hibernate_confirm ?? false);
           break;
         }
         case "disable": {
@@ -82,6 +107,15 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
           }
           break;
         }
+        case "pop-out":
+          if (appConfig()?.detached) {
+            await dockApp(props.appId);
+          } else {
+            await popOutApp(props.appId);
+            // Opens the window if the app was not loaded
+            await activateApp(props.appId);
+          }
+          break;
         case "remove":
           setShowRemoveConfirm(true);
           return; // Don't close the menu yet
@@ -95,6 +129,9 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
         case "move-workspace":
           setShowWorkspaceMenu(true);
           return; // Don't close menu
+        case "switch-account":
+          setShowAccountMenu(true);
+          return; // Don't close menu
       }
     } catch (err) {
       console.error(`Failed to execute ${action}:`, err);
@@ -122,6 +159,16 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
     props.onClose();
   };
 
+  const handleSwitchAccount = async (accountId: string) => {
+    try {
+      await switchAppAccount(props.appId, accountId);
+    } catch (err) {
+      console.error("Failed to switch account:", err);
+    }
+    setShowAccountMenu(false);
+    props.onClose();
+  };
+
   const handleHibernateConfirm = async () => {
     try {
         if (suppressForApp()) {
@@ -132,7 +179,7 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
             await refreshAppConfigs();
           }
         }
-      await hibernateApp(props.appId);
+      await hibernateApp(props.appId, true);
     } catch (err) {
       console.error("Failed to hibernate app:", err);
     }
@@ -141,6 +188,16 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
     props.onClose();
   };
 
+  const handleReloadConfirm = async () => {
+    try {
+      await reloadApp(props.appId, true);
+    } catch (err) {
+      console.error("Failed to reload app:", err);
+    }
+    setShowReloadConfirm(false);
+    props.onClose();
+  };
+
   const handleRemoveConfirm = async () => {
     try {
       await removeApp(props.appId, deleteData());
@@ -190,12 +247,17 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
           if (item.action === "separator") {
             return <div class="h-px bg-gray-200 dark:bg-gray-700 my-1" />;
           }
+          if (item.action === "switch-account" && accounts().length === 0) {
+            return null;
+          }
           const label =
             item.action === "disable"
               ? appStates.find((s) => s.id === props.appId)?.state === "disabled"
                 ? "Enable"
                 : "Disable"
-              : item.label;
+              : item.action === "pop-out" && appConfig()?.detached
+                ? "Dock"
+                : item.label;
           return (
             <button
               class="w-full text-left px-3 py-1.5 text-xs text-gray-700 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-600"
@@ -257,6 +319,30 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
           </For>
         </div>
       </Show>
+      <Show when={showAccountMenu()}>
+        <div
+          data-context-menu
+          class="fixed z-50 min-w-[160px] bg-white dark:bg-[#2D2D2D] border border-gray-200 dark:border-gray-700 rounded-lg shadow-lg py-1"
+          style={{ left: `${props.position.x + 180}px`, top: `${props.position.y}px` }}
+        >
+          <button class="w-full text-left px-3 py-1.5 text-xs text-gray-500 dark:text-gray-400" disabled>
+            Switch Account
+          </button>
+          <div class="h-px bg-gray-200 dark:bg-gray-700 my-1" />
+          <For each={accounts()}>
+            {(account) => (
+              <button
+                class="w-full text-left px-3 py-1.5 text-xs text-gray-700 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-600"
+                onClick={() => handleSwitchAccount(account.id)}
+              >
+                {account.name}
+                {accountBadge(account.id)}
+                {appConfig()?.active_account === account.id ? " ✓" : ""}
+              </button>
+            )}
+          </For>
+        </div>
+      </Show>
       <Show when={showHibernateConfirm()}>
         <ConfirmDialog
           title="Unsaved Work Detected"
@@ -277,6 +363,16 @@ const ContextMenu: Component<ContextMenuProps> = (props) => {
           </label>
         </ConfirmDialog>
       </Show>
+      <Show when={showReloadConfirm()}>
+        <ConfirmDialog
+          title="Unsaved Work Detected"
+          message="This app appears to have unsaved work. Reload anyway?"
+          confirmLabel="Reload"
+          variant="danger"
+          onConfirm={handleReloadConfirm}
+          onCancel={() => { setShowReloadConfirm(false); props.onClose(); }}
+        />
+      </Show>
       <Show when={showRemoveConfirm()}>
         <ConfirmDialog
           title="Remove App"
@@ -4,6 +4,7 @@ import {
   dndEnabled,
   setDndEnabled,
   activeDownloadCount,
+  startupProgress,
   setDownloadsVisible,
   setAppsManagerVisible,
   setSettingsVisible,
@@ -60,6 +61,33 @@ const SidebarFooter: Component = () => {
 
   return (
     <div role="toolbar" aria-label="App controls" class="flex-shrink-0 border-t border-gray-200 dark:border-gray-700 px-1.5 py-2">
+      <Show when={startupProgress()}>
+        {(progress) => (
+          <div
+            class="px-1 pb-2"
+            title={`Loading apps: ${progress().loaded} of ${progress().total}`}
+            role="progressbar"
+            aria-label="Loading apps"
+            aria-valuemin={0}
+            aria-valuemax={progress().total}
+            aria-valuenow={progress().loaded}
+          >
+            <div class="h-1 rounded-full bg-gray-200 dark:bg-gray-700 overflow-hidden">
+              <div
+                class="h-full bg-blue-500 transition-all"
+                style={{ width: `${(progress().loaded / progress().total) * 100}%` }}
+              />
+            </div>
+            <Show when={sidebarExpanded()}>
+              <p class="mt-1 text-[10px] text-center text-gray-500 dark:text-gray-400">
+                Loading apps {progress().loaded}/{progress().total}
+                {progress().deferred > 0 ? ` · ${progress().deferred} when idle` : ""}
+                {progress().delayed > 0 ? ` · ${progress().delayed} delayed` : ""}
+              </p>
+            </Show>
+          </div>
+        )}
+      </Show>
       <div
         class={`flex ${sidebarExpanded() ? "flex-row justify-around" : "flex-col items-center gap-1"}`}
       >
@@ -46,6 +46,10 @@ const AppMenu: Component<AppMenuProps> = (props) => {
     try {
       switch (action) {
         case "reload":
+          if (await checkUnsavedWork(props.appId)) {
+            showToast("Unsaved work detected; reload cancelled", "warning");
+            break;
+          }
           await reloadApp(props.appId);
           break;
         case "hibernate":
@@ -59,7 +63,7 @@ const AppMenu: Component<AppMenuProps> = (props) => {
                 break;
               }
             }
-            await hibernateApp(props.appId);
+            await hibernateApp(props.appId, appConfig?.suppress_hibernate_confirm ?? false);
           }
           break;
         case "disable":
@@ -0,0 +1,25 @@
+/** Save a bundle as `<name>.orbly` through the browser download flow. */
+export function saveBundleFile(json: string, name: string) {
+  const slug = name.toLowerCase().replace(/[^a-z0-9]+/g, "-").replace(/^-+|-+$/g, "") || "bundle";
+  const blob = new Blob([json], { type: "application/json" });
+  const url = URL.createObjectURL(blob);
+  const a = document.createElement("a");
+  a.href = url;
+  a.download = `${slug}.orbly`;
+  a.click();
+  URL.revokeObjectURL(url);
+}
+
+/** Let the user pick a `.orbly` file and return its contents. */
+export function pickBundleFile(): Promise<string | null> {
+  return new Promise((resolve) => {
+    const input = document.createElement("input");
+    input.type = "file";
+    input.accept = ".orbly,.json";
+    input.onchange = async () => {
+      const file = input.files?.[0];
+      resolve(file ? await file.text() : null);
+    };
+    input.click();
+  });
+}
@@ -3,6 +3,7 @@ import {
   setActiveAppId,
   activeAppId,
   setActiveDownloadCount,
+  setStartupProgress,
   setActiveWorkspaceId,
   activeWorkspaceId,
   setDndEnabled,
@@ -17,9 +18,10 @@ import {
   workspaces,
   setWorkspaces,
 } from "../stores/uiStore";
-import { refreshAppStates, persistRecentAppIds, refreshAppConfigs } from "./stateSync";
+import { refreshAppStates, persistRecentAppIds, refreshAppConfigs, initializeState } from "./stateSync";
 import { showToast } from "../components/Toast/ToastContainer";
 import { activateApp, getActiveDownloadCount, getConfig, updateWorkspaceTiling } from "./ipc";
+import type { AppStateChanged, StartupProgress } from "../types/appState";
 
 let unlisteners: UnlistenFn[] = [];
 let downloadCountInterval: ReturnType<typeof setInterval> | undefined;
@@ -49,6 +51,9 @@ export async function setupEventListeners() {
     await listen<string>("app-hibernated", () => {
       refreshAppStates();
     }),
+    await listen<{ appId: string; signedOut: boolean }>("app-signed-out", () => {
+      refreshAppStates();
+    }),
     await listen<string>("app-disabled", () => {
       refreshAppStates();
     }),
@@ -60,14 +65,58 @@ export async function setupEventListeners() {
       const appName = appConfigs.find(a => a.id === event.payload)?.name ?? "An app";
       showToast(`${appName} was auto-hibernated due to inactivity`, "info");
     }),
-    await listen<string>("app-state-changed", () => {
+    await listen<{ appId: string; reason: string }>("app-budget-hibernated", (event) => {
+      refreshAppStates();
+      showToast(`Hibernated to save memory: ${event.payload.reason}`, "info", 6000);
+    }),
+    await listen<{ appId: string; action: "wake" | "hibernate" }>("app-scheduled", () => {
+      refreshAppStates();
+      refreshAppConfigs();
+    }),
+    await listen<AppStateChanged>("app-state-changed", () => {
       refreshAppStates();
     }),
     await listen<string>("app-crashed", () => {
       refreshAppStates();
     }),
-    await listen<{ appId: string; count: number | null }>("badge-updated", () => {
+    await listen<{ appId: string; crashes: number; message: string }>("app-crash-loop", (event) => {
       refreshAppStates();
+      const appName = appConfigs.find(a => a.id === event.payload.appId)?.name ?? "An app";
+      showToast(`${appName} keeps crashing and was stopped. Reload it to try again.`, "error", 8000);
+    }),
+    await listen<{ appId: string; accountId: string }>("app-account-switched", () => {
+      refreshAppConfigs();
+      refreshAppStates();
+    }),
+    await listen<string>("app-popped-out", async (event) => {
+      await refreshAppConfigs();
+      refreshAppStates();
+      // The main window goes back to the app used before
+      if (activeAppId() === event.payload) {
+        const previous = recentAppIds()
+          .map((id) => appConfigs.find((a) => a.id === id))
+          .find((a) => a && a.enabled && !a.detached);
+        if (previous) {
+          activateApp(previous.id);
+        } else {
+          setActiveAppId(null);
+        }
+      }
+    }),
+    await listen<string>("app-docked", async (event) => {
+      await refreshAppConfigs();
+      activateApp(event.payload);
+    }),
+    await listen<string>("app-data-cleared", () => {
+      refreshAppConfigs();
+      refreshAppStates();
+    }),
+    await listen<{ appId: string; count: number | null; accounts: Record<string, number> | null }>("badge-updated", () => {
+      refreshAppStates();
+    }),
+    await listen<StartupProgress>("startup-progress", (event) => {
+      const { loaded, total } = event.payload;
+      setStartupProgress(loaded < total ? event.payload : null);
     }),
     await listen<string>("download-started", () => {
       refreshDownloadCount();
@@ -123,6 +172,13 @@ export async function setupEventListeners() {
       } catch {}
       refreshAppStates();
     }),
+    await listen<void>("config-reloaded", () => {
+      initializeState();
+      showToast("Reloaded config.toml", "info", 2000);
+    }),
+    await listen<string>("config-reload-failed", (event) => {
+      showToast(`config.toml edit not applied: ${event.payload}`, "error", 8000);
+    }),
     await listen<{ appId: string; message: string }>("app-error", () => {
       refreshAppStates();
     }),
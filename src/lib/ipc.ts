@@ -4,6 +4,9 @@ import type { AppStateInfo } from "../types/appState";
 
 // Config commands
 export const getConfig = () => invoke<OrblyConfig>("get_config");
+export const getConfigReadOnlyReason = () => invoke<string | null>("get_config_read_only_reason");
+export const getPolicy = () => invoke<import("../types/config").PolicyInfo>("get_policy");
+export const validateConfig = (config?: OrblyConfig) => invoke<import("../types/config").ValidationReport>("validate_config", { config: config ?? null });
 export const getApps = () => invoke<AppConfig[]>("get_apps");
 export const getApp = (appId: string) => invoke<AppConfig | null>("get_app", { app_id: appId });
 export const addApp = (app: AppConfig) => invoke<void>("add_app", { app });
@@ -13,12 +16,20 @@ export const updateGeneralConfig = (general: GeneralConfig) => invoke<void>("upd
 
 // App lifecycle commands
 export const getAppStates = () => invoke<AppStateInfo[]>("get_app_states");
+export const getAppStateHistory = (appId: string) => invoke<import("../types/appState").StateTransition[]>("get_app_state_history", { app_id: appId });
+export const switchAppAccount = (appId: string, accountId: string) => invoke<void>("switch_app_account", { app_id: appId, account_id: accountId });
+export const popOutApp = (appId: string) => invoke<void>("pop_out_app", { app_id: appId });
+export const dockApp = (appId: string) => invoke<void>("dock_app", { app_id: appId });
+export const getDataStoreSizes = () => invoke<import("../types/config").DataStoreSize[]>("get_data_store_sizes");
+export const clearAppData = (appId: string, category: import("../types/config").DataCategory) => invoke<number>("clear_app_data", { app_id: appId, category });
+export const resetAppData = (appId: string) => invoke<number>("reset_app_data", { app_id: appId });
+export const cleanOrphanedDataStores = () => invoke<number>("clean_orphaned_data_stores");
 export const activateApp = (appId: string) => invoke<void>("activate_app", { app_id: appId });
 export const ensureWebviewExists = (appId: string) => invoke<void>("ensure_webview_exists", { app_id: appId });
-export const hibernateApp = (appId: string) => invoke<void>("hibernate_app", { app_id: appId });
+export const hibernateApp = (appId: string, force: boolean = false) => invoke<void>("hibernate_app", { app_id: appId, force });
 export const disableApp = (appId: string) => invoke<void>("disable_app", { app_id: appId });
 export const enableApp = (appId: string) => invoke<void>("enable_app", { app_id: appId });
-export const reloadApp = (appId: string) => invoke<void>("reload_app", { app_id: appId });
+export const reloadApp = (appId: string, force: boolean = false) => invoke<void>("reload_app", { app_id: appId, force });
 export const notifyAppInteraction = (appId: string) => invoke<void>("notify_app_interaction", { app_id: appId });
 
 // Navigation commands
@@ -60,6 +71,10 @@ export const updateLinkRoutingConfig = (linkRouting: import("../types/config").L
 export const testLinkRoute = (url: string) => invoke<string>("test_link_route", { url });
 export const exportConfigJson = () => invoke<string>("export_config_json");
 export const importConfigJson = (json: string) => invoke<void>("import_config_json", { json });
+export const listConfigSnapshots = () => invoke<import("../types/config").SnapshotInfo[]>("list_config_snapshots");
+export const diffConfigSnapshot = (snapshotId: string, againstId?: string) =>
+  invoke<import("../types/config").ConfigChange[]>("diff_config_snapshot", { snapshot_id: snapshotId, against_id: againstId ?? null });
+export const restoreConfigSnapshot = (snapshotId: string) => invoke<void>("restore_config_snapshot", { snapshot_id: snapshotId });
 
 // Adblock commands
 export const toggleAdblock = (appId: string) => invoke<boolean>("toggle_adblock", { app_id: appId });
@@ -71,6 +86,14 @@ export const updateDownloadsConfig = (downloads: import("../types/config").Downl
 export const updateShortcutsConfig = (shortcuts: import("../types/config").ShortcutConfig) => invoke<void>("update_shortcuts_config", { shortcuts });
 export const updateWorkspacesConfig = (workspaces: import("../types/config").WorkspacesConfig) => invoke<void>("update_workspaces_config", { workspaces });
 
+// Sync commands
+export const updateSyncConfig = (sync: import("../types/config").SyncConfig) => invoke<void>("update_sync_config", { sync });
+export const getSyncStatus = () => invoke<import("../types/config").SyncStatus>("get_sync_status");
+export const syncNow = () => invoke<import("../types/config").SyncStatus>("sync_now");
+export const getSyncConflicts = () => invoke<import("../types/config").SyncConflict[]>("get_sync_conflicts");
+export const resolveSyncConflict = (conflictId: string, resolution: "local" | "remote") => invoke<void>("resolve_sync_conflict", { conflict_id: conflictId, resolution });
+export const testSyncConnection = (sync: import("../types/config").SyncConfig) => invoke<boolean>("test_sync_connection", { sync });
+
 // Download commands
 export const getDownloads = () => invoke<import("../types/downloads").DownloadEntry[]>("get_downloads");
 export const getActiveDownloadCount = () => invoke<number>("get_active_download_count");
@@ -102,6 +125,22 @@ export const updateWorkspaceTiling = (
 export const deleteWorkspace = (workspaceId: string) =>
   invoke<void>("delete_workspace", { workspace_id: workspaceId });
 
+// Bundle commands
+export const exportAppBundle = (appId: string) => invoke<string>("export_app_bundle", { app_id: appId });
+export const exportWorkspaceBundle = (workspaceId: string) =>
+  invoke<string>("export_workspace_bundle", { workspace_id: workspaceId });
+export const previewBundleImport = (json: string) =>
+  invoke<import("../types/config").BundlePreview>("preview_bundle_import", { json });
+export const importBundle = (json: string, reuseExistingApps: boolean) =>
+  invoke<import("../types/config").BundleImport>("import_bundle", { json, reuse_existing_apps: reuseExistingApps });
+
+// Service import commands
+export const detectServiceImports = () =>
+  invoke<import("../types/config").DetectedSource[]>("detect_service_imports");
+export const planServiceImport = (source: import("../types/config").ImportSource, path?: string) =>
+  invoke<import("../types/config").ImportPlan>("plan_service_import", { source, path: path ?? null });
+export const applyServiceImport = (apps: AppConfig[]) => invoke<string[]>("apply_service_import", { apps });
+
 // Audio commands
 export const setAudioMuted = (appId: string, muted: boolean) =>
   invoke<void>("set_audio_muted", { app_id: appId, muted });
@@ -1,4 +1,4 @@
-import { getConfig, getAppStates } from "./ipc";
+import { getConfig, getAppStates, getPolicy } from "./ipc";
 import {
   setAppConfigs,
   setAppStates,
@@ -9,6 +9,7 @@ import {
   setRecentAppIds,
   setLayoutMode,
   setTileAssignments,
+  setPolicy,
 } from "../stores/uiStore";
 import { load } from "@tauri-apps/plugin-store";
 
@@ -41,6 +42,7 @@ export async function initializeState() {
 
     const states = await getAppStates();
     setAppStates(states);
+    setPolicy(await getPolicy());
 
     // Restore recent app IDs from persistent store
     try {
@@ -30,6 +30,13 @@ export const visibleApps = createMemo(() => {
 // DND state
 export const [dndEnabled, setDndEnabled] = createSignal(false);
 
+// Administrator policy
+export const [policy, setPolicy] = createSignal<import("../types/config").PolicyInfo | null>(null);
+export const isLocked = (path: string) => policy()?.locked.includes(path) ?? false;
+
+// Apps still loading after startup (for footer progress)
+export const [startupProgress, setStartupProgress] = createSignal<import("../types/appState").StartupProgress | null>(null);
+
 // Active download count (for footer badge)
 export const [activeDownloadCount, setActiveDownloadCount] = createSignal(0);
 
@@ -1,10 +1,36 @@
+export type AppStateKind = 'loading' | 'active' | 'hibernated' | 'disabled' | 'error' | 'crashed';
+
 export interface AppStateInfo {
   id: string;
   name: string;
-  state: 'loading' | 'active' | 'hibernated' | 'disabled' | 'error' | 'crashed' | 'certificate_error';
+  state: AppStateKind | 'certificate_error';
   badge_count: number | null;
+  account_badges: Record<string, number>;
   current_url: string | null;
   error_message?: string;
+  crash_count: number;
+  signed_out: boolean;
+}
+
+export interface StateTransition {
+  from: AppStateKind;
+  to: AppStateKind;
+  at: string;
+  reason: string;
+}
+
+export interface AppStateChanged {
+  appId: string;
+  old: AppStateKind;
+  new: AppStateKind;
+  reason: string;
+}
+
+export interface StartupProgress {
+  loaded: number;
+  total: number;
+  deferred: number;
+  delayed: number;
 }
 
 export type AppEvent =
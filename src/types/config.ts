@@ -4,6 +4,9 @@ export type NotificationStyle = 'full' | 'private' | 'off';
 
 export type ThemeMode = 'system' | 'light' | 'dark';
 
+export type CrashRecovery = 'reload' | 'delayed' | 'off';
+export type LaunchPolicy = 'restore' | 'active' | 'hibernated' | 'delayed';
+
 export interface AppConfig {
   id: string;
   name: string;
@@ -18,6 +21,8 @@ export interface AppConfig {
   custom_css: string;
   custom_js: string;
   proxy: string;
+  /** Secret handle for proxy credentials; the URL itself carries none. */
+  proxy_credentials: string;
   dark_mode: DarkModeType;
   dark_mode_brightness: number;
   dark_mode_contrast: number;
@@ -36,6 +41,35 @@ export interface AppConfig {
   zoom_level: number;
   suppress_high_usage_alert: boolean;
   suppress_hibernate_confirm: boolean;
+  crash_recovery: CrashRecovery;
+  pinned: boolean;
+  schedule: HibernationSchedule | null;
+  restore_form_fields: boolean;
+  accounts: AppAccount[];
+  account_name: string;
+  active_account: string;
+  detached: boolean;
+  popout_window: WindowState;
+  ephemeral: boolean;
+  launch: LaunchPolicy;
+  launch_delay_secs: number;
+  login_url_patterns: string[];
+}
+
+export interface AppAccount {
+  id: string;
+  name: string;
+  data_store_uuid: string;
+  notification_style: NotificationStyle;
+}
+
+export type DataCategory = "cookies" | "cache" | "local_storage" | "all";
+
+export interface DataStoreSize {
+  app_id: string;
+  account_id: string;
+  data_store_uuid: string;
+  bytes: number;
 }
 
 export interface WindowState {
@@ -65,8 +99,11 @@ export interface GeneralConfig {
   window_state: WindowState;
   local_scripts_only: boolean;
   cpu_alert_threshold: number;
+  memory_budget_mb: number;
   sidebar_hover_expand: boolean;
   recipe_manifest_url?: string | null;
+  startup_concurrency: number;
+  defer_idle_apps: boolean;
 }
 
 export interface AdblockConfig {
@@ -102,6 +139,13 @@ export interface Workspace {
   app_ids: string[];
   tiling_layout: string;
   tile_assignments: string[];
+  schedule: HibernationSchedule | null;
+}
+
+export interface HibernationSchedule {
+  wake_at: string;
+  hibernate_at: string;
+  days: string[];
 }
 
 export interface WorkspacesConfig {
@@ -116,8 +160,107 @@ export interface DownloadGlobalConfig {
   skip_download_dialog_default: boolean;
 }
 
+export type SyncBackendKind = 'icloud' | 'folder' | 'webdav';
+
 export interface SyncConfig {
   enabled: boolean;
+  backend: SyncBackendKind;
+  folder_path: string;
+  webdav_url: string;
+  webdav_username: string;
+  /** Secret handle once saved. */
+  webdav_password: string;
+}
+
+export interface SyncStatus {
+  enabled: boolean;
+  backend: SyncBackendKind;
+  state: 'disabled' | 'idle' | 'pending' | 'error';
+  last_synced_at: string | null;
+  last_push_at: string | null;
+  last_pull_at: string | null;
+  last_error: string | null;
+  remote_updated_at: string | null;
+  remote_device_id: string | null;
+  conflict_count: number;
+}
+
+export interface SyncConflict {
+  id: string;
+  section: string;
+  item_id: string | null;
+  item_name: string | null;
+  field: string | null;
+  local: unknown;
+  remote: unknown;
+}
+
+export interface ValidationIssue {
+  path: string;
+  message: string;
+}
+
+export interface PolicyInfo {
+  path: string | null;
+  locked: string[];
+  allow: string[];
+  deny: string[];
+}
+
+export interface ValidationReport {
+  errors: ValidationIssue[];
+  warnings: ValidationIssue[];
+}
+
+export interface BundleConflict {
+  kind: "app" | "workspace" | "link_rule";
+  item: string;
+  existing: string;
+}
+
+export interface BundlePreview {
+  name: string;
+  apps: string[];
+  workspace: string | null;
+  link_rules: number;
+  conflicts: BundleConflict[];
+}
+
+export interface BundleImport {
+  app_ids: string[];
+  workspace_id: string | null;
+}
+
+export type ImportSource = "franz" | "ferdium" | "rambox";
+
+export interface DetectedSource {
+  source: ImportSource;
+  path: string;
+}
+
+export interface ImportItem {
+  app: AppConfig;
+  existing: string | null;
+  notes: string[];
+}
+
+export interface ImportPlan {
+  source: ImportSource;
+  path: string;
+  items: ImportItem[];
+  warnings: string[];
+}
+
+export interface SnapshotInfo {
+  id: string;
+  created_at: string;
+  reason: string;
+}
+
+export interface ConfigChange {
+  path: string;
+  before: unknown;
+  after: unknown;
 }
 
 export interface OrblyConfig {